@@ -1,4 +1,4 @@
-use anyhow::{Context, Error, Result};
+use anyhow::{bail, Context, Error, Result};
 use atty::Stream;
 use std::{
     fs::File,
@@ -10,9 +10,17 @@ use svanill_vault_cli::config::Config;
 use svanill_vault_cli::utils::gen_random_filename;
 use svanill_vault_cli::{
     commands::pull::sanitize_possible_filename,
-    sdk::{answer_challenge, delete, ls, request_challenge, request_upload_url, retrieve, upload},
+    sdk::{
+        add_key, answer_challenge, change_challenge, confirm_totp, create_api_key, delete,
+        disable_totp, enable_totp, list_api_keys, list_keys, list_sessions, logout_everywhere, ls,
+        remove_api_key, remove_key, request_challenge, request_upload_url, retrieve,
+        revoke_session, upload, DEFAULT_KEY_ID,
+    },
+};
+use svanill_vault_openapi::{
+    ApiKey, CreateApiKeyRequest, ListSessionsResponseContentItemContent,
+    RetrieveListOfUserFilesResponseContentItemContent, UserKey,
 };
-use svanill_vault_openapi::RetrieveListOfUserFilesResponseContentItemContent;
 
 #[derive(Debug, StructOpt)]
 #[structopt(
@@ -35,6 +43,19 @@ struct Opt {
     /// Svanill vault answer to the challenge
     #[structopt(short, long)]
     answer: Option<String>,
+    /// Log in with the challenge of this key, instead of the first one whose answer is known
+    #[structopt(long = "key-id")]
+    key_id: Option<String>,
+    /// The code of the authenticator app, if TOTP is enabled. Asked interactively when missing
+    #[structopt(long = "totp")]
+    totp: Option<String>,
+    /// Authenticate with an API key instead of the challenge. It only gives access to the files
+    #[structopt(
+        long = "api-key",
+        env = "SVANILL_VAULT_API_KEY",
+        hide_env_values = true
+    )]
+    api_key: Option<String>,
     #[structopt(subcommand)]
     cmd: Command,
 }
@@ -75,6 +96,137 @@ enum Command {
         #[structopt(name = "file")]
         remote_name: String,
     },
+    /// Revoke every authorization token of the user, on every device
+    #[structopt(name = "logout")]
+    Logout {
+        /// Also forget the answers stored in the config file
+        #[structopt(long = "forget")]
+        forget: bool,
+    },
+    /// Change the challenge and its answer, e.g. after changing the svanill password.
+    /// The new pair is stored in the config file
+    #[structopt(name = "passwd")]
+    Passwd {
+        /// The new challenge, the new answer encrypted with svanill
+        #[structopt(long = "new-challenge")]
+        new_challenge: String,
+        /// The new answer
+        #[structopt(long = "new-answer")]
+        new_answer: String,
+        /// Also revoke every other session of the user
+        #[structopt(long = "revoke-other-sessions")]
+        revoke_other_sessions: bool,
+    },
+    /// List the active sessions of the user, or revoke one of them
+    #[structopt(name = "sessions")]
+    Sessions {
+        /// Revoke the session that has this id
+        #[structopt(long = "revoke", name = "session_id")]
+        revoke: Option<String>,
+    },
+    /// List the keys of the user (a challenge and its answer each), add or remove them
+    #[structopt(name = "keys")]
+    Keys {
+        #[structopt(subcommand)]
+        cmd: Option<KeysCommand>,
+    },
+    /// Enable or disable TOTP, a second factor required to log in besides the answer
+    #[structopt(name = "totp")]
+    Totp {
+        #[structopt(subcommand)]
+        cmd: TotpCommand,
+    },
+    /// List the API keys of the user, create or revoke them.
+    /// API keys give access to the files, e.g. from CI, without answering the challenge
+    #[structopt(name = "api-keys")]
+    ApiKeys {
+        #[structopt(subcommand)]
+        cmd: Option<ApiKeysCommand>,
+    },
+}
+
+impl Command {
+    /// Tell if the command only deals with the files, so that it can be run with an API key
+    fn is_files_command(&self) -> bool {
+        matches!(
+            self,
+            Command::List {} | Command::Pull { .. } | Command::Push { .. } | Command::Delete { .. }
+        )
+    }
+}
+
+#[derive(Debug, StructOpt)]
+enum KeysCommand {
+    #[structopt(name = "ls", alias = "list")]
+    List {},
+    /// Add a key, e.g. to log in from another device with a different password.
+    /// The new pair is stored in the config file
+    #[structopt(name = "add")]
+    Add {
+        /// The id of the new key
+        #[structopt(name = "key_id")]
+        key_id: String,
+        /// The challenge, the answer encrypted with svanill
+        #[structopt(long = "new-challenge")]
+        new_challenge: String,
+        /// The answer
+        #[structopt(long = "new-answer")]
+        new_answer: String,
+    },
+    #[structopt(name = "rm")]
+    Delete {
+        /// The id of the key to remove
+        #[structopt(name = "key_id")]
+        key_id: String,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+enum TotpCommand {
+    /// Generate a secret to add to an authenticator app. It's required to log in once confirmed
+    #[structopt(name = "enable")]
+    Enable {},
+    /// Confirm the secret with a code of the authenticator app
+    #[structopt(name = "confirm")]
+    Confirm {
+        /// The code of the authenticator app
+        #[structopt(name = "code")]
+        code: String,
+    },
+    #[structopt(name = "disable")]
+    Disable {
+        /// The code of the authenticator app
+        #[structopt(name = "code")]
+        code: String,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+enum ApiKeysCommand {
+    #[structopt(name = "ls", alias = "list")]
+    List {},
+    /// Create an API key. It is printed only once, store it somewhere safe
+    #[structopt(name = "add")]
+    Add {
+        /// A name to remember what the key is used for
+        #[structopt(name = "name")]
+        name: String,
+        /// What the key allows: read, write (implies read) or delete (implies write)
+        #[structopt(long = "scope", default_value = "read")]
+        scope: String,
+        /// Only give access to the files whose name starts with this prefix
+        #[structopt(long = "prefix")]
+        prefix: Option<String>,
+        /// When the key expires, as a unix timestamp
+        #[structopt(long = "expires-at")]
+        expires_at: Option<i64>,
+    },
+    #[structopt(name = "rm")]
+    Delete {
+        /// The id of the API key to revoke
+        #[structopt(name = "id")]
+        id: String,
+    },
 }
 
 fn output_files_list(opt: &Opt, v: Vec<RetrieveListOfUserFilesResponseContentItemContent>) {
@@ -95,12 +247,64 @@ fn output_files_list(opt: &Opt, v: Vec<RetrieveListOfUserFilesResponseContentIte
         }
         print!(" | {}", f.filename);
         if opt.verbose {
-            print!(" | {}", f.url);
+            print!(" | {}", f.url.as_deref().unwrap_or_default());
+        }
+        println!();
+    }
+}
+
+fn output_sessions_list(v: Vec<ListSessionsResponseContentItemContent>) {
+    println!("  | Session id                                                       |   Created at |    Last seen | Ip              | User agent");
+
+    for s in v.iter() {
+        print!("{}", if s.current { "*" } else { " " });
+        print!(" | {:<64}", s.id);
+        print!(" | {:>12}", s.created_at);
+        print!(" | {:>12}", s.last_seen_at);
+        print!(" | {:<15}", s.ip.as_deref().unwrap_or("-"));
+        print!(" | {}", s.user_agent.as_deref().unwrap_or("-"));
+        println!();
+    }
+}
+
+fn output_keys_list(v: Vec<UserKey>) {
+    for k in v.iter() {
+        println!("{}", k.key_id);
+    }
+}
+
+fn output_api_keys_list(v: Vec<ApiKey>) {
+    println!("Id               | Scope  |   Created at |   Expires at | Prefix     | Name");
+
+    for k in v.iter() {
+        print!("{:<16}", k.id);
+        print!(" | {:<6}", k.scope);
+        print!(" | {:>12}", k.created_at);
+        match k.expires_at {
+            Some(expires_at) => print!(" | {expires_at:>12}"),
+            None => print!(" | {:>12}", "-"),
         }
+        print!(" | {:<10}", k.prefix.as_deref().unwrap_or("-"));
+        print!(" | {}", k.name);
         println!();
     }
 }
 
+/// Ask the code of the authenticator app, if there's someone to answer
+fn prompt_totp_code() -> Result<String> {
+    if !atty::is(Stream::Stdin) {
+        bail!("a TOTP code is required, pass it with --totp");
+    }
+
+    eprint!("TOTP code: ");
+    io::stderr().flush()?;
+
+    let mut code = String::new();
+    io::stdin().read_line(&mut code)?;
+
+    Ok(code.trim().to_owned())
+}
+
 fn main() -> Result<()> {
     let mut opt = Opt::from_args();
 
@@ -145,7 +349,7 @@ fn main() -> Result<()> {
         conf_updated = true;
     }
 
-    if conf.username.is_empty() {
+    if conf.username.is_empty() && opt.api_key.is_none() {
         eprintln!("Error: username is missing");
         std::process::exit(1);
     }
@@ -162,24 +366,58 @@ fn main() -> Result<()> {
         conf.base_url = format!("http://{}", conf.base_url);
     }
 
-    let challenge = request_challenge(&conf)?;
+    // The key used to log in, its challenge and answer. None when using an API key
+    let login = if let Some(api_key) = &opt.api_key {
+        if !opt.cmd.is_files_command() {
+            eprintln!("Error: an API key can only be used to ls, pull, push and rm files");
+            std::process::exit(1);
+        }
 
-    if opt.answer.is_none() && !conf.challenges.contains_key(&challenge) {
-        eprintln!("Cannot authenticate, missing answer to the server's challenge.");
-        eprintln!("Decrypt the challenge to get the answer:");
-        eprintln!("  svanill -i <(echo {challenge}) dec");
-        eprintln!();
-        eprintln!("Then re-run svanill-vault-cli passing --username and --answer. They will be stored in a config file so you won't have to provide them again");
-        std::process::exit(1);
-    }
+        conf.token = api_key.clone();
+        None
+    } else {
+        let keys = request_challenge(&conf, opt.key_id.as_deref())?;
 
-    if let Some(x) = &opt.answer {
-        conf.challenges.insert(challenge.clone(), x.clone());
-        conf_updated = true;
-    }
+        // Use the first key whose answer we know, or the first one if we are given the answer
+        let maybe_key = match opt.answer {
+            Some(_) => keys.first(),
+            None => keys
+                .iter()
+                .find(|k| conf.challenges.contains_key(&k.challenge)),
+        };
+
+        let UserKey { key_id, challenge } = match maybe_key {
+            Some(key) => key.clone(),
+            None => {
+                eprintln!("Cannot authenticate, missing answer to the server's challenge.");
+                eprintln!("Decrypt the challenge to get the answer:");
+                for key in keys.iter() {
+                    eprintln!(
+                        "  svanill -i <(echo {}) dec  # key {}",
+                        key.challenge, key.key_id
+                    );
+                }
+                eprintln!();
+                eprintln!("Then re-run svanill-vault-cli passing --username and --answer (and --key-id, if the key is not the default one). They will be stored in a config file so you won't have to provide them again");
+                std::process::exit(1);
+            }
+        };
+
+        if let Some(x) = &opt.answer {
+            conf.challenges.insert(challenge.clone(), x.clone());
+            conf_updated = true;
+        }
+
+        let answer = conf.challenges.get(&challenge).unwrap().clone();
+        conf.token = match answer_challenge(&conf, &key_id, &answer, opt.totp.as_deref()) {
+            Err(e) if e.is_totp_required() && opt.totp.is_none() => {
+                answer_challenge(&conf, &key_id, &answer, Some(&prompt_totp_code()?))?
+            }
+            result => result?,
+        };
 
-    let answer = conf.challenges.get(&challenge).unwrap();
-    conf.token = answer_challenge(&conf, answer)?;
+        Some((key_id, challenge, answer))
+    };
 
     if conf_updated && opt.store_conf {
         confy::store(cli_name, cli_name, &conf)?;
@@ -220,7 +458,11 @@ fn main() -> Result<()> {
                 )
             };
 
-            let mut reader = retrieve(&f.url)?;
+            let url = f
+                .url
+                .as_deref()
+                .ok_or_else(|| Error::msg(String::from("remote file has no url")))?;
+            let mut reader = retrieve(url)?;
             std::io::copy(&mut reader, &mut handle)?;
         }
         Command::Push {
@@ -267,6 +509,111 @@ fn main() -> Result<()> {
 
             println!("Successfully pushed file, using as remote name \"{remote_name}\"");
         }
+        Command::Logout { forget } => {
+            logout_everywhere(&conf)?;
+
+            if forget {
+                conf.challenges.clear();
+                confy::store(cli_name, cli_name, &conf)?;
+            }
+
+            println!("Success: logged out from every session");
+        }
+        Command::Passwd {
+            new_challenge,
+            new_answer,
+            revoke_other_sessions,
+        } => {
+            let (key_id, challenge, answer) = login.expect("logged in with a key");
+
+            change_challenge(
+                &conf,
+                &key_id,
+                &answer,
+                &new_challenge,
+                &new_answer,
+                revoke_other_sessions,
+            )?;
+
+            conf.challenges.remove(&challenge);
+            conf.challenges.insert(new_challenge, new_answer);
+            confy::store(cli_name, cli_name, &conf)?;
+
+            println!("Success: the challenge has been changed");
+        }
+        Command::Sessions { revoke } => match revoke {
+            Some(session_id) => {
+                revoke_session(&conf, &session_id)?;
+                println!("Success: revoked session \"{session_id}\"");
+            }
+            None => output_sessions_list(list_sessions(&conf)?),
+        },
+        Command::Keys { cmd } => match cmd {
+            None | Some(KeysCommand::List {}) => output_keys_list(list_keys(&conf)?),
+            Some(KeysCommand::Add {
+                key_id,
+                new_challenge,
+                new_answer,
+            }) => {
+                add_key(&conf, &key_id, &new_challenge, &new_answer)?;
+
+                conf.challenges.insert(new_challenge, new_answer);
+                confy::store(cli_name, cli_name, &conf)?;
+
+                println!("Success: added key \"{key_id}\"");
+            }
+            Some(KeysCommand::Delete { key_id }) => {
+                if key_id == DEFAULT_KEY_ID {
+                    eprintln!("Error: the default key can't be removed, only changed with passwd");
+                    std::process::exit(1);
+                }
+
+                remove_key(&conf, &key_id)?;
+                println!("Success: removed key \"{key_id}\"");
+            }
+        },
+        Command::Totp { cmd } => match cmd {
+            TotpCommand::Enable {} => {
+                let totp = enable_totp(&conf)?;
+
+                println!("Add this secret to your authenticator app: {}", totp.secret);
+                println!("or scan a QR code of: {}", totp.otpauth_uri);
+                println!("Then confirm it with: svanill-vault-cli totp confirm <code>");
+            }
+            TotpCommand::Confirm { code } => {
+                confirm_totp(&conf, &code)?;
+                println!("Success: TOTP enabled, from now on logging in requires a code");
+            }
+            TotpCommand::Disable { code } => {
+                disable_totp(&conf, &code)?;
+                println!("Success: TOTP disabled");
+            }
+        },
+        Command::ApiKeys { cmd } => match cmd {
+            None | Some(ApiKeysCommand::List {}) => output_api_keys_list(list_api_keys(&conf)?),
+            Some(ApiKeysCommand::Add {
+                name,
+                scope,
+                prefix,
+                expires_at,
+            }) => {
+                let mut request = CreateApiKeyRequest::new(name, scope);
+                request.prefix = prefix;
+                request.expires_at = expires_at;
+
+                let api_key = create_api_key(&conf, &request)?;
+
+                eprintln!(
+                    "Success: created API key \"{}\", it won't be shown again",
+                    api_key.id
+                );
+                println!("{}", api_key.key.unwrap_or_default());
+            }
+            Some(ApiKeysCommand::Delete { id }) => {
+                remove_api_key(&conf, &id)?;
+                println!("Success: revoked API key \"{id}\"");
+            }
+        },
     };
 
     Ok(())
@@ -0,0 +1,69 @@
+use crate::config::Config;
+use crate::sdk::response_error::SdkError;
+use svanill_vault_openapi::{
+    ApiKey, CreateApiKeyRequest, CreateApiKeyResponse, ListApiKeysResponse, RemoveApiKeyResponse,
+};
+
+/// Retrieve the user's API keys, oldest first
+pub fn list_api_keys(conf: &Config) -> Result<Vec<ApiKey>, SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/users/me/api-keys", conf.base_url);
+    let res = client.get(url).bearer_auth(&conf.token).send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<ListApiKeysResponse> = serde_json::from_str(&content).ok();
+
+        if let Some(entity) = opt_entity {
+            return Ok(entity.content.into_iter().map(|x| *x.content).collect());
+        }
+    };
+
+    vault_error!(status, content)
+}
+
+/// Create an API key. The returned key is the only time it can be read
+pub fn create_api_key(conf: &Config, request: &CreateApiKeyRequest) -> Result<ApiKey, SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/users/me/api-keys", conf.base_url);
+    let res = client
+        .post(url)
+        .bearer_auth(&conf.token)
+        .json(request)
+        .send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<CreateApiKeyResponse> = serde_json::from_str(&content).ok();
+
+        if let Some(entity) = opt_entity {
+            return Ok(*entity.content);
+        }
+    };
+
+    vault_error!(status, content)
+}
+
+/// Revoke one of the user's API keys
+pub fn remove_api_key(conf: &Config, id: &str) -> Result<(), SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/users/me/api-keys/{}", conf.base_url, id);
+    let res = client.delete(url).bearer_auth(&conf.token).send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<RemoveApiKeyResponse> = serde_json::from_str(&content).ok();
+
+        if opt_entity.is_some() {
+            return Ok(());
+        }
+    };
+
+    vault_error!(status, content)
+}
@@ -2,14 +2,24 @@ use crate::config::Config;
 use crate::sdk::response_error::SdkError;
 use svanill_vault_openapi::{
     AnswerUserChallengeRequest, AnswerUserChallengeResponse, AskForTheChallengeResponse,
+    ChangeChallengeRequest, ChangeChallengeResponse, ChangeChallengeResponseContent,
+    ListSessionsResponse, ListSessionsResponseContentItemContent, LogoutResponse, UserKey,
 };
 
-pub fn request_challenge(conf: &Config) -> Result<String, SdkError> {
+/// Id of the key that every user has
+pub const DEFAULT_KEY_ID: &str = "default";
+
+/// Retrieve the challenge of the user's key, or the challenges of every key
+/// of the user if `key_id` is None (the default key first)
+pub fn request_challenge(conf: &Config, key_id: Option<&str>) -> Result<Vec<UserKey>, SdkError> {
     let client = reqwest::blocking::Client::new();
-    let url = format!(
+    let mut url = format!(
         "{}/auth/request-challenge?username={}",
         conf.base_url, conf.username
     );
+    if let Some(key_id) = key_id {
+        url.push_str(&format!("&key_id={key_id}"));
+    }
     let res = client.get(url).send()?;
 
     let status = res.status();
@@ -19,32 +29,151 @@ pub fn request_challenge(conf: &Config) -> Result<String, SdkError> {
         let opt_entity: Option<AskForTheChallengeResponse> = serde_json::from_str(&content).ok();
 
         if let Some(entity) = opt_entity {
-            return Ok(entity.content.challenge);
+            let content = *entity.content;
+
+            // servers that don't support multiple keys only return the challenge
+            return Ok(content.challenges.unwrap_or_else(|| {
+                vec![UserKey::new(
+                    content.key_id.unwrap_or_else(|| DEFAULT_KEY_ID.to_owned()),
+                    content.challenge,
+                )]
+            }));
         }
     };
 
     vault_error!(status, content)
 }
 
-pub fn answer_challenge(conf: &Config, answer: &str) -> Result<String, SdkError> {
+/// Answer the challenge of a key, with a code of the authenticator app if the user enabled TOTP
+pub fn answer_challenge(
+    conf: &Config,
+    key_id: &str,
+    answer: &str,
+    totp_code: Option<&str>,
+) -> Result<String, SdkError> {
     let client = reqwest::blocking::Client::new();
     let url = format!("{}/auth/answer-challenge", conf.base_url);
+
+    let mut payload = AnswerUserChallengeRequest::new(conf.username.to_owned(), answer.to_owned());
+    if key_id != DEFAULT_KEY_ID {
+        payload.key_id = Some(key_id.to_owned());
+    }
+    payload.totp_code = totp_code.map(str::to_owned);
+
+    let res = client.post(url).json(&payload).send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<AnswerUserChallengeResponse> = serde_json::from_str(&content).ok();
+
+        if let Some(entity) = opt_entity {
+            return Ok(entity.content.token);
+        }
+    };
+
+    vault_error!(status, content)
+}
+
+/// Revoke every token of the user, on every device
+pub fn logout_everywhere(conf: &Config) -> Result<(), SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/auth/sessions", conf.base_url);
+    let res = client.delete(url).bearer_auth(&conf.token).send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<LogoutResponse> = serde_json::from_str(&content).ok();
+
+        if opt_entity.is_some() {
+            return Ok(());
+        }
+    };
+
+    vault_error!(status, content)
+}
+
+/// Retrieve the user's active sessions
+pub fn list_sessions(
+    conf: &Config,
+) -> Result<Vec<ListSessionsResponseContentItemContent>, SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/auth/sessions", conf.base_url);
+    let res = client.get(url).bearer_auth(&conf.token).send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<ListSessionsResponse> = serde_json::from_str(&content).ok();
+
+        if let Some(entity) = opt_entity {
+            return Ok(entity.content.into_iter().map(|x| *x.content).collect());
+        }
+    };
+
+    vault_error!(status, content)
+}
+
+/// Revoke a single session of the user
+pub fn revoke_session(conf: &Config, session_id: &str) -> Result<(), SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/auth/sessions/{}", conf.base_url, session_id);
+    let res = client.delete(url).bearer_auth(&conf.token).send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<LogoutResponse> = serde_json::from_str(&content).ok();
+
+        if opt_entity.is_some() {
+            return Ok(());
+        }
+    };
+
+    vault_error!(status, content)
+}
+
+/// Replace the challenge and answer of one of the user's keys
+pub fn change_challenge(
+    conf: &Config,
+    key_id: &str,
+    answer: &str,
+    new_challenge: &str,
+    new_answer: &str,
+    revoke_other_sessions: bool,
+) -> Result<ChangeChallengeResponseContent, SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/auth/change-challenge", conf.base_url);
+
+    let mut payload = ChangeChallengeRequest::new(
+        answer.to_owned(),
+        new_challenge.to_owned(),
+        new_answer.to_owned(),
+    );
+    payload.revoke_other_sessions = Some(revoke_other_sessions);
+    if key_id != DEFAULT_KEY_ID {
+        payload.key_id = Some(key_id.to_owned());
+    }
+
     let res = client
         .post(url)
-        .json(&AnswerUserChallengeRequest::new(
-            conf.username.to_owned(),
-            answer.to_owned(),
-        ))
+        .bearer_auth(&conf.token)
+        .json(&payload)
         .send()?;
 
     let status = res.status();
     let content = res.text()?;
 
     if status.is_success() {
-        let opt_entity: Option<AnswerUserChallengeResponse> = serde_json::from_str(&content).ok();
+        let opt_entity: Option<ChangeChallengeResponse> = serde_json::from_str(&content).ok();
 
         if let Some(entity) = opt_entity {
-            return Ok(entity.content.token);
+            return Ok(*entity.content);
         }
     };
 
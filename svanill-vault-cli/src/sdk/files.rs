@@ -1,7 +1,7 @@
 use crate::config::Config;
 use crate::sdk::response_error::SdkError;
 use md5::{Digest, Md5};
-use std::{collections::HashMap, io::Read};
+use std::io::Read;
 use svanill_vault_openapi::{
     HateoasFileUploadUrl, RemoveFileResponse, RequestUploadUrlRequestBody,
     RequestUploadUrlResponse, RequestUploadUrlResponseLinks,
@@ -99,21 +99,17 @@ pub fn upload(
 
 pub fn delete(conf: &Config, filename: &str) -> Result<(), SdkError> {
     let client = reqwest::blocking::Client::new();
-    let url = format!("{}/files/", conf.base_url);
-
-    // Right now the server expect the filename as JSON body,
-    // in future it will expect it as query param.
-    // We provide both.
+    let mut req = client
+        .delete(format!("{}/files/", conf.base_url))
+        .bearer_auth(&conf.token)
+        .build()?;
 
-    let mut request_body = HashMap::new();
-    request_body.insert("filename", filename);
+    // the filename is a single, percent-encoded, path segment
+    if let Ok(mut segments) = req.url_mut().path_segments_mut() {
+        segments.pop_if_empty().push(filename);
+    }
 
-    let res = client
-        .delete(url)
-        .bearer_auth(&conf.token)
-        .query(&[("filename", filename)])
-        .json(&request_body)
-        .send()?;
+    let res = client.execute(req)?;
 
     let status = res.status();
     let content = res.text()?;
@@ -0,0 +1,78 @@
+use crate::config::Config;
+use crate::sdk::response_error::SdkError;
+use svanill_vault_openapi::{
+    AddUserKeyRequest, AddUserKeyResponse, ListUserKeysResponse, RemoveUserKeyResponse, UserKey,
+};
+
+/// Retrieve the user's keys, the default one first
+pub fn list_keys(conf: &Config) -> Result<Vec<UserKey>, SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/users/me/keys", conf.base_url);
+    let res = client.get(url).bearer_auth(&conf.token).send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<ListUserKeysResponse> = serde_json::from_str(&content).ok();
+
+        if let Some(entity) = opt_entity {
+            return Ok(entity.content.into_iter().map(|x| *x.content).collect());
+        }
+    };
+
+    vault_error!(status, content)
+}
+
+/// Add a challenge and its answer that can be used to log in, besides the other keys
+pub fn add_key(
+    conf: &Config,
+    key_id: &str,
+    challenge: &str,
+    answer: &str,
+) -> Result<UserKey, SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/users/me/keys", conf.base_url);
+    let res = client
+        .post(url)
+        .bearer_auth(&conf.token)
+        .json(&AddUserKeyRequest::new(
+            key_id.to_owned(),
+            challenge.to_owned(),
+            answer.to_owned(),
+        ))
+        .send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<AddUserKeyResponse> = serde_json::from_str(&content).ok();
+
+        if let Some(entity) = opt_entity {
+            return Ok(*entity.content);
+        }
+    };
+
+    vault_error!(status, content)
+}
+
+/// Remove one of the user's keys
+pub fn remove_key(conf: &Config, key_id: &str) -> Result<(), SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/users/me/keys/{}", conf.base_url, key_id);
+    let res = client.delete(url).bearer_auth(&conf.token).send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<RemoveUserKeyResponse> = serde_json::from_str(&content).ok();
+
+        if opt_entity.is_some() {
+            return Ok(());
+        }
+    };
+
+    vault_error!(status, content)
+}
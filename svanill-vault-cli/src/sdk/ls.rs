@@ -8,18 +8,28 @@ pub fn ls(
     conf: &Config,
 ) -> Result<Vec<RetrieveListOfUserFilesResponseContentItemContent>, SdkError> {
     let client = reqwest::blocking::Client::new();
-    let url = format!("{}/files/", conf.base_url);
-    let res = client.get(url).bearer_auth(&conf.token).send()?;
+    let mut url = format!("{}/files/", conf.base_url);
+    let mut files = Vec::new();
 
-    let status = res.status();
-    let content = res.text()?;
+    // the files are listed one page at a time, follow the `next` links
+    loop {
+        let res = client.get(&url).bearer_auth(&conf.token).send()?;
 
-    if status.is_success() {
-        let opt_entity: Option<RetrieveListOfUserFilesResponse> =
-            serde_json::from_str(&content).ok();
+        let status = res.status();
+        let content = res.text()?;
 
-        if let Some(entity) = opt_entity {
-            return Ok(entity
+        let opt_entity: Option<RetrieveListOfUserFilesResponse> = if status.is_success() {
+            serde_json::from_str(&content).ok()
+        } else {
+            None
+        };
+
+        let Some(entity) = opt_entity else {
+            return vault_error!(status, content);
+        };
+
+        files.extend(
+            entity
                 .content
                 .into_iter()
                 .map(|mut c| {
@@ -31,10 +41,12 @@ pub fn ls(
                     *c.content
                 })
                 // ignore keys containing `/` (not pushed by svanill-cli)
-                .filter(|c| !c.filename.contains('/'))
-                .collect());
-        }
-    };
+                .filter(|c| !c.filename.contains('/')),
+        );
 
-    vault_error!(status, content)
+        match entity.links {
+            Some(links) => url = links.next.href,
+            None => return Ok(files),
+        }
+    }
 }
@@ -10,14 +10,32 @@ macro_rules! vault_error {
     };
 }
 
+mod api_keys;
+pub use api_keys::create_api_key;
+pub use api_keys::list_api_keys;
+pub use api_keys::remove_api_key;
 mod ls;
 pub use ls::ls;
 mod auth;
 pub use auth::answer_challenge;
+pub use auth::change_challenge;
+pub use auth::list_sessions;
+pub use auth::logout_everywhere;
 pub use auth::request_challenge;
+pub use auth::revoke_session;
+pub use auth::DEFAULT_KEY_ID;
 mod files;
 pub use files::delete;
 pub use files::request_upload_url;
 pub use files::retrieve;
 pub use files::upload;
+mod keys;
+pub use keys::add_key;
+pub use keys::list_keys;
+pub use keys::remove_key;
+mod totp;
+pub use totp::confirm_totp;
+pub use totp::disable_totp;
+pub use totp::enable_totp;
 mod response_error;
+pub use response_error::SdkError;
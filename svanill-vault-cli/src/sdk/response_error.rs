@@ -28,6 +28,16 @@ pub enum SdkError {
     ChecksumMismatch { local: String, remote: String },
 }
 
+/// Error code of the server when the user enabled TOTP but no code was given
+const TOTP_REQUIRED: i32 = 1012;
+
+impl SdkError {
+    /// Tell if the login failed only because a TOTP code is required
+    pub fn is_totp_required(&self) -> bool {
+        matches!(self, SdkError::ParsedError { code, .. } if *code == TOTP_REQUIRED)
+    }
+}
+
 impl From<ResponseError> for SdkError {
     fn from(res_error: ResponseError) -> Self {
         SdkError::ParsedError {
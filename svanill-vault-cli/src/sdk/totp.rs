@@ -0,0 +1,74 @@
+use crate::config::Config;
+use crate::sdk::response_error::SdkError;
+use svanill_vault_openapi::{
+    ConfirmTotpRequest, ConfirmTotpResponse, DisableTotpRequest, DisableTotpResponse,
+    EnableTotpResponse, EnableTotpResponseContent,
+};
+
+/// Generate a TOTP secret for the user, that must be confirmed before it's required to log in
+pub fn enable_totp(conf: &Config) -> Result<EnableTotpResponseContent, SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/users/me/totp", conf.base_url);
+    let res = client.post(url).bearer_auth(&conf.token).send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<EnableTotpResponse> = serde_json::from_str(&content).ok();
+
+        if let Some(entity) = opt_entity {
+            return Ok(*entity.content);
+        }
+    };
+
+    vault_error!(status, content)
+}
+
+/// Confirm the pending TOTP secret with a code of the authenticator app
+pub fn confirm_totp(conf: &Config, code: &str) -> Result<(), SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/users/me/totp/confirm", conf.base_url);
+    let res = client
+        .post(url)
+        .bearer_auth(&conf.token)
+        .json(&ConfirmTotpRequest::new(code.to_owned()))
+        .send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<ConfirmTotpResponse> = serde_json::from_str(&content).ok();
+
+        if opt_entity.is_some() {
+            return Ok(());
+        }
+    };
+
+    vault_error!(status, content)
+}
+
+/// Remove the user's TOTP secret, so that logging in requires just the answer
+pub fn disable_totp(conf: &Config, code: &str) -> Result<(), SdkError> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/users/me/totp", conf.base_url);
+    let res = client
+        .delete(url)
+        .bearer_auth(&conf.token)
+        .json(&DisableTotpRequest::new(code.to_owned()))
+        .send()?;
+
+    let status = res.status();
+    let content = res.text()?;
+
+    if status.is_success() {
+        let opt_entity: Option<DisableTotpResponse> = serde_json::from_str(&content).ok();
+
+        if opt_entity.is_some() {
+            return Ok(());
+        }
+    };
+
+    vault_error!(status, content)
+}
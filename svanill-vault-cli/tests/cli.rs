@@ -92,7 +92,7 @@ fn it_delete_files() {
     let (m1, m2) = mock_successful_authentication_requests(&mut server);
 
     let m3 = server
-        .mock("DELETE", "/files/?filename=some-file-to-delete")
+        .mock("DELETE", "/files/some-file-to-delete")
         .with_status(200)
         .with_header("content-type", "application/json")
         .with_body(r#"{"status":200}"#)
@@ -119,6 +119,43 @@ fn it_delete_files() {
         .stdout("Success: deleted file \"some-file-to-delete\"\n");
 }
 
+#[test]
+fn it_encode_the_name_of_the_files_to_delete() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    let mut server = mockito::Server::new();
+    let base_url = &server.url();
+
+    let (m1, m2) = mock_successful_authentication_requests(&mut server);
+
+    let m3 = server
+        .mock("DELETE", "/files/a%20file&more%23%3F")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"status":200}"#)
+        .create();
+
+    let assert = cmd
+        .args([
+            "-h",
+            base_url,
+            "-u",
+            "test_user",
+            "--answer",
+            "test answer",
+            "rm",
+            "a file&more#?",
+        ])
+        .assert();
+
+    m1.assert();
+    m2.assert();
+    m3.assert();
+    assert
+        .success()
+        .stdout("Success: deleted file \"a file&more#?\"\n");
+}
+
 #[test]
 fn it_pull_remote_file_output_to_stdout() {
     let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
@@ -232,6 +269,737 @@ fn it_push_content_from_stdin_to_remote_file() {
         .stdout("Successfully pushed file, using as remote name \"some-remote-filename\"\n");
 }
 
+#[test]
+fn it_logout_everywhere() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    let mut server = mockito::Server::new();
+    let base_url = &server.url();
+
+    let (m1, m2) = mock_successful_authentication_requests(&mut server);
+
+    let m3 = server
+        .mock("DELETE", "/auth/sessions")
+        .match_header("authorization", "Bearer a-secure-token")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "links":{
+                    "request_auth_challenge":{
+                        "href":format!("{base_url}/auth/request-challenge"),
+                        "rel":"auth"
+                    }
+                },
+                "status":200
+            })
+            .to_string(),
+        )
+        .create();
+
+    let assert = cmd
+        .args([
+            "-h",
+            base_url,
+            "-u",
+            "test_user",
+            "--answer",
+            "test answer",
+            "logout",
+        ])
+        .assert();
+
+    m1.assert();
+    m2.assert();
+    m3.assert();
+    assert
+        .success()
+        .stdout("Success: logged out from every session\n");
+}
+
+#[test]
+fn it_revoke_session() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    let mut server = mockito::Server::new();
+    let base_url = &server.url();
+
+    let (m1, m2) = mock_successful_authentication_requests(&mut server);
+
+    let m3 = server
+        .mock("DELETE", "/auth/sessions/some-session-id")
+        .match_header("authorization", "Bearer a-secure-token")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "links":{
+                    "request_auth_challenge":{
+                        "href":format!("{base_url}/auth/request-challenge"),
+                        "rel":"auth"
+                    }
+                },
+                "status":200
+            })
+            .to_string(),
+        )
+        .create();
+
+    let assert = cmd
+        .args([
+            "-h",
+            base_url,
+            "-u",
+            "test_user",
+            "--answer",
+            "test answer",
+            "sessions",
+            "--revoke",
+            "some-session-id",
+        ])
+        .assert();
+
+    m1.assert();
+    m2.assert();
+    m3.assert();
+    assert
+        .success()
+        .stdout("Success: revoked session \"some-session-id\"\n");
+}
+
+#[test]
+fn it_change_challenge() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    // keep the config file, that gets updated, away from the user's one
+    let config_home = std::env::temp_dir().join(format!(
+        "svanill-vault-cli-test-passwd-{}",
+        std::process::id()
+    ));
+
+    let mut server = mockito::Server::new();
+    let base_url = &server.url();
+
+    let (m1, m2) = mock_successful_authentication_requests(&mut server);
+
+    let m3 = server
+        .mock("POST", "/auth/change-challenge")
+        .match_header("authorization", "Bearer a-secure-token")
+        .match_body(Matcher::Json(json!({
+            "answer": "test answer",
+            "new_challenge": "new challenge",
+            "new_answer": "new answer",
+            "revoke_other_sessions": false
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "content": {"challenge": "new challenge"},
+                "links":{
+                    "files_list":{
+                        "href":format!("{base_url}/files/"),
+                        "rel":"file"
+                    }
+                },
+                "status":200
+            })
+            .to_string(),
+        )
+        .create();
+
+    let assert = cmd
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("HOME", &config_home)
+        .args([
+            "-h",
+            base_url,
+            "-u",
+            "test_user",
+            "--answer",
+            "test answer",
+            "passwd",
+            "--new-challenge",
+            "new challenge",
+            "--new-answer",
+            "new answer",
+        ])
+        .assert();
+
+    m1.assert();
+    m2.assert();
+    m3.assert();
+    assert
+        .success()
+        .stdout("Success: the challenge has been changed\n");
+
+    let stored_conf =
+        std::fs::read_to_string(config_home.join("svanill-vault-cli/svanill-vault-cli.toml"))
+            .expect("the config file has not been stored");
+    std::fs::remove_dir_all(&config_home).unwrap();
+
+    assert!(stored_conf.contains("\"new challenge\" = \"new answer\""));
+    assert!(!stored_conf.contains("somechallenge"));
+}
+
+#[test]
+fn it_login_with_a_known_key_and_list_keys() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    // only the answer to the laptop key is known
+    let config_home = std::env::temp_dir().join(format!(
+        "svanill-vault-cli-test-keys-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(config_home.join("svanill-vault-cli")).unwrap();
+    std::fs::write(
+        config_home.join("svanill-vault-cli/svanill-vault-cli.toml"),
+        "version = 0.1\nbase_url = \"\"\nusername = \"test_user\"\n\n[challenges]\nlaptopchallenge = \"laptop answer\"\n",
+    )
+    .unwrap();
+
+    let mut server = mockito::Server::new();
+    let base_url = &server.url();
+
+    let m1 = server
+        .mock("GET", "/auth/request-challenge?username=test_user")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "content": {
+                    "challenge": "somechallenge",
+                    "key_id": "default",
+                    "challenges": [
+                        {"key_id": "default", "challenge": "somechallenge"},
+                        {"key_id": "laptop", "challenge": "laptopchallenge"}
+                    ]
+                },
+                "links": {
+                    "answer_auth_challenge": {
+                        "href": format!("{base_url}/auth/answer-challenge"),
+                        "rel": "auth"
+                    },
+                    "create_user": {
+                        "href": format!("{base_url}/users/"),
+                        "rel": "user"
+                    }
+                },
+                "status": 200
+            })
+            .to_string(),
+        )
+        .create();
+
+    let m2 = server
+        .mock("POST", "/auth/answer-challenge")
+        .match_body(Matcher::Json(json!({
+            "username": "test_user",
+            "answer": "laptop answer",
+            "key_id": "laptop"
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "content": {"token": "a-secure-token"},
+                "links": {
+                    "files_list": {
+                        "href": format!("{base_url}/files/"),
+                        "rel": "file"
+                    },
+                    "request_upload_url": {
+                        "href": format!("{base_url}/files/request-upload-url/"),
+                        "rel": "file"
+                    }
+                },
+                "status": 200
+            })
+            .to_string(),
+        )
+        .create();
+
+    let m3 = server
+        .mock("GET", "/users/me/keys")
+        .match_header("authorization", "Bearer a-secure-token")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "content": [
+                    {
+                        "content": {"key_id": "default", "challenge": "somechallenge"},
+                        "links": {}
+                    },
+                    {
+                        "content": {"key_id": "laptop", "challenge": "laptopchallenge"},
+                        "links": {
+                            "delete": {
+                                "href": format!("{base_url}/users/me/keys/laptop"),
+                                "rel": "user"
+                            }
+                        }
+                    }
+                ],
+                "status": 200
+            })
+            .to_string(),
+        )
+        .create();
+
+    let assert = cmd
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("HOME", &config_home)
+        .args(["-h", base_url, "keys"])
+        .assert();
+
+    std::fs::remove_dir_all(&config_home).unwrap();
+
+    m1.assert();
+    m2.assert();
+    m3.assert();
+    assert.success().stdout("default\nlaptop\n");
+}
+
+#[test]
+fn it_add_key() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    // keep the config file, that gets updated, away from the user's one
+    let config_home = std::env::temp_dir().join(format!(
+        "svanill-vault-cli-test-add-key-{}",
+        std::process::id()
+    ));
+
+    let mut server = mockito::Server::new();
+    let base_url = &server.url();
+
+    let (m1, m2) = mock_successful_authentication_requests(&mut server);
+
+    let m3 = server
+        .mock("POST", "/users/me/keys")
+        .match_header("authorization", "Bearer a-secure-token")
+        .match_body(Matcher::Json(json!({
+            "key_id": "phone",
+            "challenge": "phone challenge",
+            "answer": "phone answer"
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "content": {"key_id": "phone", "challenge": "phone challenge"},
+                "links": {
+                    "delete": {
+                        "href": format!("{base_url}/users/me/keys/phone"),
+                        "rel": "user"
+                    }
+                },
+                "status": 200
+            })
+            .to_string(),
+        )
+        .create();
+
+    let assert = cmd
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("HOME", &config_home)
+        .args([
+            "-h",
+            base_url,
+            "-u",
+            "test_user",
+            "--answer",
+            "test answer",
+            "keys",
+            "add",
+            "phone",
+            "--new-challenge",
+            "phone challenge",
+            "--new-answer",
+            "phone answer",
+        ])
+        .assert();
+
+    m1.assert();
+    m2.assert();
+    m3.assert();
+    assert.success().stdout("Success: added key \"phone\"\n");
+
+    let stored_conf =
+        std::fs::read_to_string(config_home.join("svanill-vault-cli/svanill-vault-cli.toml"))
+            .expect("the config file has not been stored");
+    std::fs::remove_dir_all(&config_home).unwrap();
+
+    assert!(stored_conf.contains("\"phone challenge\" = \"phone answer\""));
+    assert!(stored_conf.contains("somechallenge = \"test answer\""));
+}
+
+#[test]
+fn it_remove_key() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    let mut server = mockito::Server::new();
+    let base_url = &server.url();
+
+    let (m1, m2) = mock_successful_authentication_requests(&mut server);
+
+    let m3 = server
+        .mock("DELETE", "/users/me/keys/phone")
+        .match_header("authorization", "Bearer a-secure-token")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({"status": 200}).to_string())
+        .create();
+
+    let assert = cmd
+        .args([
+            "-h",
+            base_url,
+            "-u",
+            "test_user",
+            "--answer",
+            "test answer",
+            "keys",
+            "rm",
+            "phone",
+        ])
+        .assert();
+
+    m1.assert();
+    m2.assert();
+    m3.assert();
+    assert.success().stdout("Success: removed key \"phone\"\n");
+}
+
+#[test]
+fn it_list_remote_files_with_an_api_key() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    // no username in the config file, the API key is enough
+    let config_home = std::env::temp_dir().join(format!(
+        "svanill-vault-cli-test-api-key-{}",
+        std::process::id()
+    ));
+
+    let mut server = mockito::Server::new();
+    let base_url = &server.url();
+
+    let m1 = server
+        .mock("GET", "/files/")
+        .match_header("authorization", "Bearer svk_some-api-key")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({"content": [], "status": 200}).to_string())
+        .create();
+
+    let assert = cmd
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("HOME", &config_home)
+        .env("SVANILL_VAULT_API_KEY", "svk_some-api-key")
+        .args(["-h", base_url, "ls"])
+        .assert();
+
+    m1.assert();
+    assert.success().stdout("       Bytes | Filename\n");
+
+    let _ = std::fs::remove_dir_all(config_home);
+}
+
+#[test]
+fn it_list_remote_files_across_pages() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    let config_home = std::env::temp_dir().join(format!(
+        "svanill-vault-cli-test-pages-{}",
+        std::process::id()
+    ));
+
+    let mut server = mockito::Server::new();
+    let base_url = &server.url();
+
+    let file = |filename: &str, size: i32| {
+        json!({
+            "content": {
+                "checksum": "a9a1bdddeacc612db8b5c01a830af1c3",
+                "filename": filename,
+                "size": size,
+                "url": format!("{base_url}/imaginary/url/{filename}"),
+            },
+            "links": {
+                "delete": {"href": format!("{base_url}/files/"), "rel": "file"},
+                "read": {"href": format!("{base_url}/imaginary/url/{filename}"), "rel": "file"}
+            }
+        })
+    };
+
+    let m1 = server
+        .mock("GET", "/files/")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "content": [file("first-file", 123)],
+                "links": {
+                    "next": {"href": format!("{base_url}/files/?limit=1&cursor=abc"), "rel": "file"}
+                },
+                "status": 200
+            })
+            .to_string(),
+        )
+        .create();
+    let m2 = server
+        .mock("GET", "/files/?limit=1&cursor=abc")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({"content": [file("second-file", 45)], "status": 200}).to_string())
+        .create();
+
+    let assert = cmd
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("HOME", &config_home)
+        .env("SVANILL_VAULT_API_KEY", "svk_some-api-key")
+        .args(["-h", base_url, "ls"])
+        .assert();
+
+    m1.assert();
+    m2.assert();
+    assert.success().stdout(
+        r#"       Bytes | Filename
+         123 | first-file
+          45 | second-file
+"#,
+    );
+
+    let _ = std::fs::remove_dir_all(config_home);
+}
+
+#[test]
+fn it_refuse_to_use_an_api_key_for_the_account() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    let assert = cmd
+        .args([
+            "-h",
+            "localhost",
+            "--api-key",
+            "svk_some-api-key",
+            "sessions",
+        ])
+        .assert();
+
+    assert
+        .failure()
+        .code(1)
+        .stderr("Error: an API key can only be used to ls, pull, push and rm files\n");
+}
+
+#[test]
+fn it_add_api_key() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    let mut server = mockito::Server::new();
+    let base_url = &server.url();
+
+    let (m1, m2) = mock_successful_authentication_requests(&mut server);
+
+    let m3 = server
+        .mock("POST", "/users/me/api-keys")
+        .match_header("authorization", "Bearer a-secure-token")
+        .match_body(Matcher::Json(json!({
+            "name": "ci",
+            "scope": "write",
+            "prefix": "ci/"
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "content": {
+                    "id": "0123456789abcdef",
+                    "name": "ci",
+                    "scope": "write",
+                    "prefix": "ci/",
+                    "created_at": 1700000000,
+                    "key": "svk_0123456789abcdef_secret"
+                },
+                "links": {
+                    "delete": {
+                        "href": format!("{base_url}/users/me/api-keys/0123456789abcdef"),
+                        "rel": "user"
+                    }
+                },
+                "status": 200
+            })
+            .to_string(),
+        )
+        .create();
+
+    let assert = cmd
+        .args([
+            "-h",
+            base_url,
+            "-u",
+            "test_user",
+            "--answer",
+            "test answer",
+            "api-keys",
+            "add",
+            "ci",
+            "--scope",
+            "write",
+            "--prefix",
+            "ci/",
+        ])
+        .assert();
+
+    m1.assert();
+    m2.assert();
+    m3.assert();
+    assert
+        .success()
+        .stdout("svk_0123456789abcdef_secret\n")
+        .stderr("Success: created API key \"0123456789abcdef\", it won't be shown again\n");
+}
+
+#[test]
+fn it_login_with_a_totp_code() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let username = "test_user";
+
+    let mut server = mockito::Server::new();
+    let base_url = &server.url();
+
+    let (m1, m2) = mock_totp_authentication_requests(&mut server);
+    let m3 = mock_list_files_happy_path(&mut server, username);
+
+    let assert = cmd
+        .args([
+            "-h",
+            base_url,
+            "-u",
+            username,
+            "--answer",
+            "test answer",
+            "--totp",
+            "123456",
+            "ls",
+        ])
+        .assert();
+
+    m1.assert();
+    m2.assert();
+    m3.assert();
+    assert.success();
+}
+
+#[test]
+fn it_exit_with_error_if_a_totp_code_is_required() {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+
+    let mut server = mockito::Server::new();
+    let base_url = &server.url();
+
+    let (m1, _m2) = mock_totp_authentication_requests(&mut server);
+
+    let m3 = server
+        .mock("POST", "/auth/answer-challenge")
+        .match_body(Matcher::Json(json!({
+            "username": "test_user",
+            "answer": "test answer"
+        })))
+        .with_status(401)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "error": {"code": 1012, "message": "A TOTP code is required"},
+                "status": 401
+            })
+            .to_string(),
+        )
+        .create();
+
+    // stdin is not a terminal, so the code can't be asked
+    let assert = cmd
+        .args([
+            "-h",
+            base_url,
+            "-u",
+            "test_user",
+            "--answer",
+            "test answer",
+            "ls",
+        ])
+        .write_stdin("")
+        .assert();
+
+    m1.assert();
+    m3.assert();
+    assert
+        .failure()
+        .code(1)
+        .stderr("Error: a TOTP code is required, pass it with --totp\n");
+}
+
+fn mock_totp_authentication_requests(
+    server: &mut mockito::ServerGuard,
+) -> (mockito::Mock, mockito::Mock) {
+    let base_url = server.url();
+
+    let m1 = server
+        .mock("GET", "/auth/request-challenge?username=test_user")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "content": {"challenge": "somechallenge"},
+                "links": {
+                    "answer_auth_challenge": {
+                        "href": format!("{base_url}/auth/answer-challenge"),
+                        "rel": "auth"
+                    },
+                    "create_user": {
+                        "href": format!("{base_url}/users/"),
+                        "rel": "user"
+                    }
+                },
+                "status": 200
+            })
+            .to_string(),
+        )
+        .create();
+
+    let m2 = server
+        .mock("POST", "/auth/answer-challenge")
+        .match_body(Matcher::Json(json!({
+            "username": "test_user",
+            "answer": "test answer",
+            "totp_code": "123456"
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "content": {"token": "a-secure-token"},
+                "links": {
+                    "files_list": {
+                        "href": format!("{base_url}/files/"),
+                        "rel": "file"
+                    },
+                    "request_upload_url": {
+                        "href": format!("{base_url}/files/request-upload-url/"),
+                        "rel": "file"
+                    }
+                },
+                "status": 200
+            })
+            .to_string(),
+        )
+        .create();
+
+    (m1, m2)
+}
+
 fn mock_successful_authentication_requests(
     server: &mut mockito::ServerGuard,
 ) -> (mockito::Mock, mockito::Mock) {
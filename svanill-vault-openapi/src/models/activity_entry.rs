@@ -0,0 +1,43 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    /// when it happened, in seconds since the unix epoch
+    #[serde(rename = "created_at")]
+    pub created_at: i64,
+    /// what was done: login, request_upload_url, list_files, retrieve_file, retrieve_file_url, stat_file or remove_file
+    #[serde(rename = "action")]
+    pub action: String,
+    /// the file involved, if any
+    #[serde(rename = "filename", skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// the ip of the client
+    #[serde(rename = "ip", skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    /// the id of the request, also sent back in the X-Request-Id header
+    #[serde(rename = "request_id")]
+    pub request_id: String,
+    /// success or failure
+    #[serde(rename = "outcome")]
+    pub outcome: String,
+    /// the code of the error, when the outcome is a failure
+    #[serde(rename = "error_code", skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<i32>,
+}
+
+impl ActivityEntry {
+    pub fn new(
+        created_at: i64,
+        action: String,
+        request_id: String,
+        outcome: String,
+    ) -> ActivityEntry {
+        ActivityEntry {
+            created_at,
+            action,
+            request_id,
+            outcome,
+            filename: None,
+            ip: None,
+            error_code: None,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AddUserKeyRequest {
+    /// the id of the new key, e.g. the name of the device that will use it
+    #[serde(rename = "key_id")]
+    pub key_id: String,
+    /// the challenge of the new key
+    #[serde(rename = "challenge")]
+    pub challenge: String,
+    /// the answer to the challenge
+    #[serde(rename = "answer")]
+    pub answer: String,
+}
+
+impl AddUserKeyRequest {
+    pub fn new(key_id: String, challenge: String, answer: String) -> AddUserKeyRequest {
+        AddUserKeyRequest {
+            key_id,
+            challenge,
+            answer,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AddUserKeyResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+    #[serde(rename = "content")]
+    pub content: Box<super::UserKey>,
+    #[serde(rename = "links")]
+    pub links: Box<super::ListUserKeysResponseContentItemLinks>,
+}
+
+impl AddUserKeyResponse {
+    pub fn new(
+        status: i32,
+        content: super::UserKey,
+        links: super::ListUserKeysResponseContentItemLinks,
+    ) -> AddUserKeyResponse {
+        AddUserKeyResponse {
+            status,
+            content: Box::new(content),
+            links: Box::new(links),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AdminCreateUserRequest {
+    #[serde(rename = "username")]
+    pub username: String,
+    #[serde(rename = "challenge")]
+    pub challenge: String,
+    #[serde(rename = "answer")]
+    pub answer: String,
+    /// whether the new user can manage the other users, false if missing
+    #[serde(rename = "is_admin", skip_serializing_if = "Option::is_none")]
+    pub is_admin: Option<bool>,
+}
+
+impl AdminCreateUserRequest {
+    pub fn new(username: String, challenge: String, answer: String) -> AdminCreateUserRequest {
+        AdminCreateUserRequest {
+            username,
+            challenge,
+            answer,
+            is_admin: None,
+        }
+    }
+}
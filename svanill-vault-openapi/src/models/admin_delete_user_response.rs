@@ -0,0 +1,11 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AdminDeleteUserResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+}
+
+impl AdminDeleteUserResponse {
+    pub fn new(status: i32) -> AdminDeleteUserResponse {
+        AdminDeleteUserResponse { status }
+    }
+}
@@ -0,0 +1,12 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AdminDisableUserRequest {
+    /// why the user is disabled, told to them when they try to log in
+    #[serde(rename = "reason", skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl AdminDisableUserRequest {
+    pub fn new() -> AdminDisableUserRequest {
+        AdminDisableUserRequest { reason: None }
+    }
+}
@@ -0,0 +1,11 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AdminLogoutUserResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+}
+
+impl AdminLogoutUserResponse {
+    pub fn new(status: i32) -> AdminLogoutUserResponse {
+        AdminLogoutUserResponse { status }
+    }
+}
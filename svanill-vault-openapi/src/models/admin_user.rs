@@ -0,0 +1,33 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AdminUser {
+    #[serde(rename = "username")]
+    pub username: String,
+    /// whether the user can manage the other users
+    #[serde(rename = "is_admin")]
+    pub is_admin: bool,
+    /// when the user has been disabled, in seconds since the unix epoch, missing if enabled
+    #[serde(rename = "disabled_at", skip_serializing_if = "Option::is_none")]
+    pub disabled_at: Option<i64>,
+    /// why the user has been disabled, if told
+    #[serde(rename = "disabled_reason", skip_serializing_if = "Option::is_none")]
+    pub disabled_reason: Option<String>,
+    /// how many files the user stores, missing if the storage could not tell
+    #[serde(rename = "files", skip_serializing_if = "Option::is_none")]
+    pub files: Option<i64>,
+    /// the total size of the user's files, missing if the storage could not tell
+    #[serde(rename = "bytes", skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<i64>,
+}
+
+impl AdminUser {
+    pub fn new(username: String, is_admin: bool) -> AdminUser {
+        AdminUser {
+            username,
+            is_admin,
+            files: None,
+            bytes: None,
+            disabled_at: None,
+            disabled_reason: None,
+        }
+    }
+}
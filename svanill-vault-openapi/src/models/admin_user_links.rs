@@ -0,0 +1,25 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AdminUserLinks {
+    #[serde(rename = "delete")]
+    pub delete: Box<super::HateoasAdminUserDelete>,
+    #[serde(rename = "logout")]
+    pub logout: Box<super::HateoasAdminUserLogout>,
+    #[serde(rename = "disable", skip_serializing_if = "Option::is_none")]
+    pub disable: Option<Box<super::HateoasAdminUserDisable>>,
+    #[serde(rename = "enable", skip_serializing_if = "Option::is_none")]
+    pub enable: Option<Box<super::HateoasAdminUserEnable>>,
+}
+
+impl AdminUserLinks {
+    pub fn new(
+        delete: super::HateoasAdminUserDelete,
+        logout: super::HateoasAdminUserLogout,
+    ) -> AdminUserLinks {
+        AdminUserLinks {
+            delete: Box::new(delete),
+            logout: Box::new(logout),
+            disable: None,
+            enable: None,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AdminUserResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+    #[serde(rename = "content")]
+    pub content: Box<super::AdminUser>,
+    #[serde(rename = "links")]
+    pub links: Box<super::AdminUserLinks>,
+}
+
+impl AdminUserResponse {
+    pub fn new(
+        status: i32,
+        content: super::AdminUser,
+        links: super::AdminUserLinks,
+    ) -> AdminUserResponse {
+        AdminUserResponse {
+            status,
+            content: Box::new(content),
+            links: Box::new(links),
+        }
+    }
+}
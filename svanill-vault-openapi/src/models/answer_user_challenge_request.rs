@@ -6,10 +6,21 @@ pub struct AnswerUserChallengeRequest {
     /// the answer to the challenge
     #[serde(rename = "answer")]
     pub answer: String,
+    /// the key whose challenge has been answered, the default one if missing
+    #[serde(rename = "key_id", skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    /// the code generated by the authenticator app, required if the user enabled TOTP
+    #[serde(rename = "totp_code", skip_serializing_if = "Option::is_none")]
+    pub totp_code: Option<String>,
 }
 
 impl AnswerUserChallengeRequest {
     pub fn new(username: String, answer: String) -> AnswerUserChallengeRequest {
-        AnswerUserChallengeRequest { username, answer }
+        AnswerUserChallengeRequest {
+            username,
+            answer,
+            key_id: None,
+            totp_code: None,
+        }
     }
 }
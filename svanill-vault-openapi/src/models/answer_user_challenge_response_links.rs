@@ -4,6 +4,14 @@ pub struct AnswerUserChallengeResponseLinks {
     pub files_list: Box<super::HateoasFilesList>,
     #[serde(rename = "request_upload_url")]
     pub request_upload_url: Box<super::HateoasRequestUploadUrl>,
+    #[serde(rename = "refresh", skip_serializing_if = "Option::is_none")]
+    pub refresh: Option<Box<super::HateoasRefreshToken>>,
+    #[serde(rename = "logout", skip_serializing_if = "Option::is_none")]
+    pub logout: Option<Box<super::HateoasLogout>>,
+    #[serde(rename = "logout_everywhere", skip_serializing_if = "Option::is_none")]
+    pub logout_everywhere: Option<Box<super::HateoasLogoutEverywhere>>,
+    #[serde(rename = "change_challenge", skip_serializing_if = "Option::is_none")]
+    pub change_challenge: Option<Box<super::HateoasChangeChallenge>>,
 }
 
 impl AnswerUserChallengeResponseLinks {
@@ -14,6 +22,10 @@ impl AnswerUserChallengeResponseLinks {
         AnswerUserChallengeResponseLinks {
             files_list: Box::new(files_list),
             request_upload_url: Box::new(request_upload_url),
+            refresh: None,
+            logout: None,
+            logout_everywhere: None,
+            change_challenge: None,
         }
     }
 }
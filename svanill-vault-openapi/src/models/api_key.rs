@@ -0,0 +1,37 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ApiKey {
+    #[serde(rename = "id")]
+    pub id: String,
+    /// a label to recognize the key, e.g. the name of the CI job using it
+    #[serde(rename = "name")]
+    pub name: String,
+    /// what the key allows: read, write or delete (each scope includes the previous ones)
+    #[serde(rename = "scope")]
+    pub scope: String,
+    /// the key only gives access to the files whose name starts with it
+    #[serde(rename = "prefix", skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// when the key was created, in seconds since the unix epoch
+    #[serde(rename = "created_at")]
+    pub created_at: i64,
+    /// when the key expires, in seconds since the unix epoch
+    #[serde(rename = "expires_at", skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    /// the key itself, only returned when it's created
+    #[serde(rename = "key", skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
+impl ApiKey {
+    pub fn new(id: String, name: String, scope: String, created_at: i64) -> ApiKey {
+        ApiKey {
+            id,
+            name,
+            scope,
+            created_at,
+            prefix: None,
+            expires_at: None,
+            key: None,
+        }
+    }
+}
@@ -2,10 +2,20 @@
 pub struct AskForTheChallengeResponseContent {
     #[serde(rename = "challenge")]
     pub challenge: String,
+    /// the id of the key the challenge belongs to
+    #[serde(rename = "key_id", skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    /// the challenges of every key of the user (only the default one, with decoy challenges on), when no key was asked for
+    #[serde(rename = "challenges", skip_serializing_if = "Option::is_none")]
+    pub challenges: Option<Vec<super::UserKey>>,
 }
 
 impl AskForTheChallengeResponseContent {
     pub fn new(challenge: String) -> AskForTheChallengeResponseContent {
-        AskForTheChallengeResponseContent { challenge }
+        AskForTheChallengeResponseContent {
+            challenge,
+            key_id: None,
+            challenges: None,
+        }
     }
 }
@@ -0,0 +1,37 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ChangeChallengeRequest {
+    /// the current answer to the challenge
+    #[serde(rename = "answer")]
+    pub answer: String,
+    /// the challenge that will replace the current one
+    #[serde(rename = "new_challenge")]
+    pub new_challenge: String,
+    /// the answer to the new challenge
+    #[serde(rename = "new_answer")]
+    pub new_answer: String,
+    /// revoke every other session of the user, a new token is returned for the current one
+    #[serde(
+        rename = "revoke_other_sessions",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub revoke_other_sessions: Option<bool>,
+    /// the key whose challenge will be replaced, the default one if missing
+    #[serde(rename = "key_id", skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+}
+
+impl ChangeChallengeRequest {
+    pub fn new(
+        answer: String,
+        new_challenge: String,
+        new_answer: String,
+    ) -> ChangeChallengeRequest {
+        ChangeChallengeRequest {
+            answer,
+            new_challenge,
+            new_answer,
+            revoke_other_sessions: None,
+            key_id: None,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ChangeChallengeResponse {
+    #[serde(rename = "content")]
+    pub content: Box<super::ChangeChallengeResponseContent>,
+    #[serde(rename = "links")]
+    pub links: Box<super::ChangeChallengeResponseLinks>,
+    #[serde(rename = "status")]
+    pub status: i32,
+}
+
+impl ChangeChallengeResponse {
+    pub fn new(
+        content: super::ChangeChallengeResponseContent,
+        links: super::ChangeChallengeResponseLinks,
+        status: i32,
+    ) -> ChangeChallengeResponse {
+        ChangeChallengeResponse {
+            content: Box::new(content),
+            links: Box::new(links),
+            status,
+        }
+    }
+}
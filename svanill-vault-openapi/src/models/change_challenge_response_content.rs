@@ -0,0 +1,17 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ChangeChallengeResponseContent {
+    #[serde(rename = "challenge")]
+    pub challenge: String,
+    /// the new token, present if the other sessions have been revoked
+    #[serde(rename = "token", skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl ChangeChallengeResponseContent {
+    pub fn new(challenge: String) -> ChangeChallengeResponseContent {
+        ChangeChallengeResponseContent {
+            challenge,
+            token: None,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ChangeChallengeResponseLinks {
+    #[serde(rename = "files_list")]
+    pub files_list: Box<super::HateoasFilesList>,
+}
+
+impl ChangeChallengeResponseLinks {
+    pub fn new(files_list: super::HateoasFilesList) -> ChangeChallengeResponseLinks {
+        ChangeChallengeResponseLinks {
+            files_list: Box::new(files_list),
+        }
+    }
+}
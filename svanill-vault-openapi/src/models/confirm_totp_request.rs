@@ -0,0 +1,12 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ConfirmTotpRequest {
+    /// a code generated by the authenticator app, to prove it has been set up
+    #[serde(rename = "code")]
+    pub code: String,
+}
+
+impl ConfirmTotpRequest {
+    pub fn new(code: String) -> ConfirmTotpRequest {
+        ConfirmTotpRequest { code }
+    }
+}
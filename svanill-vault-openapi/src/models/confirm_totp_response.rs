@@ -0,0 +1,11 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ConfirmTotpResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+}
+
+impl ConfirmTotpResponse {
+    pub fn new(status: i32) -> ConfirmTotpResponse {
+        ConfirmTotpResponse { status }
+    }
+}
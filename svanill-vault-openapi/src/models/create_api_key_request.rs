@@ -0,0 +1,26 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CreateApiKeyRequest {
+    /// a label to recognize the key, e.g. the name of the CI job using it
+    #[serde(rename = "name")]
+    pub name: String,
+    /// what the key allows: read, write or delete (each scope includes the previous ones)
+    #[serde(rename = "scope")]
+    pub scope: String,
+    /// only give access to the files whose name starts with it
+    #[serde(rename = "prefix", skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// when the key expires, in seconds since the unix epoch
+    #[serde(rename = "expires_at", skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+
+impl CreateApiKeyRequest {
+    pub fn new(name: String, scope: String) -> CreateApiKeyRequest {
+        CreateApiKeyRequest {
+            name,
+            scope,
+            prefix: None,
+            expires_at: None,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CreateApiKeyResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+    #[serde(rename = "content")]
+    pub content: Box<super::ApiKey>,
+    #[serde(rename = "links")]
+    pub links: Box<super::ListApiKeysResponseContentItemLinks>,
+}
+
+impl CreateApiKeyResponse {
+    pub fn new(
+        status: i32,
+        content: super::ApiKey,
+        links: super::ListApiKeysResponseContentItemLinks,
+    ) -> CreateApiKeyResponse {
+        CreateApiKeyResponse {
+            status,
+            content: Box::new(content),
+            links: Box::new(links),
+        }
+    }
+}
@@ -9,6 +9,9 @@ pub struct CreateUserRequest {
     /// a private blob of text that has to be provided during authorization in response to the challenge
     #[serde(rename = "answer")]
     pub answer: String,
+    /// the invite code, required only when the server accepts registrations by invitation
+    #[serde(rename = "invite_code", skip_serializing_if = "Option::is_none")]
+    pub invite_code: Option<String>,
 }
 
 impl CreateUserRequest {
@@ -17,6 +20,7 @@ impl CreateUserRequest {
             username,
             challenge,
             answer,
+            invite_code: None,
         }
     }
 }
@@ -0,0 +1,12 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct DisableTotpRequest {
+    /// a code generated by the authenticator app
+    #[serde(rename = "code")]
+    pub code: String,
+}
+
+impl DisableTotpRequest {
+    pub fn new(code: String) -> DisableTotpRequest {
+        DisableTotpRequest { code }
+    }
+}
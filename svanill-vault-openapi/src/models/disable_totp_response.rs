@@ -0,0 +1,11 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct DisableTotpResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+}
+
+impl DisableTotpResponse {
+    pub fn new(status: i32) -> DisableTotpResponse {
+        DisableTotpResponse { status }
+    }
+}
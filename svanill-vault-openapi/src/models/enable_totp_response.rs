@@ -0,0 +1,23 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct EnableTotpResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+    #[serde(rename = "content")]
+    pub content: Box<super::EnableTotpResponseContent>,
+    #[serde(rename = "links")]
+    pub links: Box<super::EnableTotpResponseLinks>,
+}
+
+impl EnableTotpResponse {
+    pub fn new(
+        status: i32,
+        content: super::EnableTotpResponseContent,
+        links: super::EnableTotpResponseLinks,
+    ) -> EnableTotpResponse {
+        EnableTotpResponse {
+            status,
+            content: Box::new(content),
+            links: Box::new(links),
+        }
+    }
+}
@@ -0,0 +1,18 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct EnableTotpResponseContent {
+    /// the secret, base32 encoded, to type in an authenticator app
+    #[serde(rename = "secret")]
+    pub secret: String,
+    /// the secret as an otpauth:// URI, usually shown as a QR code
+    #[serde(rename = "otpauth_uri")]
+    pub otpauth_uri: String,
+}
+
+impl EnableTotpResponseContent {
+    pub fn new(secret: String, otpauth_uri: String) -> EnableTotpResponseContent {
+        EnableTotpResponseContent {
+            secret,
+            otpauth_uri,
+        }
+    }
+}
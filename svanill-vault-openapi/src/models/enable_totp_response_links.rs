@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct EnableTotpResponseLinks {
+    #[serde(rename = "confirm")]
+    pub confirm: Box<super::HateoasTotpConfirm>,
+}
+
+impl EnableTotpResponseLinks {
+    pub fn new(confirm: super::HateoasTotpConfirm) -> EnableTotpResponseLinks {
+        EnableTotpResponseLinks {
+            confirm: Box::new(confirm),
+        }
+    }
+}
@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasAdminUserDelete {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasAdminUserDelete {
+    pub fn new(href: String, rel: String) -> HateoasAdminUserDelete {
+        HateoasAdminUserDelete { href, rel }
+    }
+}
@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasAdminUserDisable {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasAdminUserDisable {
+    pub fn new(href: String, rel: String) -> HateoasAdminUserDisable {
+        HateoasAdminUserDisable { href, rel }
+    }
+}
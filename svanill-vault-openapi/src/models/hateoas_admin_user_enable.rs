@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasAdminUserEnable {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasAdminUserEnable {
+    pub fn new(href: String, rel: String) -> HateoasAdminUserEnable {
+        HateoasAdminUserEnable { href, rel }
+    }
+}
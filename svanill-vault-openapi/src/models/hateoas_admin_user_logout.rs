@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasAdminUserLogout {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasAdminUserLogout {
+    pub fn new(href: String, rel: String) -> HateoasAdminUserLogout {
+        HateoasAdminUserLogout { href, rel }
+    }
+}
@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasApiKeyDelete {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasApiKeyDelete {
+    pub fn new(href: String, rel: String) -> HateoasApiKeyDelete {
+        HateoasApiKeyDelete { href, rel }
+    }
+}
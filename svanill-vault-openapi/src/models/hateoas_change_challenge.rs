@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasChangeChallenge {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasChangeChallenge {
+    pub fn new(href: String, rel: String) -> HateoasChangeChallenge {
+        HateoasChangeChallenge { href, rel }
+    }
+}
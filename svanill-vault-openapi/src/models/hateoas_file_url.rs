@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasFileUrl {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasFileUrl {
+    pub fn new(href: String, rel: String) -> HateoasFileUrl {
+        HateoasFileUrl { href, rel }
+    }
+}
@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasLogout {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasLogout {
+    pub fn new(href: String, rel: String) -> HateoasLogout {
+        HateoasLogout { href, rel }
+    }
+}
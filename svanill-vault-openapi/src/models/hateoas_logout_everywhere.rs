@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasLogoutEverywhere {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasLogoutEverywhere {
+    pub fn new(href: String, rel: String) -> HateoasLogoutEverywhere {
+        HateoasLogoutEverywhere { href, rel }
+    }
+}
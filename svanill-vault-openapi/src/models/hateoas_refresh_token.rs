@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasRefreshToken {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasRefreshToken {
+    pub fn new(href: String, rel: String) -> HateoasRefreshToken {
+        HateoasRefreshToken { href, rel }
+    }
+}
@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasSessionDelete {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasSessionDelete {
+    pub fn new(href: String, rel: String) -> HateoasSessionDelete {
+        HateoasSessionDelete { href, rel }
+    }
+}
@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasTotpConfirm {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasTotpConfirm {
+    pub fn new(href: String, rel: String) -> HateoasTotpConfirm {
+        HateoasTotpConfirm { href, rel }
+    }
+}
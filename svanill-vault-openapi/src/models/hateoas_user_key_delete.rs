@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HateoasUserKeyDelete {
+    #[serde(rename = "href")]
+    pub href: String,
+    #[serde(rename = "rel")]
+    pub rel: String,
+}
+
+impl HateoasUserKeyDelete {
+    pub fn new(href: String, rel: String) -> HateoasUserKeyDelete {
+        HateoasUserKeyDelete { href, rel }
+    }
+}
@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListActivityResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+    #[serde(rename = "content")]
+    pub content: Vec<super::ActivityEntry>,
+}
+
+impl ListActivityResponse {
+    pub fn new(status: i32, content: Vec<super::ActivityEntry>) -> ListActivityResponse {
+        ListActivityResponse { status, content }
+    }
+}
@@ -0,0 +1,16 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListAdminUsersResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+    #[serde(rename = "content")]
+    pub content: Vec<super::ListAdminUsersResponseContentItem>,
+}
+
+impl ListAdminUsersResponse {
+    pub fn new(
+        status: i32,
+        content: Vec<super::ListAdminUsersResponseContentItem>,
+    ) -> ListAdminUsersResponse {
+        ListAdminUsersResponse { status, content }
+    }
+}
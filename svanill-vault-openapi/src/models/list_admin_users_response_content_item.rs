@@ -0,0 +1,19 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListAdminUsersResponseContentItem {
+    #[serde(rename = "content")]
+    pub content: Box<super::AdminUser>,
+    #[serde(rename = "links")]
+    pub links: Box<super::AdminUserLinks>,
+}
+
+impl ListAdminUsersResponseContentItem {
+    pub fn new(
+        content: super::AdminUser,
+        links: super::AdminUserLinks,
+    ) -> ListAdminUsersResponseContentItem {
+        ListAdminUsersResponseContentItem {
+            content: Box::new(content),
+            links: Box::new(links),
+        }
+    }
+}
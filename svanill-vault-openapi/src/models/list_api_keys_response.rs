@@ -0,0 +1,16 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListApiKeysResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+    #[serde(rename = "content")]
+    pub content: Vec<super::ListApiKeysResponseContentItem>,
+}
+
+impl ListApiKeysResponse {
+    pub fn new(
+        status: i32,
+        content: Vec<super::ListApiKeysResponseContentItem>,
+    ) -> ListApiKeysResponse {
+        ListApiKeysResponse { status, content }
+    }
+}
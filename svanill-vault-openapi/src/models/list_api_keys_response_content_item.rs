@@ -0,0 +1,19 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListApiKeysResponseContentItem {
+    #[serde(rename = "links")]
+    pub links: Box<super::ListApiKeysResponseContentItemLinks>,
+    #[serde(rename = "content")]
+    pub content: Box<super::ApiKey>,
+}
+
+impl ListApiKeysResponseContentItem {
+    pub fn new(
+        links: super::ListApiKeysResponseContentItemLinks,
+        content: super::ApiKey,
+    ) -> ListApiKeysResponseContentItem {
+        ListApiKeysResponseContentItem {
+            links: Box::new(links),
+            content: Box::new(content),
+        }
+    }
+}
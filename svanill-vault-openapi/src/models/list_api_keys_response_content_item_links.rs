@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListApiKeysResponseContentItemLinks {
+    #[serde(rename = "delete")]
+    pub delete: Box<super::HateoasApiKeyDelete>,
+}
+
+impl ListApiKeysResponseContentItemLinks {
+    pub fn new(delete: super::HateoasApiKeyDelete) -> ListApiKeysResponseContentItemLinks {
+        ListApiKeysResponseContentItemLinks {
+            delete: Box::new(delete),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListSessionsResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+    #[serde(rename = "content")]
+    pub content: Vec<super::ListSessionsResponseContentItem>,
+}
+
+impl ListSessionsResponse {
+    pub fn new(
+        status: i32,
+        content: Vec<super::ListSessionsResponseContentItem>,
+    ) -> ListSessionsResponse {
+        ListSessionsResponse { status, content }
+    }
+}
@@ -0,0 +1,19 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListSessionsResponseContentItem {
+    #[serde(rename = "links")]
+    pub links: Box<super::ListSessionsResponseContentItemLinks>,
+    #[serde(rename = "content")]
+    pub content: Box<super::ListSessionsResponseContentItemContent>,
+}
+
+impl ListSessionsResponseContentItem {
+    pub fn new(
+        links: super::ListSessionsResponseContentItemLinks,
+        content: super::ListSessionsResponseContentItemContent,
+    ) -> ListSessionsResponseContentItem {
+        ListSessionsResponseContentItem {
+            links: Box::new(links),
+            content: Box::new(content),
+        }
+    }
+}
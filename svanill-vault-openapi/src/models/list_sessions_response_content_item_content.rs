@@ -0,0 +1,33 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListSessionsResponseContentItemContent {
+    #[serde(rename = "id")]
+    pub id: String,
+    #[serde(rename = "created_at")]
+    pub created_at: i64,
+    #[serde(rename = "last_seen_at")]
+    pub last_seen_at: i64,
+    #[serde(rename = "current")]
+    pub current: bool,
+    #[serde(rename = "ip", skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    #[serde(rename = "user_agent", skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+}
+
+impl ListSessionsResponseContentItemContent {
+    pub fn new(
+        id: String,
+        created_at: i64,
+        last_seen_at: i64,
+        current: bool,
+    ) -> ListSessionsResponseContentItemContent {
+        ListSessionsResponseContentItemContent {
+            id,
+            created_at,
+            last_seen_at,
+            current,
+            ip: None,
+            user_agent: None,
+        }
+    }
+}
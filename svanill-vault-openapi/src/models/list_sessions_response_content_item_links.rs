@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListSessionsResponseContentItemLinks {
+    #[serde(rename = "delete")]
+    pub delete: Box<super::HateoasSessionDelete>,
+}
+
+impl ListSessionsResponseContentItemLinks {
+    pub fn new(delete: super::HateoasSessionDelete) -> ListSessionsResponseContentItemLinks {
+        ListSessionsResponseContentItemLinks {
+            delete: Box::new(delete),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListUserKeysResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+    #[serde(rename = "content")]
+    pub content: Vec<super::ListUserKeysResponseContentItem>,
+}
+
+impl ListUserKeysResponse {
+    pub fn new(
+        status: i32,
+        content: Vec<super::ListUserKeysResponseContentItem>,
+    ) -> ListUserKeysResponse {
+        ListUserKeysResponse { status, content }
+    }
+}
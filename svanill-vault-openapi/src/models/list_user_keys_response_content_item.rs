@@ -0,0 +1,19 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListUserKeysResponseContentItem {
+    #[serde(rename = "links")]
+    pub links: Box<super::ListUserKeysResponseContentItemLinks>,
+    #[serde(rename = "content")]
+    pub content: Box<super::UserKey>,
+}
+
+impl ListUserKeysResponseContentItem {
+    pub fn new(
+        links: super::ListUserKeysResponseContentItemLinks,
+        content: super::UserKey,
+    ) -> ListUserKeysResponseContentItem {
+        ListUserKeysResponseContentItem {
+            links: Box::new(links),
+            content: Box::new(content),
+        }
+    }
+}
@@ -0,0 +1,11 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ListUserKeysResponseContentItemLinks {
+    #[serde(rename = "delete", skip_serializing_if = "Option::is_none")]
+    pub delete: Option<Box<super::HateoasUserKeyDelete>>,
+}
+
+impl ListUserKeysResponseContentItemLinks {
+    pub fn new() -> ListUserKeysResponseContentItemLinks {
+        ListUserKeysResponseContentItemLinks { delete: None }
+    }
+}
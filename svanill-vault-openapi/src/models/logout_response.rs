@@ -0,0 +1,16 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct LogoutResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+    #[serde(rename = "links")]
+    pub links: Box<super::LogoutResponseLinks>,
+}
+
+impl LogoutResponse {
+    pub fn new(status: i32, links: super::LogoutResponseLinks) -> LogoutResponse {
+        LogoutResponse {
+            status,
+            links: Box::new(links),
+        }
+    }
+}
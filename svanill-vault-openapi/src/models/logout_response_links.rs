@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct LogoutResponseLinks {
+    #[serde(rename = "request_auth_challenge")]
+    pub request_auth_challenge: Box<super::HateoasRequestAuthChallenge>,
+}
+
+impl LogoutResponseLinks {
+    pub fn new(request_auth_challenge: super::HateoasRequestAuthChallenge) -> LogoutResponseLinks {
+        LogoutResponseLinks {
+            request_auth_challenge: Box::new(request_auth_challenge),
+        }
+    }
+}
@@ -1,3 +1,23 @@
+pub mod activity_entry;
+pub use self::activity_entry::ActivityEntry;
+pub mod add_user_key_request;
+pub use self::add_user_key_request::AddUserKeyRequest;
+pub mod add_user_key_response;
+pub use self::add_user_key_response::AddUserKeyResponse;
+pub mod admin_create_user_request;
+pub use self::admin_create_user_request::AdminCreateUserRequest;
+pub mod admin_delete_user_response;
+pub use self::admin_delete_user_response::AdminDeleteUserResponse;
+pub mod admin_disable_user_request;
+pub use self::admin_disable_user_request::AdminDisableUserRequest;
+pub mod admin_logout_user_response;
+pub use self::admin_logout_user_response::AdminLogoutUserResponse;
+pub mod admin_user;
+pub use self::admin_user::AdminUser;
+pub mod admin_user_links;
+pub use self::admin_user_links::AdminUserLinks;
+pub mod admin_user_response;
+pub use self::admin_user_response::AdminUserResponse;
 pub mod answer_user_challenge_request;
 pub use self::answer_user_challenge_request::AnswerUserChallengeRequest;
 pub mod answer_user_challenge_response;
@@ -6,12 +26,30 @@ pub mod answer_user_challenge_response_content;
 pub use self::answer_user_challenge_response_content::AnswerUserChallengeResponseContent;
 pub mod answer_user_challenge_response_links;
 pub use self::answer_user_challenge_response_links::AnswerUserChallengeResponseLinks;
+pub mod api_key;
+pub use self::api_key::ApiKey;
 pub mod ask_for_the_challenge_response;
 pub use self::ask_for_the_challenge_response::AskForTheChallengeResponse;
 pub mod ask_for_the_challenge_response_content;
 pub use self::ask_for_the_challenge_response_content::AskForTheChallengeResponseContent;
 pub mod ask_for_the_challenge_response_links;
 pub use self::ask_for_the_challenge_response_links::AskForTheChallengeResponseLinks;
+pub mod change_challenge_request;
+pub use self::change_challenge_request::ChangeChallengeRequest;
+pub mod change_challenge_response;
+pub use self::change_challenge_response::ChangeChallengeResponse;
+pub mod change_challenge_response_content;
+pub use self::change_challenge_response_content::ChangeChallengeResponseContent;
+pub mod change_challenge_response_links;
+pub use self::change_challenge_response_links::ChangeChallengeResponseLinks;
+pub mod confirm_totp_request;
+pub use self::confirm_totp_request::ConfirmTotpRequest;
+pub mod confirm_totp_response;
+pub use self::confirm_totp_response::ConfirmTotpResponse;
+pub mod create_api_key_request;
+pub use self::create_api_key_request::CreateApiKeyRequest;
+pub mod create_api_key_response;
+pub use self::create_api_key_response::CreateApiKeyResponse;
 pub mod create_user_request;
 pub use self::create_user_request::CreateUserRequest;
 pub mod create_user_response;
@@ -24,12 +62,34 @@ pub mod delete_user_response;
 pub use self::delete_user_response::DeleteUserResponse;
 pub mod delete_user_response_links;
 pub use self::delete_user_response_links::DeleteUserResponseLinks;
+pub mod disable_totp_request;
+pub use self::disable_totp_request::DisableTotpRequest;
+pub mod disable_totp_response;
+pub use self::disable_totp_response::DisableTotpResponse;
+pub mod enable_totp_response;
+pub use self::enable_totp_response::EnableTotpResponse;
+pub mod enable_totp_response_content;
+pub use self::enable_totp_response_content::EnableTotpResponseContent;
+pub mod enable_totp_response_links;
+pub use self::enable_totp_response_links::EnableTotpResponseLinks;
 pub mod get_starting_endpoints_response;
 pub use self::get_starting_endpoints_response::GetStartingEndpointsResponse;
 pub mod get_starting_endpoints_response_links;
 pub use self::get_starting_endpoints_response_links::GetStartingEndpointsResponseLinks;
+pub mod hateoas_admin_user_delete;
+pub use self::hateoas_admin_user_delete::HateoasAdminUserDelete;
+pub mod hateoas_admin_user_disable;
+pub use self::hateoas_admin_user_disable::HateoasAdminUserDisable;
+pub mod hateoas_admin_user_enable;
+pub use self::hateoas_admin_user_enable::HateoasAdminUserEnable;
+pub mod hateoas_admin_user_logout;
+pub use self::hateoas_admin_user_logout::HateoasAdminUserLogout;
 pub mod hateoas_answer_auth_challenge;
 pub use self::hateoas_answer_auth_challenge::HateoasAnswerAuthChallenge;
+pub mod hateoas_api_key_delete;
+pub use self::hateoas_api_key_delete::HateoasApiKeyDelete;
+pub mod hateoas_change_challenge;
+pub use self::hateoas_change_challenge::HateoasChangeChallenge;
 pub mod hateoas_create_user;
 pub use self::hateoas_create_user::HateoasCreateUser;
 pub mod hateoas_file_delete;
@@ -38,22 +98,74 @@ pub mod hateoas_file_read;
 pub use self::hateoas_file_read::HateoasFileRead;
 pub mod hateoas_file_retrieve_url;
 pub use self::hateoas_file_retrieve_url::HateoasFileRetrieveUrl;
+pub mod hateoas_file_url;
+pub use self::hateoas_file_url::HateoasFileUrl;
 pub mod hateoas_file_upload_url;
 pub use self::hateoas_file_upload_url::HateoasFileUploadUrl;
 pub mod hateoas_files_list;
 pub use self::hateoas_files_list::HateoasFilesList;
+pub mod hateoas_logout;
+pub use self::hateoas_logout::HateoasLogout;
+pub mod hateoas_logout_everywhere;
+pub use self::hateoas_logout_everywhere::HateoasLogoutEverywhere;
+pub mod hateoas_refresh_token;
+pub use self::hateoas_refresh_token::HateoasRefreshToken;
 pub mod hateoas_request_auth_challenge;
 pub use self::hateoas_request_auth_challenge::HateoasRequestAuthChallenge;
 pub mod hateoas_request_upload_url;
 pub use self::hateoas_request_upload_url::HateoasRequestUploadUrl;
+pub mod hateoas_session_delete;
+pub use self::hateoas_session_delete::HateoasSessionDelete;
+pub mod hateoas_totp_confirm;
+pub use self::hateoas_totp_confirm::HateoasTotpConfirm;
+pub mod hateoas_user_key_delete;
+pub use self::hateoas_user_key_delete::HateoasUserKeyDelete;
+pub mod list_activity_response;
+pub use self::list_activity_response::ListActivityResponse;
+pub mod list_admin_users_response;
+pub use self::list_admin_users_response::ListAdminUsersResponse;
+pub mod list_admin_users_response_content_item;
+pub use self::list_admin_users_response_content_item::ListAdminUsersResponseContentItem;
+pub mod list_api_keys_response;
+pub use self::list_api_keys_response::ListApiKeysResponse;
+pub mod list_api_keys_response_content_item;
+pub use self::list_api_keys_response_content_item::ListApiKeysResponseContentItem;
+pub mod list_api_keys_response_content_item_links;
+pub use self::list_api_keys_response_content_item_links::ListApiKeysResponseContentItemLinks;
+pub mod list_sessions_response;
+pub use self::list_sessions_response::ListSessionsResponse;
+pub mod list_sessions_response_content_item;
+pub use self::list_sessions_response_content_item::ListSessionsResponseContentItem;
+pub mod list_sessions_response_content_item_content;
+pub use self::list_sessions_response_content_item_content::ListSessionsResponseContentItemContent;
+pub mod list_sessions_response_content_item_links;
+pub use self::list_sessions_response_content_item_links::ListSessionsResponseContentItemLinks;
+pub mod list_user_keys_response;
+pub use self::list_user_keys_response::ListUserKeysResponse;
+pub mod list_user_keys_response_content_item;
+pub use self::list_user_keys_response_content_item::ListUserKeysResponseContentItem;
+pub mod list_user_keys_response_content_item_links;
+pub use self::list_user_keys_response_content_item_links::ListUserKeysResponseContentItemLinks;
+pub mod logout_response;
+pub use self::logout_response::LogoutResponse;
+pub mod logout_response_links;
+pub use self::logout_response_links::LogoutResponseLinks;
+pub mod remove_api_key_response;
+pub use self::remove_api_key_response::RemoveApiKeyResponse;
 pub mod remove_file_response;
 pub use self::remove_file_response::RemoveFileResponse;
+pub mod remove_user_key_response;
+pub use self::remove_user_key_response::RemoveUserKeyResponse;
 pub mod request_upload_url_request_body;
 pub use self::request_upload_url_request_body::RequestUploadUrlRequestBody;
 pub mod request_upload_url_response;
 pub use self::request_upload_url_response::RequestUploadUrlResponse;
 pub mod request_upload_url_response_links;
 pub use self::request_upload_url_response_links::RequestUploadUrlResponseLinks;
+pub mod retrieve_file_response;
+pub use self::retrieve_file_response::RetrieveFileResponse;
+pub mod retrieve_file_url_response;
+pub use self::retrieve_file_url_response::RetrieveFileUrlResponse;
 pub mod retrieve_list_of_user_files_response;
 pub use self::retrieve_list_of_user_files_response::RetrieveListOfUserFilesResponse;
 pub mod retrieve_list_of_user_files_response_content_item;
@@ -62,3 +174,7 @@ pub mod retrieve_list_of_user_files_response_content_item_content;
 pub use self::retrieve_list_of_user_files_response_content_item_content::RetrieveListOfUserFilesResponseContentItemContent;
 pub mod retrieve_list_of_user_files_response_content_item_links;
 pub use self::retrieve_list_of_user_files_response_content_item_links::RetrieveListOfUserFilesResponseContentItemLinks;
+pub mod retrieve_list_of_user_files_response_links;
+pub use self::retrieve_list_of_user_files_response_links::RetrieveListOfUserFilesResponseLinks;
+pub mod user_key;
+pub use self::user_key::UserKey;
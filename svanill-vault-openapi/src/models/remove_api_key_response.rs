@@ -0,0 +1,11 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RemoveApiKeyResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+}
+
+impl RemoveApiKeyResponse {
+    pub fn new(status: i32) -> RemoveApiKeyResponse {
+        RemoveApiKeyResponse { status }
+    }
+}
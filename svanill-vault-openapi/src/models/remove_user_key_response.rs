@@ -0,0 +1,11 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RemoveUserKeyResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+}
+
+impl RemoveUserKeyResponse {
+    pub fn new(status: i32) -> RemoveUserKeyResponse {
+        RemoveUserKeyResponse { status }
+    }
+}
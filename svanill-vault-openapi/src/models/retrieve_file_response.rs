@@ -0,0 +1,23 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RetrieveFileResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+    #[serde(rename = "content")]
+    pub content: Box<super::RetrieveListOfUserFilesResponseContentItemContent>,
+    #[serde(rename = "links")]
+    pub links: Box<super::RetrieveListOfUserFilesResponseContentItemLinks>,
+}
+
+impl RetrieveFileResponse {
+    pub fn new(
+        status: i32,
+        content: super::RetrieveListOfUserFilesResponseContentItemContent,
+        links: super::RetrieveListOfUserFilesResponseContentItemLinks,
+    ) -> RetrieveFileResponse {
+        RetrieveFileResponse {
+            status,
+            content: Box::new(content),
+            links: Box::new(links),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RetrieveFileUrlResponse {
+    #[serde(rename = "status")]
+    pub status: i32,
+    #[serde(rename = "content")]
+    pub content: Box<super::RetrieveListOfUserFilesResponseContentItemContent>,
+}
+
+impl RetrieveFileUrlResponse {
+    pub fn new(
+        status: i32,
+        content: super::RetrieveListOfUserFilesResponseContentItemContent,
+    ) -> RetrieveFileUrlResponse {
+        RetrieveFileUrlResponse {
+            status,
+            content: Box::new(content),
+        }
+    }
+}
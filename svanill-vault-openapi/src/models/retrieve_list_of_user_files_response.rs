@@ -4,6 +4,8 @@ pub struct RetrieveListOfUserFilesResponse {
     pub status: i32,
     #[serde(rename = "content")]
     pub content: Vec<super::RetrieveListOfUserFilesResponseContentItem>,
+    #[serde(rename = "links", skip_serializing_if = "Option::is_none")]
+    pub links: Option<Box<super::RetrieveListOfUserFilesResponseLinks>>,
 }
 
 impl RetrieveListOfUserFilesResponse {
@@ -11,6 +13,10 @@ impl RetrieveListOfUserFilesResponse {
         status: i32,
         content: Vec<super::RetrieveListOfUserFilesResponseContentItem>,
     ) -> RetrieveListOfUserFilesResponse {
-        RetrieveListOfUserFilesResponse { status, content }
+        RetrieveListOfUserFilesResponse {
+            status,
+            content,
+            links: None,
+        }
     }
 }
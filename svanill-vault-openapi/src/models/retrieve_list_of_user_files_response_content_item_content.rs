@@ -6,8 +6,9 @@ pub struct RetrieveListOfUserFilesResponseContentItemContent {
     pub filename: String,
     #[serde(rename = "size")]
     pub size: i32,
-    #[serde(rename = "url")]
-    pub url: String,
+    /// missing when the files are listed without urls
+    #[serde(rename = "url", skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
 }
 
 impl RetrieveListOfUserFilesResponseContentItemContent {
@@ -15,7 +16,7 @@ impl RetrieveListOfUserFilesResponseContentItemContent {
         checksum: String,
         filename: String,
         size: i32,
-        url: String,
+        url: Option<String>,
     ) -> RetrieveListOfUserFilesResponseContentItemContent {
         RetrieveListOfUserFilesResponseContentItemContent {
             checksum,
@@ -1,19 +1,21 @@
 #[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct RetrieveListOfUserFilesResponseContentItemLinks {
-    #[serde(rename = "read")]
-    pub read: Box<super::HateoasFileRead>,
+    #[serde(rename = "read", skip_serializing_if = "Option::is_none")]
+    pub read: Option<Box<super::HateoasFileRead>>,
     #[serde(rename = "delete")]
     pub delete: Box<super::HateoasFileDelete>,
+    #[serde(rename = "url", skip_serializing_if = "Option::is_none")]
+    pub url: Option<Box<super::HateoasFileUrl>>,
 }
 
 impl RetrieveListOfUserFilesResponseContentItemLinks {
     pub fn new(
-        read: super::HateoasFileRead,
         delete: super::HateoasFileDelete,
     ) -> RetrieveListOfUserFilesResponseContentItemLinks {
         RetrieveListOfUserFilesResponseContentItemLinks {
-            read: Box::new(read),
+            read: None,
             delete: Box::new(delete),
+            url: None,
         }
     }
 }
@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RetrieveListOfUserFilesResponseLinks {
+    #[serde(rename = "next")]
+    pub next: Box<super::HateoasFilesList>,
+}
+
+impl RetrieveListOfUserFilesResponseLinks {
+    pub fn new(next: super::HateoasFilesList) -> RetrieveListOfUserFilesResponseLinks {
+        RetrieveListOfUserFilesResponseLinks {
+            next: Box::new(next),
+        }
+    }
+}
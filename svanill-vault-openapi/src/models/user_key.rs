@@ -0,0 +1,13 @@
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct UserKey {
+    #[serde(rename = "key_id")]
+    pub key_id: String,
+    #[serde(rename = "challenge")]
+    pub challenge: String,
+}
+
+impl UserKey {
+    pub fn new(key_id: String, challenge: String) -> UserKey {
+        UserKey { key_id, challenge }
+    }
+}
@@ -0,0 +1 @@
+DROP TABLE token
\ No newline at end of file
@@ -0,0 +1,9 @@
+CREATE TABLE token (
+  token_hash VARCHAR(64) NOT NULL PRIMARY KEY,
+  username VARCHAR(50) NOT NULL,
+  created_at BIGINT NOT NULL,
+  expires_at BIGINT NOT NULL
+);
+
+CREATE INDEX token_username ON token (username);
+CREATE INDEX token_expires_at ON token (expires_at);
@@ -0,0 +1,2 @@
+DROP TABLE revoked_token;
+DROP TABLE user_token_revocation
\ No newline at end of file
@@ -0,0 +1,12 @@
+-- timestamps are in milliseconds since the unix epoch, to match the signed tokens
+CREATE TABLE revoked_token (
+  jti VARCHAR(32) NOT NULL PRIMARY KEY,
+  expires_at BIGINT NOT NULL
+);
+
+CREATE INDEX revoked_token_expires_at ON revoked_token (expires_at);
+
+CREATE TABLE user_token_revocation (
+  username VARCHAR(50) NOT NULL PRIMARY KEY,
+  revoked_at BIGINT NOT NULL
+);
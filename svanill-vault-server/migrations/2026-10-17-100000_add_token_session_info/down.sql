@@ -0,0 +1,3 @@
+ALTER TABLE token DROP COLUMN user_agent;
+ALTER TABLE token DROP COLUMN ip;
+ALTER TABLE token DROP COLUMN last_seen_at
\ No newline at end of file
@@ -0,0 +1,5 @@
+ALTER TABLE token ADD COLUMN last_seen_at BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE token ADD COLUMN ip VARCHAR(45);
+ALTER TABLE token ADD COLUMN user_agent VARCHAR(255);
+
+UPDATE token SET last_seen_at = created_at;
@@ -0,0 +1 @@
+DROP TABLE login_lockout;
\ No newline at end of file
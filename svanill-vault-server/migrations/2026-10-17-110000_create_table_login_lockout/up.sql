@@ -0,0 +1,12 @@
+-- lockouts caused by too many failed attempts to answer the challenge,
+-- kept for the operators to review. Timestamps are in seconds since the unix epoch
+CREATE TABLE login_lockout (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  kind VARCHAR(16) NOT NULL,
+  subject VARCHAR(255) NOT NULL,
+  failures INTEGER NOT NULL,
+  locked_at BIGINT NOT NULL,
+  locked_until BIGINT NOT NULL
+);
+
+CREATE INDEX login_lockout_locked_at ON login_lockout (locked_at);
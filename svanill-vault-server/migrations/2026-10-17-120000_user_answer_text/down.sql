@@ -0,0 +1,11 @@
+CREATE TABLE user_old (
+  username VARCHAR(50) NOT NULL PRIMARY KEY,
+  challenge VARCHAR(255) NOT NULL,
+  answer VARCHAR(32) NOT NULL
+);
+
+INSERT INTO user_old (username, challenge, answer) SELECT username, challenge, answer FROM user;
+
+DROP TABLE user;
+
+ALTER TABLE user_old RENAME TO user;
\ No newline at end of file
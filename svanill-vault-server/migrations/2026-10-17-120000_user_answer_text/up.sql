@@ -0,0 +1,13 @@
+-- answers are now stored hashed, so they don't fit in 32 characters anymore.
+-- The existing plaintext answers are hashed the next time their user logs in.
+CREATE TABLE user_new (
+  username VARCHAR(50) NOT NULL PRIMARY KEY,
+  challenge VARCHAR(255) NOT NULL,
+  answer TEXT NOT NULL
+);
+
+INSERT INTO user_new (username, challenge, answer) SELECT username, challenge, answer FROM user;
+
+DROP TABLE user;
+
+ALTER TABLE user_new RENAME TO user;
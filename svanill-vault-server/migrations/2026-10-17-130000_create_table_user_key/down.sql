@@ -0,0 +1 @@
+DROP TABLE user_key;
\ No newline at end of file
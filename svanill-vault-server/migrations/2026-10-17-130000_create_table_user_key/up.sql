@@ -0,0 +1,10 @@
+-- additional challenge/answer pairs of the users, e.g. one per device.
+-- The pair stored in the user table is the user's "default" key.
+CREATE TABLE user_key (
+  username VARCHAR(50) NOT NULL,
+  key_id VARCHAR(50) NOT NULL,
+  challenge VARCHAR(255) NOT NULL,
+  answer TEXT NOT NULL,
+  created_at BIGINT NOT NULL,
+  PRIMARY KEY (username, key_id)
+);
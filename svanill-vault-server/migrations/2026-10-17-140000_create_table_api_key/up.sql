@@ -0,0 +1,14 @@
+-- long-lived keys to access the files without answering the challenge, e.g. from CI.
+-- Only the SHA-256 of their secret is stored.
+CREATE TABLE api_key (
+  id VARCHAR(16) NOT NULL PRIMARY KEY,
+  username VARCHAR(50) NOT NULL,
+  name VARCHAR(100) NOT NULL,
+  secret_hash VARCHAR(64) NOT NULL,
+  scope VARCHAR(10) NOT NULL,
+  prefix TEXT,
+  created_at BIGINT NOT NULL,
+  expires_at BIGINT
+);
+
+CREATE INDEX api_key_username ON api_key (username);
@@ -0,0 +1,9 @@
+-- optional TOTP second factor. The secret is pending until confirmed with a valid code.
+-- last_used_step is the time step of the last accepted code, so that codes can't be replayed
+CREATE TABLE user_totp (
+  username VARCHAR(50) NOT NULL PRIMARY KEY,
+  secret VARCHAR(40) NOT NULL,
+  created_at BIGINT NOT NULL,
+  confirmed_at BIGINT,
+  last_used_step BIGINT
+);
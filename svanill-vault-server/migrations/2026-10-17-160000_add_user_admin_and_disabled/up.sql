@@ -0,0 +1,4 @@
+-- admins can manage the other users through /admin/users.
+-- Disabled users keep their files, but can't log in
+ALTER TABLE user ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE user ADD COLUMN disabled_at BIGINT;
@@ -0,0 +1,14 @@
+CREATE TABLE user_old (
+  username VARCHAR(50) NOT NULL PRIMARY KEY,
+  challenge VARCHAR(255) NOT NULL,
+  answer TEXT NOT NULL,
+  is_admin BOOLEAN NOT NULL DEFAULT 0,
+  disabled_at BIGINT
+);
+
+INSERT INTO user_old (username, challenge, answer, is_admin, disabled_at)
+  SELECT username, challenge, answer, is_admin, disabled_at FROM user;
+
+DROP TABLE user;
+
+ALTER TABLE user_old RENAME TO user;
\ No newline at end of file
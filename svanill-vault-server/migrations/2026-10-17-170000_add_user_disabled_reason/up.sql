@@ -0,0 +1,2 @@
+-- Why the user has been disabled, shown to them when they try to log in
+ALTER TABLE user ADD COLUMN disabled_reason TEXT;
@@ -0,0 +1,16 @@
+-- append-only record of the logins and of what the users did with their files.
+-- Entries older than the retention period are removed. Timestamps are in seconds since the unix epoch
+CREATE TABLE audit_log (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  created_at BIGINT NOT NULL,
+  username VARCHAR(50) NOT NULL,
+  action VARCHAR(32) NOT NULL,
+  filename TEXT,
+  ip VARCHAR(45),
+  request_id VARCHAR(64) NOT NULL,
+  outcome VARCHAR(16) NOT NULL,
+  error_code INTEGER
+);
+
+CREATE INDEX audit_log_username ON audit_log (username, created_at);
+CREATE INDEX audit_log_created_at ON audit_log (created_at);
@@ -0,0 +1,159 @@
+use crate::errors::VaultError;
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{digest, pbkdf2};
+use std::num::NonZeroU32;
+use subtle::ConstantTimeEq;
+
+/// Prefix of the answers hashed with PBKDF2-HMAC-SHA256.
+/// Answers stored without it are legacy plaintext ones.
+const PBKDF2_SHA256: &str = "pbkdf2-sha256";
+
+/// Iterations used to hash new answers
+pub const PBKDF2_ITERATIONS: u32 = 600_000;
+
+const SALT_LENGTH: usize = 16;
+const HASH_LENGTH: usize = digest::SHA256_OUTPUT_LEN;
+
+/// How a stored answer matched the given one
+#[derive(Debug, PartialEq, Eq)]
+pub enum AnswerMatch {
+    Valid,
+    /// The answer is right, but it's stored in plaintext or hashed with
+    /// outdated parameters, so it should be hashed again
+    ValidNeedsRehash,
+    Invalid,
+}
+
+/// Hash the answer with a random salt, in the format `pbkdf2-sha256$<iterations>$<salt>$<hash>`
+/// (salt and hash are base64 encoded)
+pub fn hash_answer(answer: &str) -> Result<String, VaultError> {
+    hash_answer_with_iterations(answer, PBKDF2_ITERATIONS)
+}
+
+fn hash_answer_with_iterations(answer: &str, iterations: u32) -> Result<String, VaultError> {
+    let iterations = NonZeroU32::new(iterations)
+        .ok_or_else(|| VaultError::UnexpectedError("iterations must be positive".into()))?;
+
+    let mut salt = [0u8; SALT_LENGTH];
+    SystemRandom::new()
+        .fill(&mut salt)
+        .map_err(|_| VaultError::UnexpectedError("cannot generate the salt".into()))?;
+
+    let mut hash = [0u8; HASH_LENGTH];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        &salt,
+        answer.as_bytes(),
+        &mut hash,
+    );
+
+    Ok(format!(
+        "{}${}${}${}",
+        PBKDF2_SHA256,
+        iterations,
+        STANDARD_NO_PAD.encode(salt),
+        STANDARD_NO_PAD.encode(hash)
+    ))
+}
+
+/// Check the answer against the stored one, in constant time
+pub fn verify_answer(stored: &str, answer: &str) -> AnswerMatch {
+    let mut parts = stored.split('$');
+
+    if parts.next() != Some(PBKDF2_SHA256) {
+        // legacy plaintext answer
+        return if bool::from(stored.as_bytes().ct_eq(answer.as_bytes())) {
+            AnswerMatch::ValidNeedsRehash
+        } else {
+            AnswerMatch::Invalid
+        };
+    }
+
+    let parsed = (|| {
+        let iterations = parts.next()?.parse::<NonZeroU32>().ok()?;
+        let salt = STANDARD_NO_PAD.decode(parts.next()?).ok()?;
+        let hash = STANDARD_NO_PAD.decode(parts.next()?).ok()?;
+        match parts.next() {
+            None => Some((iterations, salt, hash)),
+            Some(_) => None,
+        }
+    })();
+
+    let Some((iterations, salt, hash)) = parsed else {
+        log::error!("Cannot parse a stored answer, it will never match");
+        return AnswerMatch::Invalid;
+    };
+
+    // pbkdf2::verify compares in constant time
+    match pbkdf2::verify(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        &salt,
+        answer.as_bytes(),
+        &hash,
+    ) {
+        Ok(()) if iterations.get() < PBKDF2_ITERATIONS => AnswerMatch::ValidNeedsRehash,
+        Ok(()) => AnswerMatch::Valid,
+        Err(_) => AnswerMatch::Invalid,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_answer_with_iterations, verify_answer, AnswerMatch, PBKDF2_ITERATIONS};
+
+    #[test]
+    fn hashed_answer_can_be_verified() {
+        let stored = hash_answer_with_iterations("some answer", PBKDF2_ITERATIONS).unwrap();
+
+        assert!(stored.starts_with("pbkdf2-sha256$600000$"));
+        assert!(!stored.contains("some answer"));
+        assert_eq!(AnswerMatch::Valid, verify_answer(&stored, "some answer"));
+        assert_eq!(AnswerMatch::Invalid, verify_answer(&stored, "other answer"));
+    }
+
+    #[test]
+    fn same_answer_gets_a_different_salt() {
+        assert_ne!(
+            hash_answer_with_iterations("some answer", 1).unwrap(),
+            hash_answer_with_iterations("some answer", 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn outdated_hash_needs_rehash() {
+        let stored = hash_answer_with_iterations("some answer", 1000).unwrap();
+
+        assert_eq!(
+            AnswerMatch::ValidNeedsRehash,
+            verify_answer(&stored, "some answer")
+        );
+        assert_eq!(AnswerMatch::Invalid, verify_answer(&stored, "other answer"));
+    }
+
+    #[test]
+    fn legacy_plaintext_answer_needs_rehash() {
+        assert_eq!(
+            AnswerMatch::ValidNeedsRehash,
+            verify_answer("some answer", "some answer")
+        );
+        assert_eq!(
+            AnswerMatch::Invalid,
+            verify_answer("some answer", "other answer")
+        );
+    }
+
+    #[test]
+    fn malformed_hash_never_matches() {
+        assert_eq!(
+            AnswerMatch::Invalid,
+            verify_answer("pbkdf2-sha256$1000$not base64$", "")
+        );
+        assert_eq!(
+            AnswerMatch::Invalid,
+            verify_answer("pbkdf2-sha256$0$AAAA$AAAA", "")
+        );
+    }
+}
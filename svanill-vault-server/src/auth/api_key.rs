@@ -0,0 +1,214 @@
+use crate::errors::VaultError;
+use ring::digest;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fmt;
+use std::str::FromStr;
+use subtle::ConstantTimeEq;
+
+/// Prefix of every API key, so that they can't be mistaken for auth tokens
+pub const API_KEY_PREFIX: &str = "svk_";
+
+const ID_LENGTH: usize = 8;
+const SECRET_LENGTH: usize = 32;
+
+/// What an API key allows to do. Each scope includes the previous ones
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiKeyScope {
+    /// List and download the files
+    Read,
+    /// Also upload files, overwriting the existing ones
+    Write,
+    /// Also remove files
+    Delete,
+}
+
+impl ApiKeyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::Read => "read",
+            ApiKeyScope::Write => "write",
+            ApiKeyScope::Delete => "delete",
+        }
+    }
+}
+
+impl FromStr for ApiKeyScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(ApiKeyScope::Read),
+            "write" => Ok(ApiKeyScope::Write),
+            "delete" => Ok(ApiKeyScope::Delete),
+            _ => Err(format!(
+                "unknown scope {s}, expected one of: read, write, delete"
+            )),
+        }
+    }
+}
+
+/// An API key, in the format `svk_<id>_<secret>` (both hex encoded).
+/// Only the id and a hash of the secret are stored.
+pub struct ApiKey {
+    pub id: String,
+    secret: String,
+}
+
+impl ApiKey {
+    pub fn generate() -> Result<ApiKey, VaultError> {
+        let rng = SystemRandom::new();
+        let mut id = [0u8; ID_LENGTH];
+        let mut secret = [0u8; SECRET_LENGTH];
+
+        rng.fill(&mut id)
+            .and_then(|_| rng.fill(&mut secret))
+            .map_err(|_| VaultError::UnexpectedError("cannot generate an API key".into()))?;
+
+        Ok(ApiKey {
+            id: hex::encode(id),
+            secret: hex::encode(secret),
+        })
+    }
+
+    /// Parse a string that looks like an API key
+    pub fn parse(key: &str) -> Option<ApiKey> {
+        let (id, secret) = key.strip_prefix(API_KEY_PREFIX)?.split_once('_')?;
+
+        let is_hex =
+            |s: &str, len: usize| s.len() == len * 2 && s.bytes().all(|b| b.is_ascii_hexdigit());
+
+        if !is_hex(id, ID_LENGTH) || !is_hex(secret, SECRET_LENGTH) {
+            return None;
+        }
+
+        Some(ApiKey {
+            id: id.to_owned(),
+            secret: secret.to_owned(),
+        })
+    }
+
+    /// Hex encoded SHA-256 of the secret. The secret is random and long,
+    /// so a fast hash is enough to keep it safe
+    pub fn secret_hash(&self) -> String {
+        hex::encode(digest::digest(&digest::SHA256, self.secret.as_bytes()))
+    }
+
+    /// Check the secret against the stored hash, in constant time
+    pub fn matches(&self, secret_hash: &str) -> bool {
+        bool::from(self.secret_hash().as_bytes().ct_eq(secret_hash.as_bytes()))
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}{}_{}", API_KEY_PREFIX, self.id, self.secret)
+    }
+}
+
+/// What the API key used to authenticate the request allows.
+/// Requests authenticated by an auth token have no grant, and can do anything
+#[derive(Clone, Debug)]
+pub struct ApiKeyGrant {
+    pub scope: ApiKeyScope,
+    pub prefix: Option<String>,
+}
+
+impl ApiKeyGrant {
+    /// Fail unless the grant includes the scope
+    pub fn check_scope(&self, scope: ApiKeyScope) -> Result<(), VaultError> {
+        if self.scope < scope {
+            return Err(VaultError::InsufficientScope(format!(
+                "the API key does not have the {} scope",
+                scope.as_str()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Tell if the grant covers the file
+    pub fn allows_file(&self, filename: &str) -> bool {
+        self.prefix
+            .as_ref()
+            .is_none_or(|prefix| filename.starts_with(prefix))
+    }
+
+    /// Fail unless the grant includes the scope, for the file
+    pub fn check(&self, scope: ApiKeyScope, filename: &str) -> Result<(), VaultError> {
+        self.check_scope(scope)?;
+
+        if !self.allows_file(filename) {
+            return Err(VaultError::InsufficientScope(
+                "the API key does not allow access to this file".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiKey, ApiKeyGrant, ApiKeyScope};
+
+    #[test]
+    fn generated_key_can_be_parsed() {
+        let key = ApiKey::generate().unwrap();
+        let as_string = key.to_string();
+
+        assert!(as_string.starts_with("svk_"));
+
+        let parsed = ApiKey::parse(&as_string).unwrap();
+        assert_eq!(key.id, parsed.id);
+        assert!(parsed.matches(&key.secret_hash()));
+        assert!(!ApiKey::generate().unwrap().matches(&key.secret_hash()));
+    }
+
+    #[test]
+    fn garbage_keys_are_rejected() {
+        let key = ApiKey::generate().unwrap().to_string();
+
+        for garbage in [
+            "",
+            "svk_",
+            "svk_abc_def",
+            &key[4..],
+            &key[..key.len() - 1],
+            &key.replace('_', "-"),
+        ] {
+            assert!(
+                ApiKey::parse(garbage).is_none(),
+                "{garbage} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn each_scope_includes_the_previous_ones() {
+        let grant = ApiKeyGrant {
+            scope: ApiKeyScope::Write,
+            prefix: None,
+        };
+
+        assert!(grant.check_scope(ApiKeyScope::Read).is_ok());
+        assert!(grant.check_scope(ApiKeyScope::Write).is_ok());
+        assert!(grant.check_scope(ApiKeyScope::Delete).is_err());
+    }
+
+    #[test]
+    fn prefix_restricts_the_files() {
+        let grant = ApiKeyGrant {
+            scope: ApiKeyScope::Delete,
+            prefix: Some("ci/".to_owned()),
+        };
+
+        assert!(grant.check(ApiKeyScope::Delete, "ci/secrets").is_ok());
+        assert!(grant.check(ApiKeyScope::Delete, "personal").is_err());
+    }
+
+    #[test]
+    fn scope_from_str() {
+        assert_eq!(Ok(ApiKeyScope::Read), "read".parse());
+        assert!("admin".parse::<ApiKeyScope>().is_err());
+    }
+}
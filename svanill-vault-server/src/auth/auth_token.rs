@@ -1,18 +1,42 @@
-use ring::{hmac, rand::SecureRandom};
+use crate::auth::token_keys::TokenKeys;
+use ring::{digest, hmac, rand::SecureRandom};
 use std::cmp::{Eq, PartialEq};
 use std::fmt;
 use std::hash::Hash;
 
-#[derive(Eq, PartialEq, Hash)]
+/// Length of the hex encoded HMAC-SHA256 tag prefixing every token
+const TAG_HEX_LENGTH: usize = 64;
+
+#[derive(Clone, Eq, PartialEq, Hash)]
 pub struct AuthToken(pub String);
 
 impl AuthToken {
-    pub fn new(crypto_key: &ring::hmac::Key) -> AuthToken {
+    pub fn new(token_keys: &TokenKeys) -> AuthToken {
         let token = generate_token();
-        let tag = hmac::sign(crypto_key, &token);
+        let tag = hmac::sign(token_keys.signing_key(), &token);
         let signed_token = format!("{}{}", hex::encode(tag), hex::encode(token.as_ref()));
         AuthToken(signed_token)
     }
+
+    /// Check that the token was signed by one of our keys
+    pub fn has_valid_signature(&self, token_keys: &TokenKeys) -> bool {
+        if self.0.len() <= TAG_HEX_LENGTH || !self.0.is_char_boundary(TAG_HEX_LENGTH) {
+            return false;
+        }
+
+        let (hex_tag, hex_token) = self.0.split_at(TAG_HEX_LENGTH);
+
+        match (hex::decode(hex_tag), hex::decode(hex_token)) {
+            (Ok(tag), Ok(token)) => token_keys.verify(&token, &tag),
+            _ => false,
+        }
+    }
+
+    /// Hex encoded SHA-256 of the token. It identifies the session without
+    /// disclosing the token, so it's safe to store or to show it.
+    pub fn session_id(&self) -> String {
+        hex::encode(digest::digest(&digest::SHA256, self.0.as_bytes()))
+    }
 }
 
 impl fmt::Display for AuthToken {
@@ -27,3 +51,30 @@ fn generate_token() -> [u8; TOKEN_LENGTH] {
     ring::rand::SystemRandom::new().fill(&mut token).unwrap();
     token
 }
+
+#[cfg(test)]
+mod tests {
+    use super::AuthToken;
+    use crate::auth::token_keys::TokenKeys;
+
+    #[test]
+    fn new_tokens_have_a_valid_signature() {
+        let keys = TokenKeys::generate().unwrap();
+        assert!(AuthToken::new(&keys).has_valid_signature(&keys));
+    }
+
+    #[test]
+    fn tokens_signed_by_other_keys_are_rejected() {
+        let keys = TokenKeys::generate().unwrap();
+        let other_keys = TokenKeys::generate().unwrap();
+        assert!(!AuthToken::new(&other_keys).has_valid_signature(&keys));
+    }
+
+    #[test]
+    fn garbage_tokens_are_rejected() {
+        let keys = TokenKeys::generate().unwrap();
+        for garbage in ["", "dummy-token", &"0".repeat(64), &"è".repeat(70)] {
+            assert!(!AuthToken(garbage.to_owned()).has_valid_signature(&keys));
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use ring::{hmac, rand};
+use thiserror::Error;
+
+/// Minimum length of the secret, in bytes
+const MIN_SECRET_LENGTH: usize = 32;
+
+/// Header of the challenges produced by svanill with its default settings
+/// (format version and PBKDF2 iterations), shared by the decoys
+const CHALLENGE_HEADER: &[u8] = &[0x00, 0x00, 0x01, 0x86, 0xa0];
+
+/// Length in bytes of a svanill challenge, when the answer is 16 random bytes
+/// hex encoded, as suggested. Decoys have the same length.
+const CHALLENGE_LENGTH: usize = 104;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum DecoyChallengesError {
+    #[error("the decoy secret is not hex encoded")]
+    NotHex,
+    #[error("the decoy secret is too short, it must be at least {MIN_SECRET_LENGTH} bytes long")]
+    TooShort,
+    #[error("cannot generate a decoy secret")]
+    CannotGenerate,
+}
+
+/// Forge the challenges of the users that do not exist, so that
+/// nobody can tell which users exist by requesting their challenge.
+///
+/// A decoy is derived from the username with an HMAC under a server
+/// secret, so the same username always gets the same challenge.
+pub struct DecoyChallenges {
+    key: hmac::Key,
+}
+
+impl DecoyChallenges {
+    pub fn new(key: hmac::Key) -> DecoyChallenges {
+        DecoyChallenges { key }
+    }
+
+    /// Generate a random secret. The decoys will change once
+    /// the server restarts, hinting that their users don't exist.
+    pub fn generate() -> Result<DecoyChallenges, DecoyChallengesError> {
+        let rng = rand::SystemRandom::new();
+        let key = hmac::Key::generate(hmac::HMAC_SHA256, &rng)
+            .map_err(|_| DecoyChallengesError::CannotGenerate)?;
+        Ok(DecoyChallenges::new(key))
+    }
+
+    /// Use a hex encoded secret
+    pub fn from_hex(hex_secret: &str) -> Result<DecoyChallenges, DecoyChallengesError> {
+        let secret = hex::decode(hex_secret.trim()).map_err(|_| DecoyChallengesError::NotHex)?;
+
+        if secret.len() < MIN_SECRET_LENGTH {
+            return Err(DecoyChallengesError::TooShort);
+        }
+
+        Ok(DecoyChallenges::new(hmac::Key::new(
+            hmac::HMAC_SHA256,
+            &secret,
+        )))
+    }
+
+    /// Return the decoy challenge of the username, hex encoded like the real ones
+    pub fn challenge_for(&self, username: &str) -> String {
+        self.derive(&[username.as_bytes()])
+    }
+
+    /// Return the decoy challenge of one of the username's keys
+    pub fn challenge_for_key(&self, username: &str, key_id: &str) -> String {
+        // a NUL can't appear in either, so different pairs can't collide
+        self.derive(&[username.as_bytes(), b"\0", key_id.as_bytes()])
+    }
+
+    fn derive(&self, parts: &[&[u8]]) -> String {
+        let mut challenge = CHALLENGE_HEADER.to_vec();
+        let mut counter: u32 = 0;
+
+        // expand the HMAC output up to the challenge length
+        while challenge.len() < CHALLENGE_LENGTH {
+            let mut ctx = hmac::Context::with_key(&self.key);
+            ctx.update(&counter.to_be_bytes());
+            for part in parts {
+                ctx.update(part);
+            }
+            challenge.extend_from_slice(ctx.sign().as_ref());
+            counter += 1;
+        }
+
+        challenge.truncate(CHALLENGE_LENGTH);
+        hex::encode(challenge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DecoyChallenges, DecoyChallengesError};
+
+    fn setup_decoys() -> DecoyChallenges {
+        DecoyChallenges::from_hex(&"ab".repeat(32)).unwrap()
+    }
+
+    #[test]
+    fn decoy_is_stable() {
+        let decoys = setup_decoys();
+
+        assert_eq!(decoys.challenge_for("alice"), decoys.challenge_for("alice"));
+        assert_eq!(
+            decoys.challenge_for("alice"),
+            setup_decoys().challenge_for("alice")
+        );
+        assert_ne!(decoys.challenge_for("alice"), decoys.challenge_for("bob"));
+    }
+
+    #[test]
+    fn each_key_gets_its_own_decoy() {
+        let decoys = setup_decoys();
+        let phone = decoys.challenge_for_key("alice", "phone");
+
+        assert_eq!(phone, decoys.challenge_for_key("alice", "phone"));
+        assert_ne!(phone, decoys.challenge_for_key("alice", "laptop"));
+        assert_ne!(phone, decoys.challenge_for("alice"));
+        assert!(phone.starts_with("00000186a0"));
+    }
+
+    #[test]
+    fn decoy_depends_on_the_secret() {
+        let other_decoys = DecoyChallenges::from_hex(&"cd".repeat(32)).unwrap();
+
+        assert_ne!(
+            setup_decoys().challenge_for("alice"),
+            other_decoys.challenge_for("alice")
+        );
+    }
+
+    #[test]
+    fn decoy_looks_like_a_svanill_challenge() {
+        let challenge = setup_decoys().challenge_for("alice");
+
+        assert_eq!(208, challenge.len());
+        assert!(challenge.starts_with("00000186a0"));
+    }
+
+    #[test]
+    fn secret_must_be_long_hex() {
+        assert_eq!(
+            Some(DecoyChallengesError::NotHex),
+            DecoyChallenges::from_hex("not hex").err()
+        );
+        assert_eq!(
+            Some(DecoyChallengesError::TooShort),
+            DecoyChallenges::from_hex("abcd").err()
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use lru_cache::LruCache;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Thresholds of the protection against brute-force attacks on the challenge's answer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoginThrottleConfig {
+    /// Failed attempts allowed before having to wait between attempts
+    pub free_failures: u32,
+    /// Wait after the first failure past the free ones, doubled at every further failure
+    pub backoff: Duration,
+    /// Failed attempts after which the client is locked out
+    pub lockout_failures: u32,
+    /// How long a lockout lasts. It's also the max backoff, and how long
+    /// a failure is remembered if no other failure follows
+    pub lockout_duration: Duration,
+}
+
+impl Default for LoginThrottleConfig {
+    fn default() -> Self {
+        LoginThrottleConfig {
+            free_failures: 5,
+            backoff: Duration::from_secs(1),
+            lockout_failures: 20,
+            lockout_duration: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+/// What a client is identified by, when counting its failures
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Subject {
+    Username,
+    Ip,
+}
+
+impl Subject {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Subject::Username => "username",
+            Subject::Ip => "ip",
+        }
+    }
+}
+
+/// A client that reached the failures limit and has been locked out
+#[derive(Debug, PartialEq, Eq)]
+pub struct Lockout {
+    pub subject: Subject,
+    pub value: String,
+    pub failures: u32,
+    pub duration: Duration,
+}
+
+struct Failures {
+    count: u32,
+    last_failure: Instant,
+    blocked_until: Instant,
+}
+
+/// Count the failed attempts to answer the challenge, per username and per client ip,
+/// and tell for how long a client must wait before trying again.
+pub struct LoginThrottle {
+    config: LoginThrottleConfig,
+    by_username: LruCache<String, Failures>,
+    by_ip: LruCache<String, Failures>,
+}
+
+pub type SharedLoginThrottle = Arc<Mutex<LoginThrottle>>;
+
+impl LoginThrottle {
+    pub fn new(capacity: usize, config: LoginThrottleConfig) -> LoginThrottle {
+        LoginThrottle {
+            config,
+            by_username: LruCache::new(capacity),
+            by_ip: LruCache::new(capacity),
+        }
+    }
+
+    /// Return how long the client must still wait before trying again, if it must
+    pub fn check(&mut self, username: &str, ip: Option<&str>, now: Instant) -> Option<Duration> {
+        let by_username = wait_time(self.by_username.get_mut(username), now);
+        let by_ip = ip.and_then(|ip| wait_time(self.by_ip.get_mut(ip), now));
+
+        by_username.max(by_ip)
+    }
+
+    /// Count a failed attempt, returning the lockouts it caused
+    pub fn record_failure(
+        &mut self,
+        username: &str,
+        ip: Option<&str>,
+        now: Instant,
+    ) -> Vec<Lockout> {
+        let config = self.config;
+        let mut lockouts = Vec::new();
+
+        let mut targets = vec![(Subject::Username, &mut self.by_username, username)];
+        if let Some(ip) = ip {
+            targets.push((Subject::Ip, &mut self.by_ip, ip));
+        }
+
+        for (subject, cache, value) in targets {
+            let failures = match cache.get_mut(value) {
+                Some(failures)
+                    if now.duration_since(failures.last_failure) < config.lockout_duration =>
+                {
+                    failures
+                }
+                _ => {
+                    cache.insert(
+                        value.to_owned(),
+                        Failures {
+                            count: 0,
+                            last_failure: now,
+                            blocked_until: now,
+                        },
+                    );
+                    cache.get_mut(value).unwrap()
+                }
+            };
+
+            failures.count += 1;
+            failures.last_failure = now;
+
+            let delay = delay_after(&config, failures.count);
+            failures.blocked_until = now + delay;
+
+            if failures.count == config.lockout_failures {
+                lockouts.push(Lockout {
+                    subject,
+                    value: value.to_owned(),
+                    failures: failures.count,
+                    duration: delay,
+                });
+            }
+        }
+
+        lockouts
+    }
+
+    /// Forget the failures of the user, who proved to know the answer
+    pub fn record_success(&mut self, username: &str) {
+        self.by_username.remove(username);
+    }
+}
+
+impl Default for LoginThrottle {
+    fn default() -> Self {
+        LoginThrottle::new(1000, LoginThrottleConfig::default())
+    }
+}
+
+fn wait_time(failures: Option<&mut Failures>, now: Instant) -> Option<Duration> {
+    failures
+        .map(|failures| failures.blocked_until.saturating_duration_since(now))
+        .filter(|wait| !wait.is_zero())
+}
+
+fn delay_after(config: &LoginThrottleConfig, failures: u32) -> Duration {
+    if failures >= config.lockout_failures {
+        config.lockout_duration
+    } else if failures > config.free_failures {
+        let exponent = (failures - config.free_failures - 1).min(31);
+        config
+            .backoff
+            .saturating_mul(1 << exponent)
+            .min(config.lockout_duration)
+    } else {
+        Duration::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LoginThrottle, LoginThrottleConfig, Subject};
+    use std::time::{Duration, Instant};
+
+    fn setup_throttle() -> LoginThrottle {
+        LoginThrottle::new(
+            10,
+            LoginThrottleConfig {
+                free_failures: 2,
+                backoff: Duration::from_secs(1),
+                lockout_failures: 5,
+                lockout_duration: Duration::from_secs(60),
+            },
+        )
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_after_the_free_failures() {
+        let mut throttle = setup_throttle();
+        let now = Instant::now();
+
+        for _ in 0..2 {
+            throttle.record_failure("alice", None, now);
+            assert_eq!(None, throttle.check("alice", None, now));
+        }
+
+        throttle.record_failure("alice", None, now);
+        assert_eq!(
+            Some(Duration::from_secs(1)),
+            throttle.check("alice", None, now)
+        );
+
+        throttle.record_failure("alice", None, now);
+        assert_eq!(
+            Some(Duration::from_secs(2)),
+            throttle.check("alice", None, now)
+        );
+
+        let later = now + Duration::from_secs(2);
+        assert_eq!(None, throttle.check("alice", None, later));
+        assert_eq!(None, throttle.check("bob", None, now));
+    }
+
+    #[test]
+    fn lockout_is_reported_once() {
+        let mut throttle = setup_throttle();
+        let now = Instant::now();
+
+        for _ in 0..4 {
+            assert!(throttle
+                .record_failure("alice", Some("10.0.0.1"), now)
+                .is_empty());
+        }
+
+        let lockouts = throttle.record_failure("alice", Some("10.0.0.1"), now);
+        assert_eq!(2, lockouts.len());
+        assert_eq!(Subject::Username, lockouts[0].subject);
+        assert_eq!(Subject::Ip, lockouts[1].subject);
+        assert_eq!("10.0.0.1", lockouts[1].value);
+        assert_eq!(Duration::from_secs(60), lockouts[1].duration);
+
+        // the ip is locked out for any username
+        assert_eq!(
+            Some(Duration::from_secs(60)),
+            throttle.check("bob", Some("10.0.0.1"), now)
+        );
+
+        assert!(throttle
+            .record_failure("alice", Some("10.0.0.1"), now)
+            .is_empty());
+    }
+
+    #[test]
+    fn failures_are_forgotten() {
+        let mut throttle = setup_throttle();
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            throttle.record_failure("alice", Some("10.0.0.1"), now);
+        }
+
+        // after a while without failures
+        let later = now + Duration::from_secs(60);
+        throttle.record_failure("alice", Some("10.0.0.1"), later);
+        assert_eq!(None, throttle.check("alice", Some("10.0.0.1"), later));
+
+        // after a success, but only for the username
+        throttle.record_failure("alice", Some("10.0.0.1"), later);
+        throttle.record_failure("alice", Some("10.0.0.1"), later);
+        throttle.record_success("alice");
+        assert_eq!(None, throttle.check("alice", None, later));
+        assert_eq!(
+            Some(Duration::from_secs(1)),
+            throttle.check("alice", Some("10.0.0.1"), later)
+        );
+    }
+}
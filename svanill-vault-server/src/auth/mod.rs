@@ -1,4 +1,28 @@
+pub mod answer_hash;
+pub mod api_key;
 pub mod auth_token;
+pub mod decoy_challenge;
+pub mod login_throttle;
+pub mod signed_token;
+pub mod sqlite_token_store;
+pub mod token_keys;
+pub mod token_store;
 pub mod tokens_cache;
+pub mod totp;
+
+use std::time::Duration;
 
 pub struct Username(pub String);
+
+/// How the authorization tokens are issued and verified
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TokenMode {
+    /// Random tokens, that must be looked up in the token store
+    #[default]
+    Opaque,
+    /// Self-contained signed tokens, valid until they expire unless revoked
+    Signed {
+        lifetime: Duration,
+        max_lifetime: Option<Duration>,
+    },
+}
@@ -0,0 +1,196 @@
+use crate::auth::auth_token::AuthToken;
+use crate::auth::token_keys::TokenKeys;
+use base64::{engine::general_purpose, Engine as _};
+use ring::hmac;
+use ring::rand::SecureRandom;
+use std::time::Duration;
+
+/// Every signed token starts with this prefix, so that we can tell
+/// it apart from the opaque tokens (and change format in the future)
+const SIGNED_TOKEN_PREFIX: &str = "v1.";
+
+/// Scope of the tokens issued after a successful login
+pub const DEFAULT_SCOPE: &str = "vault";
+
+/// What a signed token says about itself
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Claims {
+    /// The user the token belongs to
+    pub sub: String,
+    /// When the token was issued, in milliseconds since the unix epoch
+    pub iat: i64,
+    /// When the token expires, in milliseconds since the unix epoch
+    pub exp: i64,
+    /// What the token can be used for
+    pub scope: String,
+    /// Unique id of the token, used to revoke it
+    pub jti: String,
+    /// When the user logged in, in milliseconds since the unix epoch.
+    /// Refreshed tokens keep it, so that sessions can't last forever
+    pub auth_time: i64,
+}
+
+impl Claims {
+    pub fn new(
+        username: &str,
+        now: i64,
+        lifetime: Duration,
+        max_lifetime: Option<Duration>,
+    ) -> Claims {
+        Claims {
+            sub: username.to_owned(),
+            iat: now,
+            exp: expiration(now, now, lifetime, max_lifetime),
+            scope: DEFAULT_SCOPE.to_owned(),
+            jti: generate_jti(),
+            auth_time: now,
+        }
+    }
+
+    /// The claims of a new token replacing this one, None if the
+    /// session already reached its max lifetime
+    pub fn refreshed(
+        &self,
+        now: i64,
+        lifetime: Duration,
+        max_lifetime: Option<Duration>,
+    ) -> Option<Claims> {
+        let exp = expiration(self.auth_time, now, lifetime, max_lifetime);
+
+        if exp <= now {
+            return None;
+        }
+
+        Some(Claims {
+            iat: now,
+            exp,
+            jti: generate_jti(),
+            ..self.clone()
+        })
+    }
+}
+
+fn expiration(auth_time: i64, now: i64, lifetime: Duration, max_lifetime: Option<Duration>) -> i64 {
+    let exp = now + lifetime.as_millis() as i64;
+
+    match max_lifetime {
+        Some(max_lifetime) => exp.min(auth_time + max_lifetime.as_millis() as i64),
+        None => exp,
+    }
+}
+
+fn generate_jti() -> String {
+    let mut jti = [0u8; 16];
+    ring::rand::SystemRandom::new().fill(&mut jti).unwrap();
+    hex::encode(jti)
+}
+
+pub fn is_signed_token(token: &AuthToken) -> bool {
+    token.0.starts_with(SIGNED_TOKEN_PREFIX)
+}
+
+/// Build a self-contained token: `v1.<base64 json claims>.<base64 signature>`
+pub fn sign(claims: &Claims, token_keys: &TokenKeys) -> AuthToken {
+    let json = serde_json::to_vec(claims).expect("claims are always serializable");
+    let data = format!(
+        "{}{}",
+        SIGNED_TOKEN_PREFIX,
+        general_purpose::URL_SAFE_NO_PAD.encode(json)
+    );
+    let tag = hmac::sign(token_keys.signing_key(), data.as_bytes());
+
+    AuthToken(format!(
+        "{}.{}",
+        data,
+        general_purpose::URL_SAFE_NO_PAD.encode(tag)
+    ))
+}
+
+/// Return the token's claims, if it has been signed by one of our keys
+/// and it did not expire yet
+pub fn verify(token: &AuthToken, token_keys: &TokenKeys, now: i64) -> Option<Claims> {
+    let (data, encoded_tag) = token.0.rsplit_once('.')?;
+    let encoded_claims = data.strip_prefix(SIGNED_TOKEN_PREFIX)?;
+
+    let tag = general_purpose::URL_SAFE_NO_PAD.decode(encoded_tag).ok()?;
+
+    if !token_keys.verify(data.as_bytes(), &tag) {
+        return None;
+    }
+
+    let json = general_purpose::URL_SAFE_NO_PAD
+        .decode(encoded_claims)
+        .ok()?;
+    let claims = serde_json::from_slice::<Claims>(&json).ok()?;
+
+    if claims.exp <= now {
+        return None;
+    }
+
+    Some(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000;
+
+    #[test]
+    fn signed_tokens_can_be_verified() {
+        let keys = TokenKeys::generate().unwrap();
+        let claims = Claims::new("test_user", NOW, Duration::from_secs(60), None);
+        let token = sign(&claims, &keys);
+
+        assert!(is_signed_token(&token));
+        assert_eq!(Some(claims), verify(&token, &keys, NOW + 1000));
+    }
+
+    #[test]
+    fn expired_tokens_are_rejected() {
+        let keys = TokenKeys::generate().unwrap();
+        let claims = Claims::new("test_user", NOW, Duration::from_secs(60), None);
+        let token = sign(&claims, &keys);
+
+        assert_eq!(None, verify(&token, &keys, NOW + 60_000));
+    }
+
+    #[test]
+    fn tampered_tokens_are_rejected() {
+        let keys = TokenKeys::generate().unwrap();
+        let claims = Claims::new("test_user", NOW, Duration::from_secs(60), None);
+        let token = sign(&claims, &keys);
+
+        let mut other_claims = claims.clone();
+        other_claims.sub = "other_user".to_owned();
+        let other_token = sign(&other_claims, &keys);
+
+        // the claims of one token with the signature of the other
+        let (data, _) = other_token.0.rsplit_once('.').unwrap();
+        let (_, tag) = token.0.rsplit_once('.').unwrap();
+        let tampered = AuthToken(format!("{data}.{tag}"));
+
+        assert_eq!(None, verify(&tampered, &keys, NOW));
+        assert_eq!(None, verify(&token, &TokenKeys::generate().unwrap(), NOW));
+        assert_eq!(None, verify(&AuthToken("v1.garbage".into()), &keys, NOW));
+    }
+
+    #[test]
+    fn refreshed_claims_keep_the_session_start() {
+        let max_lifetime = Some(Duration::from_secs(90));
+        let claims = Claims::new("test_user", NOW, Duration::from_secs(60), max_lifetime);
+
+        let refreshed = claims
+            .refreshed(NOW + 50_000, Duration::from_secs(60), max_lifetime)
+            .unwrap();
+
+        assert_eq!(NOW, refreshed.auth_time);
+        assert_eq!(NOW + 90_000, refreshed.exp);
+        assert_ne!(claims.jti, refreshed.jti);
+
+        assert_eq!(
+            None,
+            claims.refreshed(NOW + 90_000, Duration::from_secs(60), max_lifetime)
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::auth::auth_token::AuthToken;
+use crate::auth::token_store::{ClientInfo, Session, TokenLifetime, TokenStore};
+use crate::db::models::{NewToken, Token};
+use crate::db::{actions, DbPool};
+use crate::errors::VaultError;
+use crate::utils::unix_timestamp;
+use diesel::r2d2::{ConnectionManager, PooledConnection};
+use diesel::{Connection, SqliteConnection};
+use std::time::Duration;
+
+/// How stale the last-seen time can be, so that we don't
+/// write to the database every time a token is used
+const LAST_SEEN_RESOLUTION: i64 = 60;
+
+/// Token store backed by the SQLite database, so that tokens survive
+/// a restart and can be shared by many instances using the same db.
+///
+/// Only a hash of each token is stored, so that whoever can read the
+/// database can't use the tokens.
+pub struct SqliteTokenStore {
+    pool: DbPool,
+    lifetime: TokenLifetime,
+}
+
+impl SqliteTokenStore {
+    pub fn new(pool: DbPool, timeout: Duration) -> SqliteTokenStore {
+        SqliteTokenStore::with_lifetime(pool, TokenLifetime::fixed(timeout))
+    }
+
+    pub fn with_lifetime(pool: DbPool, lifetime: TokenLifetime) -> SqliteTokenStore {
+        SqliteTokenStore { pool, lifetime }
+    }
+
+    fn get_conn(
+        &self,
+    ) -> Result<PooledConnection<ConnectionManager<SqliteConnection>>, VaultError> {
+        self.pool
+            .get()
+            .map_err(|e| VaultError::UnexpectedError(e.to_string()))
+    }
+}
+
+impl From<Token> for Session {
+    fn from(token: Token) -> Session {
+        Session {
+            id: token.token_hash,
+            created_at: token.created_at,
+            last_seen_at: token.last_seen_at,
+            client: ClientInfo {
+                ip: token.ip,
+                user_agent: token.user_agent,
+            },
+        }
+    }
+}
+
+impl TokenStore for SqliteTokenStore {
+    fn insert(
+        &self,
+        token: AuthToken,
+        username: String,
+        client: ClientInfo,
+    ) -> Result<(), VaultError> {
+        let now = unix_timestamp();
+
+        actions::insert_token(
+            &mut *self.get_conn()?,
+            &NewToken {
+                token_hash: &token.session_id(),
+                username: &username,
+                created_at: now,
+                expires_at: self.lifetime.expires_at(now, now),
+                last_seen_at: now,
+                ip: client.ip.as_deref(),
+                user_agent: client.user_agent.as_deref(),
+            },
+        )
+    }
+
+    fn get_username(&self, token: &AuthToken) -> Result<Option<String>, VaultError> {
+        let now = unix_timestamp();
+        let token_hash = token.session_id();
+        let conn = &mut *self.get_conn()?;
+
+        match actions::find_token(conn, &token_hash, now)? {
+            Some(found) => {
+                let expires_at = if self.lifetime.sliding {
+                    self.lifetime.expires_at(found.created_at, now)
+                } else {
+                    found.expires_at
+                };
+
+                if expires_at != found.expires_at
+                    || now - found.last_seen_at >= LAST_SEEN_RESOLUTION
+                {
+                    actions::touch_token(conn, &token_hash, expires_at, now)?;
+                }
+
+                Ok(Some(found.username))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn refresh(
+        &self,
+        token: &AuthToken,
+        new_token: AuthToken,
+    ) -> Result<Option<String>, VaultError> {
+        let now = unix_timestamp();
+        let token_hash = token.session_id();
+        let lifetime = self.lifetime;
+
+        self.get_conn()?.transaction(|conn| {
+            let found = match actions::find_token(conn, &token_hash, now)? {
+                Some(found) => found,
+                None => return Ok(None),
+            };
+
+            actions::delete_token(conn, &token_hash)?;
+            actions::insert_token(
+                conn,
+                &NewToken {
+                    token_hash: &new_token.session_id(),
+                    username: &found.username,
+                    created_at: found.created_at,
+                    expires_at: lifetime.expires_at(found.created_at, now),
+                    last_seen_at: now,
+                    ip: found.ip.as_deref(),
+                    user_agent: found.user_agent.as_deref(),
+                },
+            )?;
+
+            Ok(Some(found.username))
+        })
+    }
+
+    fn remove(&self, token: &AuthToken) -> Result<(), VaultError> {
+        actions::delete_token(&mut *self.get_conn()?, &token.session_id())?;
+        Ok(())
+    }
+
+    fn list_user_sessions(&self, username: &str) -> Result<Vec<Session>, VaultError> {
+        let tokens = actions::find_user_tokens(&mut *self.get_conn()?, username, unix_timestamp())?;
+        Ok(tokens.into_iter().map(Session::from).collect())
+    }
+
+    fn remove_user_session(&self, username: &str, session_id: &str) -> Result<bool, VaultError> {
+        actions::delete_user_token(&mut *self.get_conn()?, username, session_id)
+    }
+
+    fn remove_user_tokens(&self, username: &str) -> Result<(), VaultError> {
+        actions::delete_user_tokens(&mut *self.get_conn()?, username)?;
+        Ok(())
+    }
+
+    fn purge_expired(&self) -> Result<usize, VaultError> {
+        actions::delete_expired_tokens(&mut *self.get_conn()?, unix_timestamp())
+    }
+}
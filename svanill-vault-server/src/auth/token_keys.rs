@@ -0,0 +1,177 @@
+use ring::{hmac, rand};
+use thiserror::Error;
+
+/// Minimum length of a key, in bytes
+const MIN_KEY_LENGTH: usize = 32;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TokenKeysError {
+    #[error("no token key provided")]
+    Empty,
+    #[error("token key #{0} is not hex encoded")]
+    NotHex(usize),
+    #[error("token key #{0} is too short, it must be at least {MIN_KEY_LENGTH} bytes long")]
+    TooShort(usize),
+    #[error("cannot generate a token key")]
+    CannotGenerate,
+}
+
+/// The keys used to sign and verify the authorization tokens.
+///
+/// The first key signs the new tokens. Every key, the first one
+/// and the old ones, is accepted to verify a token, so that keys
+/// can be rotated without logging out everyone.
+pub struct TokenKeys {
+    keys: Vec<hmac::Key>,
+}
+
+impl TokenKeys {
+    pub fn new(current: hmac::Key, old: Vec<hmac::Key>) -> TokenKeys {
+        let mut keys = vec![current];
+        keys.extend(old);
+        TokenKeys { keys }
+    }
+
+    /// Generate a random key, every token signed with it
+    /// will become invalid once the server restarts.
+    pub fn generate() -> Result<TokenKeys, TokenKeysError> {
+        let rng = rand::SystemRandom::new();
+        let key = hmac::Key::generate(hmac::HMAC_SHA256, &rng)
+            .map_err(|_| TokenKeysError::CannotGenerate)?;
+        Ok(TokenKeys::new(key, vec![]))
+    }
+
+    /// Parse a list of hex encoded keys, separated by commas or whitespaces
+    /// (e.g. one per line). The first one is the current key.
+    pub fn from_hex_list(list: &str) -> Result<TokenKeys, TokenKeysError> {
+        let keys = list
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .enumerate()
+            .map(|(idx, hex_key)| {
+                let key = hex::decode(hex_key).map_err(|_| TokenKeysError::NotHex(idx + 1))?;
+
+                if key.len() < MIN_KEY_LENGTH {
+                    return Err(TokenKeysError::TooShort(idx + 1));
+                }
+
+                Ok(hmac::Key::new(hmac::HMAC_SHA256, &key))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if keys.is_empty() {
+            return Err(TokenKeysError::Empty);
+        }
+
+        Ok(TokenKeys { keys })
+    }
+
+    /// The key used to sign new tokens
+    pub fn signing_key(&self) -> &hmac::Key {
+        &self.keys[0]
+    }
+
+    /// Check that `tag` is the signature of `data` by any of the keys
+    pub fn verify(&self, data: &[u8], tag: &[u8]) -> bool {
+        self.keys
+            .iter()
+            .any(|key| hmac::verify(key, data, tag).is_ok())
+    }
+
+    /// Keys for something else than the tokens, each one derived from one of
+    /// these keys, so that they rotate along with them. Different purposes
+    /// get unrelated keys.
+    pub fn derive_keys(&self, purpose: &str) -> TokenKeys {
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| {
+                let tag = hmac::sign(key, purpose.as_bytes());
+                hmac::Key::new(hmac::HMAC_SHA256, tag.as_ref())
+            })
+            .collect();
+
+        TokenKeys { keys }
+    }
+}
+
+/// Generate a new random key, hex encoded
+pub fn generate_hex_key() -> Result<String, TokenKeysError> {
+    let mut key = [0u8; MIN_KEY_LENGTH];
+    rand::SecureRandom::fill(&rand::SystemRandom::new(), &mut key)
+        .map_err(|_| TokenKeysError::CannotGenerate)?;
+    Ok(hex::encode(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_1: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+    const KEY_2: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+
+    #[test]
+    fn parse_keys() {
+        assert!(TokenKeys::from_hex_list(KEY_1).is_ok());
+        assert!(TokenKeys::from_hex_list(&format!("{KEY_1}\n{KEY_2}\n")).is_ok());
+        assert!(TokenKeys::from_hex_list(&format!("{KEY_1},{KEY_2}")).is_ok());
+    }
+
+    #[test]
+    fn reject_bad_keys() {
+        assert_eq!(
+            Some(TokenKeysError::Empty),
+            TokenKeys::from_hex_list(" \n").err()
+        );
+        assert_eq!(
+            Some(TokenKeysError::NotHex(2)),
+            TokenKeys::from_hex_list(&format!("{KEY_1}\nnot-hex")).err()
+        );
+        assert_eq!(
+            Some(TokenKeysError::TooShort(1)),
+            TokenKeys::from_hex_list("00ff").err()
+        );
+    }
+
+    #[test]
+    fn old_keys_can_verify_but_not_sign() {
+        let old_keys = TokenKeys::from_hex_list(KEY_2).unwrap();
+        let keys = TokenKeys::from_hex_list(&format!("{KEY_1}\n{KEY_2}")).unwrap();
+
+        let old_tag = hmac::sign(old_keys.signing_key(), b"data");
+        let new_tag = hmac::sign(keys.signing_key(), b"data");
+
+        assert!(keys.verify(b"data", old_tag.as_ref()));
+        assert!(keys.verify(b"data", new_tag.as_ref()));
+        assert!(!old_keys.verify(b"data", new_tag.as_ref()));
+        assert!(!keys.verify(b"other data", new_tag.as_ref()));
+    }
+
+    #[test]
+    fn derived_keys_depend_on_the_purpose() {
+        let keys = TokenKeys::from_hex_list(KEY_1).unwrap();
+
+        let tag = hmac::sign(keys.derive_keys("files").signing_key(), b"data");
+
+        assert!(keys.derive_keys("files").verify(b"data", tag.as_ref()));
+        assert!(!keys.derive_keys("other").verify(b"data", tag.as_ref()));
+        assert!(!keys.verify(b"data", tag.as_ref()));
+    }
+
+    #[test]
+    fn derived_keys_rotate_along() {
+        let old_keys = TokenKeys::from_hex_list(KEY_2).unwrap();
+        let keys = TokenKeys::from_hex_list(&format!("{KEY_1}\n{KEY_2}")).unwrap();
+
+        let old_tag = hmac::sign(old_keys.derive_keys("files").signing_key(), b"data");
+
+        assert!(keys.derive_keys("files").verify(b"data", old_tag.as_ref()));
+    }
+
+    #[test]
+    fn generated_hex_keys_are_valid() {
+        let hex_key = generate_hex_key().unwrap();
+        assert_eq!(64, hex_key.len());
+        assert!(TokenKeys::from_hex_list(&hex_key).is_ok());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::auth::auth_token::AuthToken;
+use crate::errors::VaultError;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Somewhere to keep the tokens we handed out, alongside the user they represent.
+pub trait TokenStore: Send + Sync {
+    /// Store the token, alongside the user it represent and the client it was issued to.
+    fn insert(
+        &self,
+        token: AuthToken,
+        username: String,
+        client: ClientInfo,
+    ) -> Result<(), VaultError>;
+
+    /// Check if the token is present and not stale, then return the username associated.
+    /// With sliding expiration, using the token postpones its expiration.
+    fn get_username(&self, token: &AuthToken) -> Result<Option<String>, VaultError>;
+
+    /// Replace a valid token with a new one, keeping the time the session started
+    /// so that the session can't outlive its max lifetime. Return the username
+    /// associated, or None if the old token was not valid.
+    fn refresh(
+        &self,
+        token: &AuthToken,
+        new_token: AuthToken,
+    ) -> Result<Option<String>, VaultError>;
+
+    /// Forget the token.
+    fn remove(&self, token: &AuthToken) -> Result<(), VaultError>;
+
+    /// List the sessions of the user that are still valid.
+    fn list_user_sessions(&self, username: &str) -> Result<Vec<Session>, VaultError>;
+
+    /// Forget the token of one of the user's sessions.
+    /// Return false if the user has no such session.
+    fn remove_user_session(&self, username: &str, session_id: &str) -> Result<bool, VaultError>;
+
+    /// Forget every token associated to the user.
+    fn remove_user_tokens(&self, username: &str) -> Result<(), VaultError>;
+
+    /// Forget the stale tokens, return how many were removed.
+    /// Stores that evict stale tokens on their own don't need to do anything.
+    fn purge_expired(&self) -> Result<usize, VaultError> {
+        Ok(0)
+    }
+}
+
+/// Stores synchronize on their own (`TokenStore` requires `Send + Sync`), so they are
+/// shared without an outer lock.
+pub type SharedTokenStore = Arc<dyn TokenStore>;
+
+/// Who a token was issued to, as far as we can tell
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// A token that is still valid, as shown to the user it belongs to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Session {
+    /// See `AuthToken::session_id`
+    pub id: String,
+    /// When the user logged in, in seconds since the unix epoch
+    pub created_at: i64,
+    /// When the token was last used, in seconds since the unix epoch
+    pub last_seen_at: i64,
+    pub client: ClientInfo,
+}
+
+/// How long the tokens stay valid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenLifetime {
+    /// A token expires this long after it was issued, or last used with sliding expiration
+    pub timeout: Duration,
+    /// Postpone the expiration every time the token is used
+    pub sliding: bool,
+    /// Neither using nor refreshing a token extends the session past this long
+    pub max_lifetime: Option<Duration>,
+}
+
+impl TokenLifetime {
+    /// Tokens expire `timeout` after they were issued
+    pub fn fixed(timeout: Duration) -> TokenLifetime {
+        TokenLifetime {
+            timeout,
+            sliding: false,
+            max_lifetime: None,
+        }
+    }
+
+    /// When a token issued or used at `now` expires, given that its session
+    /// started at `created_at` (both in seconds since the unix epoch)
+    pub fn expires_at(&self, created_at: i64, now: i64) -> i64 {
+        let expires_at = now + self.timeout.as_secs() as i64;
+
+        match self.max_lifetime {
+            Some(max_lifetime) => expires_at.min(created_at + max_lifetime.as_secs() as i64),
+            None => expires_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenLifetime;
+    use std::time::Duration;
+
+    #[test]
+    fn max_lifetime_caps_the_expiration() {
+        let mut lifetime = TokenLifetime::fixed(Duration::from_secs(60));
+        assert_eq!(1060, lifetime.expires_at(1000, 1000));
+        assert_eq!(1560, lifetime.expires_at(1000, 1500));
+
+        lifetime.max_lifetime = Some(Duration::from_secs(100));
+        assert_eq!(1060, lifetime.expires_at(1000, 1000));
+        assert_eq!(1100, lifetime.expires_at(1000, 1050));
+    }
+}
@@ -1,31 +1,161 @@
 use crate::auth::auth_token::AuthToken;
+use crate::auth::token_store::{ClientInfo, Session, TokenLifetime, TokenStore};
+use crate::errors::VaultError;
+use crate::utils::unix_timestamp;
 use lru_cache::LruCache;
+use std::sync::{Mutex, MutexGuard};
 use std::time::{Duration, Instant};
 
+struct Entry {
+    username: String,
+    session: Session,
+    created_at: Instant,
+    refreshed_at: Instant,
+}
+
+impl Entry {
+    fn is_valid(&self, lifetime: &TokenLifetime) -> bool {
+        self.refreshed_at.elapsed() < lifetime.timeout
+            && lifetime
+                .max_lifetime
+                .is_none_or(|max_lifetime| self.created_at.elapsed() < max_lifetime)
+    }
+}
+
+/// In-process token store. Tokens are lost when the server restarts,
+/// and the least recently used ones are dropped when the cache is full.
 pub struct TokensCache {
-    timeout: Duration,
-    cache: LruCache<AuthToken, (String, Instant)>,
+    lifetime: TokenLifetime,
+    cache: Mutex<LruCache<AuthToken, Entry>>,
 }
 
 impl TokensCache {
     pub fn new(capacity: usize, timeout: Duration) -> TokensCache {
+        TokensCache::with_lifetime(capacity, TokenLifetime::fixed(timeout))
+    }
+
+    pub fn with_lifetime(capacity: usize, lifetime: TokenLifetime) -> TokensCache {
         TokensCache {
-            timeout,
-            cache: LruCache::new(capacity),
+            lifetime,
+            cache: Mutex::new(LruCache::new(capacity)),
         }
     }
-    pub fn insert(&mut self, token: AuthToken, username: String) {
-        self.cache.insert(token, (username, Instant::now()));
+
+    fn cache(&self) -> MutexGuard<'_, LruCache<AuthToken, Entry>> {
+        self.cache.lock().unwrap()
+    }
+}
+
+impl TokenStore for TokensCache {
+    fn insert(
+        &self,
+        token: AuthToken,
+        username: String,
+        client: ClientInfo,
+    ) -> Result<(), VaultError> {
+        let now = Instant::now();
+        let unix_now = unix_timestamp();
+        let session = Session {
+            id: token.session_id(),
+            created_at: unix_now,
+            last_seen_at: unix_now,
+            client,
+        };
+
+        self.cache().insert(
+            token,
+            Entry {
+                username,
+                session,
+                created_at: now,
+                refreshed_at: now,
+            },
+        );
+        Ok(())
+    }
+
+    fn get_username(&self, token: &AuthToken) -> Result<Option<String>, VaultError> {
+        if let Some(entry) = self.cache().get_mut(token) {
+            if entry.is_valid(&self.lifetime) {
+                if self.lifetime.sliding {
+                    entry.refreshed_at = Instant::now();
+                }
+                entry.session.last_seen_at = unix_timestamp();
+                return Ok(Some(entry.username.clone()));
+            }
+        }
+        Ok(None)
     }
 
-    /// Check if the token is present and not stale, then return the username associated.
-    pub fn get_username(&mut self, token: &AuthToken) -> Option<String> {
-        if let Some((username, refreshed_at)) = self.cache.get_mut(token) {
-            if refreshed_at.elapsed() < self.timeout {
-                return Some(username.clone());
+    fn refresh(
+        &self,
+        token: &AuthToken,
+        new_token: AuthToken,
+    ) -> Result<Option<String>, VaultError> {
+        let mut cache = self.cache();
+        match cache.remove(token) {
+            Some(entry) if entry.is_valid(&self.lifetime) => {
+                let username = entry.username.clone();
+                let session = Session {
+                    id: new_token.session_id(),
+                    last_seen_at: unix_timestamp(),
+                    ..entry.session
+                };
+
+                cache.insert(
+                    new_token,
+                    Entry {
+                        session,
+                        refreshed_at: Instant::now(),
+                        ..entry
+                    },
+                );
+                Ok(Some(username))
             }
+            _ => Ok(None),
+        }
+    }
+
+    fn remove(&self, token: &AuthToken) -> Result<(), VaultError> {
+        self.cache().remove(token);
+        Ok(())
+    }
+
+    fn list_user_sessions(&self, username: &str) -> Result<Vec<Session>, VaultError> {
+        Ok(self
+            .cache()
+            .iter()
+            .filter(|(_, entry)| entry.username == username && entry.is_valid(&self.lifetime))
+            .map(|(_, entry)| entry.session.clone())
+            .collect())
+    }
+
+    fn remove_user_session(&self, username: &str, session_id: &str) -> Result<bool, VaultError> {
+        let mut cache = self.cache();
+        let maybe_token = cache
+            .iter()
+            .find(|(_, entry)| entry.username == username && entry.session.id == session_id)
+            .map(|(token, _)| token.clone());
+
+        match maybe_token {
+            Some(token) => Ok(cache.remove(&token).is_some()),
+            None => Ok(false),
+        }
+    }
+
+    fn remove_user_tokens(&self, username: &str) -> Result<(), VaultError> {
+        let mut cache = self.cache();
+        let tokens: Vec<AuthToken> = cache
+            .iter()
+            .filter(|(_, entry)| entry.username == username)
+            .map(|(token, _)| token.clone())
+            .collect();
+
+        for token in tokens {
+            cache.remove(&token);
         }
-        None
+
+        Ok(())
     }
 }
 
@@ -34,3 +164,205 @@ impl Default for TokensCache {
         TokensCache::new(1, Duration::from_secs(1))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::TokensCache;
+    use crate::auth::auth_token::AuthToken;
+    use crate::auth::token_store::{ClientInfo, TokenLifetime, TokenStore};
+    use std::time::Duration;
+
+    #[test]
+    fn remove_user_tokens_only_affect_that_user() {
+        let cache = TokensCache::new(10, Duration::from_secs(60));
+        cache
+            .insert(
+                AuthToken("a1".to_owned()),
+                "alice".to_owned(),
+                ClientInfo::default(),
+            )
+            .unwrap();
+        cache
+            .insert(
+                AuthToken("a2".to_owned()),
+                "alice".to_owned(),
+                ClientInfo::default(),
+            )
+            .unwrap();
+        cache
+            .insert(
+                AuthToken("b1".to_owned()),
+                "bob".to_owned(),
+                ClientInfo::default(),
+            )
+            .unwrap();
+
+        cache.remove_user_tokens("alice").unwrap();
+
+        assert_eq!(
+            None,
+            cache.get_username(&AuthToken("a1".to_owned())).unwrap()
+        );
+        assert_eq!(
+            None,
+            cache.get_username(&AuthToken("a2".to_owned())).unwrap()
+        );
+        assert_eq!(
+            Some("bob".to_owned()),
+            cache.get_username(&AuthToken("b1".to_owned())).unwrap()
+        );
+    }
+
+    #[test]
+    fn stale_tokens_are_ignored() {
+        let cache = TokensCache::new(10, Duration::ZERO);
+        cache
+            .insert(
+                AuthToken("a1".to_owned()),
+                "alice".to_owned(),
+                ClientInfo::default(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            None,
+            cache.get_username(&AuthToken("a1".to_owned())).unwrap()
+        );
+    }
+
+    #[test]
+    fn sliding_expiration_keeps_used_tokens_alive() {
+        let cache = TokensCache::with_lifetime(
+            10,
+            TokenLifetime {
+                timeout: Duration::from_millis(100),
+                sliding: true,
+                max_lifetime: None,
+            },
+        );
+        cache
+            .insert(
+                AuthToken("a1".to_owned()),
+                "alice".to_owned(),
+                ClientInfo::default(),
+            )
+            .unwrap();
+
+        for _ in 0..4 {
+            std::thread::sleep(Duration::from_millis(40));
+            assert_eq!(
+                Some("alice".to_owned()),
+                cache.get_username(&AuthToken("a1".to_owned())).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn max_lifetime_ends_the_session() {
+        let cache = TokensCache::with_lifetime(
+            10,
+            TokenLifetime {
+                timeout: Duration::from_secs(60),
+                sliding: true,
+                max_lifetime: Some(Duration::from_millis(50)),
+            },
+        );
+        cache
+            .insert(
+                AuthToken("a1".to_owned()),
+                "alice".to_owned(),
+                ClientInfo::default(),
+            )
+            .unwrap();
+
+        std::thread::sleep(Duration::from_millis(60));
+
+        assert_eq!(
+            None,
+            cache.get_username(&AuthToken("a1".to_owned())).unwrap()
+        );
+        assert_eq!(
+            None,
+            cache
+                .refresh(&AuthToken("a1".to_owned()), AuthToken("a2".to_owned()))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn refresh_replaces_the_token() {
+        let cache = TokensCache::new(10, Duration::from_secs(60));
+        cache
+            .insert(
+                AuthToken("a1".to_owned()),
+                "alice".to_owned(),
+                ClientInfo::default(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            Some("alice".to_owned()),
+            cache
+                .refresh(&AuthToken("a1".to_owned()), AuthToken("a2".to_owned()))
+                .unwrap()
+        );
+        assert_eq!(
+            None,
+            cache.get_username(&AuthToken("a1".to_owned())).unwrap()
+        );
+        assert_eq!(
+            Some("alice".to_owned()),
+            cache.get_username(&AuthToken("a2".to_owned())).unwrap()
+        );
+    }
+
+    #[test]
+    fn list_and_remove_user_sessions() {
+        let cache = TokensCache::new(10, Duration::from_secs(60));
+        let client = ClientInfo {
+            ip: Some("203.0.113.7".to_owned()),
+            user_agent: Some("some-agent".to_owned()),
+        };
+        cache
+            .insert(
+                AuthToken("a1".to_owned()),
+                "alice".to_owned(),
+                client.clone(),
+            )
+            .unwrap();
+        cache
+            .insert(
+                AuthToken("a2".to_owned()),
+                "alice".to_owned(),
+                ClientInfo::default(),
+            )
+            .unwrap();
+        cache
+            .insert(
+                AuthToken("b1".to_owned()),
+                "bob".to_owned(),
+                ClientInfo::default(),
+            )
+            .unwrap();
+
+        let mut sessions = cache.list_user_sessions("alice").unwrap();
+        sessions.sort_by_key(|session| session.client.ip.is_none());
+
+        assert_eq!(2, sessions.len());
+        assert_eq!(AuthToken("a1".to_owned()).session_id(), sessions[0].id);
+        assert_eq!(client, sessions[0].client);
+
+        // can't remove someone else's session
+        assert!(!cache.remove_user_session("bob", &sessions[0].id).unwrap());
+        assert!(cache.remove_user_session("alice", &sessions[0].id).unwrap());
+
+        assert_eq!(
+            None,
+            cache.get_username(&AuthToken("a1".to_owned())).unwrap()
+        );
+        assert_eq!(
+            Some("alice".to_owned()),
+            cache.get_username(&AuthToken("a2".to_owned())).unwrap()
+        );
+    }
+}
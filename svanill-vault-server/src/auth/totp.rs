@@ -0,0 +1,167 @@
+use crate::errors::VaultError;
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+use subtle::ConstantTimeEq;
+
+/// Seconds each code is valid for
+pub const TOTP_STEP: i64 = 30;
+/// Length of the codes
+pub const TOTP_DIGITS: u32 = 6;
+/// How many steps before and after the current one are accepted, to tolerate clock drift
+const TOTP_SKEW: i64 = 1;
+/// Length of the secret, 160 bits as recommended by RFC 4226
+const SECRET_LENGTH: usize = 20;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// The secret shared with the user's authenticator app (RFC 6238, HMAC-SHA1)
+pub struct TotpSecret(Vec<u8>);
+
+impl TotpSecret {
+    pub fn generate() -> Result<TotpSecret, VaultError> {
+        let mut secret = vec![0u8; SECRET_LENGTH];
+
+        SystemRandom::new()
+            .fill(&mut secret)
+            .map_err(|_| VaultError::UnexpectedError("cannot generate a TOTP secret".into()))?;
+
+        Ok(TotpSecret(secret))
+    }
+
+    /// Read a secret as stored in the db
+    pub fn from_hex(secret: &str) -> Option<TotpSecret> {
+        hex::decode(secret).ok().map(TotpSecret)
+    }
+
+    /// The secret as stored in the db
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.0)
+    }
+
+    /// The secret as shown to the user, base32 encoded without padding
+    pub fn to_base32(&self) -> String {
+        let mut encoded = String::with_capacity(self.0.len().div_ceil(5) * 8);
+        let mut buffer: u32 = 0;
+        let mut bits = 0;
+
+        for byte in self.0.iter() {
+            buffer = (buffer << 8) | u32::from(*byte);
+            bits += 8;
+
+            while bits >= 5 {
+                bits -= 5;
+                encoded.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+            }
+        }
+
+        if bits > 0 {
+            encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+        }
+
+        encoded
+    }
+
+    /// The URI to enroll the secret in an authenticator app, usually shown as a QR code
+    pub fn otpauth_uri(&self, issuer: &str, username: &str) -> String {
+        format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+            percent_encode(issuer),
+            percent_encode(username),
+            self.to_base32(),
+            percent_encode(issuer),
+            TOTP_DIGITS,
+            TOTP_STEP
+        )
+    }
+
+    /// The code of a time step (the HOTP code of the step counter, RFC 4226)
+    pub fn code_at(&self, step: i64) -> String {
+        let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, &self.0);
+        let digest = hmac::sign(&key, &step.to_be_bytes());
+        let digest = digest.as_ref();
+
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let truncated = u32::from_be_bytes([
+            digest[offset] & 0x7f,
+            digest[offset + 1],
+            digest[offset + 2],
+            digest[offset + 3],
+        ]);
+
+        format!(
+            "{:0width$}",
+            truncated % 10u32.pow(TOTP_DIGITS),
+            width = TOTP_DIGITS as usize
+        )
+    }
+
+    /// Return the time step of the code, if it is valid at `now` (a unix timestamp).
+    /// Codes of a step not after `last_used_step` are rejected, so that they can't be replayed
+    pub fn verify(&self, code: &str, now: i64, last_used_step: Option<i64>) -> Option<i64> {
+        let current_step = now / TOTP_STEP;
+
+        (current_step - TOTP_SKEW..=current_step + TOTP_SKEW)
+            .filter(|step| last_used_step.is_none_or(|last| *step > last))
+            .find(|step| bool::from(self.code_at(*step).as_bytes().ct_eq(code.as_bytes())))
+    }
+}
+
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TotpSecret;
+
+    fn rfc_secret() -> TotpSecret {
+        TotpSecret(b"12345678901234567890".to_vec())
+    }
+
+    #[test]
+    fn codes_match_the_rfc_6238_test_vectors() {
+        let secret = rfc_secret();
+
+        // The RFC lists 8 digits codes, ours are their last 6 digits
+        assert_eq!("287082", secret.code_at(59 / 30));
+        assert_eq!("081804", secret.code_at(1111111109 / 30));
+        assert_eq!("005924", secret.code_at(1234567890 / 30));
+        assert_eq!("279037", secret.code_at(2000000000 / 30));
+    }
+
+    #[test]
+    fn secret_is_base32_encoded() {
+        assert_eq!("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", rfc_secret().to_base32());
+        assert_eq!("MZXW6", TotpSecret(b"foo".to_vec()).to_base32());
+    }
+
+    #[test]
+    fn codes_are_valid_once_and_within_the_skew() {
+        let secret = rfc_secret();
+        let now = 1111111109;
+        let step = now / 30;
+
+        assert_eq!(Some(step), secret.verify("081804", now, None));
+        assert_eq!(Some(step), secret.verify("081804", now + 30, None));
+        assert_eq!(None, secret.verify("081804", now + 60, None));
+        assert_eq!(None, secret.verify("081804", now, Some(step)));
+        assert_eq!(None, secret.verify("000000", now, None));
+    }
+
+    #[test]
+    fn otpauth_uri_escapes_the_label() {
+        let uri = rfc_secret().otpauth_uri("svanill vault", "me");
+
+        assert_eq!(
+            "otpauth://totp/svanill%20vault:me?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=svanill%20vault&algorithm=SHA1&digits=6&period=30",
+            uri
+        );
+    }
+}
@@ -1,31 +1,42 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use aws_config::meta::region::RegionProviderChain;
 use aws_config::BehaviorVersion;
 use aws_credential_types::provider::SharedCredentialsProvider;
 use aws_smithy_types::timeout;
 use aws_types::region::Region;
+use diesel::migration::MigrationSource;
 use diesel::r2d2::{self, ConnectionManager};
+use diesel::sqlite::Sqlite;
 use diesel::SqliteConnection;
-use diesel_migrations::{EmbeddedMigrations, MigrationHarness};
-use ring::{hmac, rand};
+use diesel_migrations::MigrationHarness;
 use std::env;
 use std::fmt::Write as _;
 use std::net::TcpListener;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
+use svanill_vault_server::auth::answer_hash::hash_answer;
+use svanill_vault_server::auth::decoy_challenge::DecoyChallenges;
+use svanill_vault_server::auth::login_throttle::{LoginThrottle, LoginThrottleConfig};
+use svanill_vault_server::auth::sqlite_token_store::SqliteTokenStore;
+use svanill_vault_server::auth::token_keys::{generate_hex_key, TokenKeys};
+use svanill_vault_server::auth::token_store::{TokenLifetime, TokenStore};
 use svanill_vault_server::auth::tokens_cache::TokensCache;
-use svanill_vault_server::file_server;
+use svanill_vault_server::auth::TokenMode;
+use svanill_vault_server::db::models::{NewUser, NewUserTokenRevocation};
+use svanill_vault_server::db::{self, DbPool, DB_MIGRATIONS};
+use svanill_vault_server::errors::{ApiError, VaultError};
+use svanill_vault_server::file_server::{
+    FileServer, FsStorage, MemoryStorage, S3Storage, SignedUrls,
+};
+use svanill_vault_server::registration::RegistrationMode;
 use svanill_vault_server::server::{run, AppData};
-
-#[macro_use]
-extern crate diesel_migrations;
-
-const DB_MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+use svanill_vault_server::utils::{unix_timestamp, unix_timestamp_ms};
+use svanill_vault_server::validation::validate_username;
 
 #[derive(Debug, StructOpt)]
 #[structopt(
-    name = "svanill-vault-cli",
-    about = "Read/Write data from/to a svanill vault server"
+    name = "svanill-vault-server",
+    about = "Serve a svanill vault, or manage its users and database"
 )]
 struct Opt {
     /// Server host
@@ -52,6 +63,16 @@ struct Opt {
         env = "SVANILL_VAULT_AUTH_TOKEN_TIMEOUT"
     )]
     auth_token_timeout: u32,
+    /// Postpone the expiration of the authorization tokens every time they are used
+    /// (ignored with --token-mode signed, where tokens must be refreshed instead)
+    #[structopt(long = "sliding-sessions")]
+    sliding_sessions: bool,
+    /// Max lifetime of a session in minutes, neither using nor refreshing a token extends it
+    #[structopt(
+        long = "max-session-lifetime",
+        env = "SVANILL_VAULT_MAX_SESSION_LIFETIME"
+    )]
+    max_session_lifetime: Option<u32>,
     /// Max number of concurrent users
     #[structopt(
         short = "u",
@@ -59,9 +80,61 @@ struct Opt {
         env = "SVANILL_VAULT_MAX_CONC_USERS"
     )]
     max_concurrent_users: usize,
-    /// S3 bucket
+    /// Where to store the authorization tokens: "memory" (lost on restart) or "sqlite" (in the database)
+    #[structopt(
+        long = "token-store",
+        default_value = "memory",
+        possible_values = &["memory", "sqlite"],
+        env = "SVANILL_VAULT_TOKEN_STORE"
+    )]
+    token_store: String,
+    /// Kind of authorization tokens: "opaque" (kept in the token store) or "signed"
+    /// (self-contained, verified without any lookup but the revocations in the db)
+    #[structopt(
+        long = "token-mode",
+        default_value = "opaque",
+        possible_values = &["opaque", "signed"],
+        env = "SVANILL_VAULT_TOKEN_MODE"
+    )]
+    token_mode: String,
+    /// File containing the hex encoded keys used to sign the authorization tokens, one per line.
+    /// The first key signs new tokens, the others are only used to verify them (key rotation)
+    #[structopt(
+        long = "token-key-file",
+        env = "SVANILL_VAULT_TOKEN_KEY_FILE",
+        parse(from_os_str)
+    )]
+    token_key_file: Option<PathBuf>,
+    /// Same as --token-key-file, but the hex encoded keys are comma separated. Ignored if --token-key-file is set
+    #[structopt(
+        long = "token-keys",
+        env = "SVANILL_VAULT_TOKEN_KEYS",
+        hide_env_values = true
+    )]
+    token_keys: Option<String>,
+    /// Where to store the files: "s3" (a S3 compatible bucket), "fs" (a local directory)
+    /// or "memory" (lost on restart). The server itself serves the files with "fs" and "memory"
+    #[structopt(
+        long = "storage",
+        default_value = "s3",
+        possible_values = &["s3", "fs", "memory"],
+        env = "SVANILL_VAULT_STORAGE"
+    )]
+    storage: String,
+    /// Directory of the files (required with --storage fs)
+    #[structopt(
+        long = "storage-dir",
+        env = "SVANILL_VAULT_STORAGE_DIR",
+        parse(from_os_str)
+    )]
+    storage_dir: Option<PathBuf>,
+    /// Url where the clients reach the server, to build the urls of the files with --storage fs|memory.
+    /// Default to http://<host>:<port>
+    #[structopt(long = "public-url", env = "SVANILL_VAULT_PUBLIC_URL")]
+    public_url: Option<String>,
+    /// S3 bucket (required with --storage s3)
     #[structopt(long = "s3-bucket", env = "SVANILL_VAULT_S3_BUCKET")]
-    s3_bucket: String,
+    s3_bucket: Option<String>,
     /// S3 region
     #[structopt(long = "s3-region")]
     s3_region: Option<String>,
@@ -94,6 +167,135 @@ struct Opt {
         env = "SVANILL_VAULT_CORS_ORIGIN"
     )]
     cors_origin: String,
+    /// Who can create a new account: "open" (anyone), "closed" (nobody) or "invite" (requires --invite-code)
+    #[structopt(
+        long = "registration",
+        default_value = "closed",
+        possible_values = &["open", "closed", "invite"],
+        env = "SVANILL_VAULT_REGISTRATION"
+    )]
+    registration: String,
+    /// Invite code that must be provided to create a new account, when registration is "invite"
+    #[structopt(
+        long = "invite-code",
+        env = "SVANILL_VAULT_INVITE_CODE",
+        required_if("registration", "invite")
+    )]
+    invite_code: Option<String>,
+    /// Failed attempts to answer the challenge allowed, per username and per ip,
+    /// before having to wait between attempts
+    #[structopt(
+        long = "login-free-failures",
+        default_value = "5",
+        env = "SVANILL_VAULT_LOGIN_FREE_FAILURES"
+    )]
+    login_free_failures: u32,
+    /// Seconds to wait after the first failed attempt past the free ones, doubled at every further failure
+    #[structopt(
+        long = "login-backoff",
+        default_value = "1",
+        env = "SVANILL_VAULT_LOGIN_BACKOFF"
+    )]
+    login_backoff: u64,
+    /// Failed attempts to answer the challenge after which the username or ip is locked out
+    #[structopt(
+        long = "login-lockout-failures",
+        default_value = "20",
+        env = "SVANILL_VAULT_LOGIN_LOCKOUT_FAILURES"
+    )]
+    login_lockout_failures: u32,
+    /// Lockout duration in minutes
+    #[structopt(
+        long = "login-lockout-duration",
+        default_value = "15",
+        env = "SVANILL_VAULT_LOGIN_LOCKOUT_DURATION"
+    )]
+    login_lockout_duration: u64,
+    /// Take the client ip from the `Forwarded` or `X-Forwarded-For` header, for the login throttle,
+    /// the sessions and the audit log. Only set it behind a reverse proxy that overwrites those headers
+    #[structopt(long = "trusted-proxy", env = "SVANILL_VAULT_TRUSTED_PROXY")]
+    trusted_proxy: bool,
+    /// Answer with a decoy challenge when the user does not exist, so that nobody can probe which users exist
+    #[structopt(long = "decoy-challenges", env = "SVANILL_VAULT_DECOY_CHALLENGES")]
+    decoy_challenges: bool,
+    /// Hex encoded secret used to derive the decoy challenges, at least 32 bytes long.
+    /// If missing a random one is used, so the decoys change on restart
+    #[structopt(
+        long = "decoy-secret",
+        env = "SVANILL_VAULT_DECOY_SECRET",
+        hide_env_values = true
+    )]
+    decoy_secret: Option<String>,
+    /// Days the audit log entries are kept for, 0 to keep them forever
+    #[structopt(
+        long = "audit-retention",
+        default_value = "90",
+        env = "SVANILL_VAULT_AUDIT_RETENTION"
+    )]
+    audit_retention: u64,
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Start the HTTP server (the default when no command is given)
+    Serve,
+    /// Run the pending database migrations, without starting the HTTP server
+    Migrate {
+        /// Only show which migrations are applied and which are pending
+        #[structopt(long = "status")]
+        status: bool,
+    },
+    /// Manage the users
+    User(UserCommand),
+    /// Manage the keys used to sign the authorization tokens
+    TokenKey(TokenKeyCommand),
+}
+
+#[derive(Debug, StructOpt)]
+enum UserCommand {
+    /// Add a user
+    Add {
+        username: String,
+        /// The challenge, usually a random string encrypted with svanill
+        #[structopt(long = "challenge")]
+        challenge: String,
+        /// The plaintext of the challenge, stored hashed
+        #[structopt(
+            long = "answer",
+            env = "SVANILL_VAULT_USER_ANSWER",
+            hide_env_values = true
+        )]
+        answer: String,
+        /// Make the user an admin
+        #[structopt(long = "admin")]
+        admin: bool,
+    },
+    /// List the users
+    List,
+    /// Remove a user, with their keys, tokens and files
+    Remove {
+        username: String,
+        /// Leave the user's files in the bucket, so that --s3-bucket is not required
+        #[structopt(long = "keep-files")]
+        keep_files: bool,
+    },
+    /// Disable a user, revoking their tokens. They can't log in until enabled again
+    Disable {
+        username: String,
+        /// Why the user is disabled, told to them when they try to log in
+        #[structopt(long = "reason")]
+        reason: Option<String>,
+    },
+    /// Enable a disabled user
+    Enable { username: String },
+}
+
+#[derive(Debug, StructOpt)]
+enum TokenKeyCommand {
+    /// Print a new random key, to be used with --token-key-file or --token-keys
+    Generate,
 }
 
 fn setup_log(level: Option<log::Level>) {
@@ -122,54 +324,84 @@ async fn download_file(url: &str, path: &Path) -> Result<()> {
     Ok(())
 }
 
-#[actix_web::main]
-async fn main() -> Result<()> {
-    #[cfg(debug_assertions)]
-    color_backtrace::install();
+/// What the key that signs the urls of the files is derived for
+const FILE_URLS_KEY_PURPOSE: &str = "svanill-vault file urls";
 
-    let opt = Opt::from_args();
+/// The option required to access the files, if it's missing
+fn missing_storage_option(opt: &Opt) -> Option<&'static str> {
+    match opt.storage.as_str() {
+        "fs" if opt.storage_dir.is_none() => Some("--storage-dir"),
+        "s3" if opt.s3_bucket.is_none() => Some("--s3-bucket"),
+        _ => None,
+    }
+}
 
-    setup_log(if opt.verbose == 1 {
-        Some(log::Level::Debug)
-    } else if opt.verbose == 2 {
-        Some(log::Level::Trace)
-    } else if opt.quiet {
-        Some(log::Level::Warn)
-    } else if env::var_os("RUST_LOG").unwrap_or_default().is_empty() {
-        Some(log::Level::Info)
-    } else {
-        None
-    });
+async fn setup_file_server(opt: &Opt, token_keys: &TokenKeys) -> Result<FileServer> {
+    let url_timeout = std::time::Duration::from_secs(opt.presigned_url_duration_in_min as u64 * 60);
+
+    // the urls of the local storages point to the server itself
+    let public_url = opt
+        .public_url
+        .clone()
+        .unwrap_or_else(|| format!("http://{}:{}", opt.host, opt.port));
+    let signed_urls = || {
+        SignedUrls::new(
+            &public_url,
+            token_keys.derive_keys(FILE_URLS_KEY_PURPOSE),
+            url_timeout,
+        )
+    };
 
-    if std::env::var_os("SENTRY_DSN").is_none() {
-        log::warn!("Env var SENTRY_DSN not set, Sentry won't be initialized");
-    }
+    match opt.storage.as_str() {
+        "fs" => {
+            let Some(storage_dir) = opt.storage_dir.clone() else {
+                bail!("--storage-dir is required");
+            };
 
-    // Note: requires env SENTRY_DSN to be properly set to become active
-    let _guard = sentry::init(sentry::ClientOptions {
-        release: Some(format!("svanill-vault-server@{}", std::env!("GIT_HASH")).into()),
-        attach_stacktrace: true,
-        in_app_include: vec!["svanill"],
-        session_mode: sentry::SessionMode::Request,
-        auto_session_tracking: true,
-        sample_rate: 1.0,
-        traces_sample_rate: 1.0,
-        ..Default::default()
-    });
+            std::fs::create_dir_all(&storage_dir)?;
+
+            Ok(FileServer::with_local_storage(FsStorage::new(
+                storage_dir,
+                signed_urls(),
+            )))
+        }
+        "memory" => {
+            log::warn!("Files are stored in memory, they will be lost on restart");
+
+            Ok(FileServer::with_local_storage(MemoryStorage::new(
+                signed_urls(),
+            )))
+        }
+        _ => {
+            let Some(bucket) = opt.s3_bucket.clone() else {
+                bail!("--s3-bucket is required");
+            };
 
-    if let Some(region) = opt.s3_region {
+            let s3_storage = setup_s3_storage(opt, bucket, url_timeout).await?;
+
+            Ok(FileServer::new(s3_storage))
+        }
+    }
+}
+
+async fn setup_s3_storage(
+    opt: &Opt,
+    bucket: String,
+    presigned_url_timeout: std::time::Duration,
+) -> Result<S3Storage> {
+    if let Some(region) = &opt.s3_region {
         unsafe {
             env::set_var("AWS_DEFAULT_REGION", region);
         }
     }
 
-    if let Some(access_key_id) = opt.s3_access_key_id {
+    if let Some(access_key_id) = &opt.s3_access_key_id {
         unsafe {
             env::set_var("AWS_ACCESS_KEY_ID", access_key_id);
         }
     }
 
-    if let Some(secret_access_key) = opt.s3_secret_access_key {
+    if let Some(secret_access_key) = &opt.s3_secret_access_key {
         unsafe {
             env::set_var("AWS_SECRET_ACCESS_KEY", secret_access_key);
         }
@@ -206,7 +438,7 @@ async fn main() -> Result<()> {
     let mut s3_config_builder =
         aws_sdk_s3::config::Builder::from(&aws_sdk_conf).timeout_config(timeout_config);
 
-    if let Some(endpoint) = opt.s3_endpoint {
+    if let Some(endpoint) = &opt.s3_endpoint {
         s3_config_builder = s3_config_builder.endpoint_url(endpoint);
     }
 
@@ -214,57 +446,356 @@ async fn main() -> Result<()> {
         .credentials_provider(SharedCredentialsProvider::new(credentials.clone()))
         .build();
 
-    let s3_fs = file_server::FileServer::new(
-        aws_s3_conf,
-        credentials,
-        opt.s3_bucket,
-        std::time::Duration::from_secs(opt.presigned_url_duration_in_min as u64 * 60),
+    let s3_storage =
+        S3Storage::new(aws_s3_conf, credentials, bucket, presigned_url_timeout).await?;
+
+    Ok(s3_storage)
+}
+
+fn setup_db_pool(db_path: &Path) -> DbPool {
+    let manager = ConnectionManager::<SqliteConnection>::new(
+        db_path.to_str().expect("Cannot convert db_path to string"),
+    );
+
+    r2d2::Pool::builder()
+        .build(manager)
+        .expect("Failed to create database connection pool")
+}
+
+#[actix_web::main]
+async fn main() -> Result<()> {
+    #[cfg(debug_assertions)]
+    color_backtrace::install();
+
+    let mut opt = Opt::from_args();
+
+    setup_log(if opt.verbose == 1 {
+        Some(log::Level::Debug)
+    } else if opt.verbose == 2 {
+        Some(log::Level::Trace)
+    } else if opt.quiet {
+        Some(log::Level::Warn)
+    } else if env::var_os("RUST_LOG").unwrap_or_default().is_empty() {
+        Some(log::Level::Info)
+    } else {
+        None
+    });
+
+    match opt.cmd.take() {
+        None | Some(Command::Serve) => serve(opt).await,
+        Some(Command::Migrate { status }) => migrate(&setup_db_pool(&opt.db_path), status),
+        Some(Command::User(cmd)) => manage_user(&opt, cmd).await,
+        Some(Command::TokenKey(TokenKeyCommand::Generate)) => {
+            println!("{}", generate_hex_key()?);
+            Ok(())
+        }
+    }
+}
+
+fn migrate(pool: &DbPool, status: bool) -> Result<()> {
+    let mut conn = pool.get()?;
+
+    if status {
+        let applied = conn.applied_migrations().map_err(|e| anyhow!(e))?;
+        let migrations =
+            MigrationSource::<Sqlite>::migrations(&DB_MIGRATIONS).map_err(|e| anyhow!(e))?;
+
+        for migration in migrations {
+            let name = migration.name();
+            let state = if applied.iter().any(|version| *version == name.version()) {
+                "applied"
+            } else {
+                "pending"
+            };
+            println!("{state}\t{name}");
+        }
+    } else {
+        let applied = conn
+            .run_pending_migrations(DB_MIGRATIONS)
+            .map_err(|e| anyhow!(e))?;
+
+        if applied.is_empty() {
+            println!("No pending migrations");
+        }
+
+        for version in applied {
+            println!("Applied {version}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Report the error by its message, rather than as the json sent to the clients
+fn error_message(error: VaultError) -> anyhow::Error {
+    match error {
+        VaultError::DatabaseError(e) => e.into(),
+        e => anyhow!(ApiError::from(&e).error.message),
+    }
+}
+
+/// Revoke the tokens kept in the db. The ones kept in the memory of a running
+/// server can't be reached from here, but a running server rejects the tokens
+/// of a disabled user anyway, and a removed user can't be found anymore
+fn revoke_user_tokens(conn: &mut SqliteConnection, username: &str) -> Result<()> {
+    db::actions::delete_user_tokens(conn, username).map_err(error_message)?;
+    db::actions::revoke_user_signed_tokens(
+        conn,
+        &NewUserTokenRevocation {
+            username,
+            revoked_at: unix_timestamp_ms(),
+        },
     )
-    .await?;
+    .map_err(error_message)?;
+
+    Ok(())
+}
+
+async fn manage_user(opt: &Opt, cmd: UserCommand) -> Result<()> {
+    let pool = setup_db_pool(&opt.db_path);
+    let mut conn = pool.get()?;
+
+    if conn
+        .has_pending_migration(DB_MIGRATIONS)
+        .map_err(|e| anyhow!(e))?
+    {
+        bail!("The database has pending migrations, run the migrate command first");
+    }
+
+    match cmd {
+        UserCommand::Add {
+            username,
+            challenge,
+            answer,
+            admin,
+        } => {
+            validate_username(&username).map_err(error_message)?;
+
+            if challenge.is_empty() || answer.is_empty() {
+                bail!("The challenge and the answer can't be empty");
+            }
+
+            db::actions::create_user(
+                &mut conn,
+                &NewUser {
+                    username: &username,
+                    challenge: &challenge,
+                    answer: &hash_answer(&answer).map_err(error_message)?,
+                    is_admin: admin,
+                },
+            )
+            .map_err(error_message)?;
+
+            println!("Added user {username}");
+        }
+        UserCommand::List => {
+            for user in db::actions::find_users(&mut conn).map_err(error_message)? {
+                let state = match (&user.disabled_at, &user.disabled_reason) {
+                    (None, _) => "enabled".to_owned(),
+                    (Some(_), None) => "disabled".to_owned(),
+                    (Some(_), Some(reason)) => format!("disabled: {reason}"),
+                };
+
+                println!(
+                    "{}\t{}\t{}",
+                    user.username,
+                    if user.is_admin { "admin" } else { "user" },
+                    state
+                );
+            }
+        }
+        UserCommand::Remove {
+            username,
+            keep_files,
+        } => {
+            if db::actions::find_user_by_username(&mut conn, &username)
+                .map_err(error_message)?
+                .is_none()
+            {
+                bail!("User {username} does not exist");
+            }
+
+            if !keep_files {
+                // The files in memory belong to the running server, out of reach from here
+                if opt.storage == "memory" {
+                    bail!("The files kept in memory can't be removed, pass --keep-files");
+                }
+
+                if let Some(option) = missing_storage_option(opt) {
+                    bail!("{option} is required to remove the user's files, or pass --keep-files");
+                }
+
+                // No url is signed to remove the files, any key will do
+                let file_server = setup_file_server(opt, &TokenKeys::generate()?).await?;
+                let removed = file_server.remove_all_files(&username).await?;
+                println!("Removed {removed} files");
+            }
+
+            revoke_user_tokens(&mut conn, &username)?;
+            db::actions::delete_user(&mut conn, &username).map_err(error_message)?;
+
+            println!("Removed user {username}");
+        }
+        UserCommand::Disable { username, reason } => {
+            if !db::actions::set_user_disabled_at(
+                &mut conn,
+                &username,
+                Some(unix_timestamp()),
+                reason.as_deref(),
+            )
+            .map_err(error_message)?
+            {
+                bail!("User {username} does not exist");
+            }
+
+            revoke_user_tokens(&mut conn, &username)?;
+
+            println!("Disabled user {username}");
+        }
+        UserCommand::Enable { username } => {
+            if !db::actions::set_user_disabled_at(&mut conn, &username, None, None)
+                .map_err(error_message)?
+            {
+                bail!("User {username} does not exist");
+            }
+
+            println!("Enabled user {username}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn serve(opt: Opt) -> Result<()> {
+    if std::env::var_os("SENTRY_DSN").is_none() {
+        log::warn!("Env var SENTRY_DSN not set, Sentry won't be initialized");
+    }
+
+    // Note: requires env SENTRY_DSN to be properly set to become active
+    let _guard = sentry::init(sentry::ClientOptions {
+        release: Some(format!("svanill-vault-server@{}", std::env!("GIT_HASH")).into()),
+        attach_stacktrace: true,
+        in_app_include: vec!["svanill"],
+        session_mode: sentry::SessionMode::Request,
+        auto_session_tracking: true,
+        sample_rate: 1.0,
+        traces_sample_rate: 1.0,
+        ..Default::default()
+    });
+
+    if let Some(option) = missing_storage_option(&opt) {
+        bail!("{option} is required to serve");
+    }
+
+    let registration = match (opt.registration.as_str(), &opt.invite_code) {
+        ("open", _) => RegistrationMode::Open,
+        ("closed", _) => RegistrationMode::Closed,
+        ("invite", Some(invite_code)) => RegistrationMode::InviteCode(invite_code.clone()),
+        ("invite", None) => bail!("--invite-code is required when registration is \"invite\""),
+        (mode, _) => bail!("Unknown registration mode \"{mode}\""),
+    };
 
     // download the SQLite db, if asked to
-    if let Some(db_download_url) = opt.db_download_url {
-        download_file(&db_download_url, &opt.db_path)
+    if let Some(db_download_url) = &opt.db_download_url {
+        download_file(db_download_url, &opt.db_path)
             .await
             .expect("could not download db");
     }
 
     // set up database connection pool
-    let connspec = opt.db_path;
-    let manager = ConnectionManager::<SqliteConnection>::new(
-        connspec.to_str().expect("Cannot convert db_path to string"),
-    );
-    let pool = r2d2::Pool::builder()
-        .build(manager)
-        .expect("Failed to create database connection pool");
+    let pool = setup_db_pool(&opt.db_path);
 
     let mut conn = pool.get().expect("couldn't get db connection from pool");
 
     conn.run_pending_migrations(DB_MIGRATIONS)
         .expect("failed to run migrations");
 
-    // generate server key, used to sign and verify tokens
-    let rng = rand::SystemRandom::new();
-    let crypto_key =
-        hmac::Key::generate(hmac::HMAC_SHA256, &rng).expect("Cannot generate cryptographyc key");
+    // load the server keys, used to sign and verify tokens
+    let token_keys = if let Some(token_key_file) = &opt.token_key_file {
+        let keys = std::fs::read_to_string(token_key_file)
+            .unwrap_or_else(|e| panic!("Cannot read {}: {}", token_key_file.display(), e));
+        TokenKeys::from_hex_list(&keys).expect("Invalid token key file")
+    } else if let Some(keys) = &opt.token_keys {
+        TokenKeys::from_hex_list(keys).expect("Invalid token keys")
+    } else {
+        log::warn!("No token key provided, the tokens will be invalidated on restart");
+        TokenKeys::generate().expect("Cannot generate cryptographyc key")
+    };
+
+    let file_server = setup_file_server(&opt, &token_keys).await?;
+
+    let auth_token_timeout = std::time::Duration::from_secs(60 * opt.auth_token_timeout as u64);
+    let max_session_lifetime = opt
+        .max_session_lifetime
+        .map(|minutes| std::time::Duration::from_secs(60 * minutes as u64));
 
-    // Use a LRU cache to store tokens, until we add redis support
-    let tokens_cache = TokensCache::new(
+    let token_lifetime = TokenLifetime {
+        timeout: auth_token_timeout,
+        sliding: opt.sliding_sessions,
+        max_lifetime: max_session_lifetime,
+    };
+
+    let token_store: Box<dyn TokenStore> = match opt.token_store.as_str() {
+        "sqlite" => Box::new(SqliteTokenStore::with_lifetime(
+            pool.clone(),
+            token_lifetime,
+        )),
+        // Use a LRU cache to store tokens, they will be lost on restart
+        _ => Box::new(TokensCache::with_lifetime(
+            opt.max_concurrent_users,
+            token_lifetime,
+        )),
+    };
+
+    let token_mode = match opt.token_mode.as_str() {
+        "signed" => TokenMode::Signed {
+            lifetime: auth_token_timeout,
+            max_lifetime: max_session_lifetime,
+        },
+        _ => TokenMode::Opaque,
+    };
+
+    let cors_origin = opt.cors_origin;
+
+    let login_throttle = LoginThrottle::new(
         opt.max_concurrent_users,
-        std::time::Duration::from_secs(60 * opt.auth_token_timeout as u64),
+        LoginThrottleConfig {
+            free_failures: opt.login_free_failures,
+            backoff: std::time::Duration::from_secs(opt.login_backoff),
+            lockout_failures: opt.login_lockout_failures,
+            lockout_duration: std::time::Duration::from_secs(60 * opt.login_lockout_duration),
+        },
     );
 
-    let cors_origin = opt.cors_origin;
+    let decoy_challenges = if !opt.decoy_challenges {
+        None
+    } else if let Some(secret) = opt.decoy_secret {
+        Some(DecoyChallenges::from_hex(&secret).expect("Invalid decoy secret"))
+    } else {
+        log::warn!("No decoy secret provided, the decoy challenges will change on restart");
+        Some(DecoyChallenges::generate().expect("Cannot generate cryptographyc key"))
+    };
+
+    let audit_retention = match opt.audit_retention {
+        0 => None,
+        days => Some(std::time::Duration::from_secs(days * 24 * 60 * 60)),
+    };
 
     let listener =
         TcpListener::bind(format!("{}:{}", opt.host, opt.port)).expect("Failed to bind port");
 
     let data = AppData {
-        tokens_cache,
-        crypto_key,
+        token_store,
+        token_keys,
+        token_mode,
         pool,
-        s3_fs,
+        file_server,
         cors_origin,
+        registration,
+        login_throttle,
+        decoy_challenges,
+        audit_retention,
+        trusted_proxy: opt.trusted_proxy,
     };
 
     let _server = run(listener, data)?.await;
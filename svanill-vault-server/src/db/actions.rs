@@ -1,6 +1,11 @@
-use crate::db::models::User;
+use crate::db::models::{
+    AuditEntry, Credential, NewApiKey, NewAuditEntry, NewLoginLockout, NewRevokedToken, NewToken,
+    NewUser, NewUserKey, NewUserTokenRevocation, NewUserTotp, StoredApiKey, Token, User, UserKey,
+    UserTotp, DEFAULT_KEY_ID,
+};
 use crate::errors::VaultError;
 use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 
 pub fn find_user_by_username(
     conn: &mut SqliteConnection,
@@ -15,3 +20,646 @@ pub fn find_user_by_username(
 
     Ok(user)
 }
+
+/// Return every user, sorted by username
+pub fn find_users(conn: &mut SqliteConnection) -> Result<Vec<User>, VaultError> {
+    use crate::db::schema::user;
+
+    let users = user::table.order(user::username.asc()).load::<User>(conn)?;
+
+    Ok(users)
+}
+
+/// Disable the user, optionally telling why, or enable it back with `None`.
+/// Return false if the user does not exist
+pub fn set_user_disabled_at(
+    conn: &mut SqliteConnection,
+    username: &str,
+    disabled_at: Option<i64>,
+    reason: Option<&str>,
+) -> Result<bool, VaultError> {
+    use crate::db::schema::user;
+
+    let updated = diesel::update(user::table.filter(user::username.eq(username)))
+        .set((
+            user::disabled_at.eq(disabled_at),
+            user::disabled_reason.eq(reason),
+        ))
+        .execute(conn)?;
+
+    Ok(updated > 0)
+}
+
+pub fn create_user(conn: &mut SqliteConnection, new_user: &NewUser) -> Result<(), VaultError> {
+    use crate::db::schema::user;
+
+    diesel::insert_into(user::table)
+        .values(new_user)
+        .execute(conn)
+        .map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                VaultError::UserAlreadyExists
+            }
+            e => e.into(),
+        })?;
+
+    Ok(())
+}
+
+/// Return the user's challenge/answer pair identified by `key_id`
+pub fn find_credential(
+    conn: &mut SqliteConnection,
+    username: &str,
+    key_id: &str,
+) -> Result<Option<Credential>, VaultError> {
+    use crate::db::schema::user_key;
+
+    if key_id == DEFAULT_KEY_ID {
+        return Ok(find_user_by_username(conn, username)?.map(Credential::from));
+    }
+
+    let key = user_key::table
+        .filter(user_key::username.eq(username))
+        .filter(user_key::key_id.eq(key_id))
+        .first::<UserKey>(conn)
+        .optional()?;
+
+    Ok(key.map(Credential::from))
+}
+
+/// Return every challenge/answer pair of the user, the default one first
+pub fn find_credentials(
+    conn: &mut SqliteConnection,
+    username: &str,
+) -> Result<Vec<Credential>, VaultError> {
+    use crate::db::schema::user_key;
+
+    let user = match find_user_by_username(conn, username)? {
+        Some(user) => user,
+        None => return Ok(Vec::new()),
+    };
+
+    let keys = user_key::table
+        .filter(user_key::username.eq(username))
+        .order((user_key::created_at.asc(), user_key::key_id.asc()))
+        .load::<UserKey>(conn)?;
+
+    let mut credentials = vec![Credential::from(user)];
+    credentials.extend(keys.into_iter().map(Credential::from));
+
+    Ok(credentials)
+}
+
+/// Replace the answer of a user's key, e.g. with a freshly hashed one
+pub fn update_credential_answer(
+    conn: &mut SqliteConnection,
+    username: &str,
+    key_id: &str,
+    answer: &str,
+) -> Result<(), VaultError> {
+    use crate::db::schema::{user, user_key};
+
+    if key_id == DEFAULT_KEY_ID {
+        diesel::update(user::table.filter(user::username.eq(username)))
+            .set(user::answer.eq(answer))
+            .execute(conn)?;
+    } else {
+        diesel::update(
+            user_key::table
+                .filter(user_key::username.eq(username))
+                .filter(user_key::key_id.eq(key_id)),
+        )
+        .set(user_key::answer.eq(answer))
+        .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+/// Replace the challenge and answer of a user's key, but only if the answer is still `current_answer`,
+/// so that concurrent changes can't overwrite each other. Return false if nothing changed
+pub fn change_credential(
+    conn: &mut SqliteConnection,
+    username: &str,
+    key_id: &str,
+    current_answer: &str,
+    challenge: &str,
+    answer: &str,
+) -> Result<bool, VaultError> {
+    use crate::db::schema::{user, user_key};
+
+    let updated = if key_id == DEFAULT_KEY_ID {
+        diesel::update(
+            user::table
+                .filter(user::username.eq(username))
+                .filter(user::answer.eq(current_answer)),
+        )
+        .set((user::challenge.eq(challenge), user::answer.eq(answer)))
+        .execute(conn)?
+    } else {
+        diesel::update(
+            user_key::table
+                .filter(user_key::username.eq(username))
+                .filter(user_key::key_id.eq(key_id))
+                .filter(user_key::answer.eq(current_answer)),
+        )
+        .set((
+            user_key::challenge.eq(challenge),
+            user_key::answer.eq(answer),
+        ))
+        .execute(conn)?
+    };
+
+    Ok(updated > 0)
+}
+
+pub fn insert_user_key(
+    conn: &mut SqliteConnection,
+    new_key: &NewUserKey,
+) -> Result<(), VaultError> {
+    use crate::db::schema::user_key;
+
+    diesel::insert_into(user_key::table)
+        .values(new_key)
+        .execute(conn)
+        .map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                VaultError::UserKeyAlreadyExists
+            }
+            e => e.into(),
+        })?;
+
+    Ok(())
+}
+
+/// Delete the user's key, return false if the user has no such key
+pub fn delete_user_key(
+    conn: &mut SqliteConnection,
+    username: &str,
+    key_id: &str,
+) -> Result<bool, VaultError> {
+    use crate::db::schema::user_key;
+
+    let deleted = diesel::delete(
+        user_key::table
+            .filter(user_key::username.eq(username))
+            .filter(user_key::key_id.eq(key_id)),
+    )
+    .execute(conn)?;
+
+    Ok(deleted > 0)
+}
+
+/// Delete the user, return false if there was no user to delete
+pub fn delete_user(conn: &mut SqliteConnection, username: &str) -> Result<bool, VaultError> {
+    use crate::db::schema::{api_key, user, user_key, user_totp};
+
+    conn.transaction(|conn| {
+        diesel::delete(user_key::table.filter(user_key::username.eq(username))).execute(conn)?;
+        diesel::delete(api_key::table.filter(api_key::username.eq(username))).execute(conn)?;
+        diesel::delete(user_totp::table.filter(user_totp::username.eq(username))).execute(conn)?;
+
+        let deleted =
+            diesel::delete(user::table.filter(user::username.eq(username))).execute(conn)?;
+
+        Ok(deleted > 0)
+    })
+}
+
+pub fn insert_token(conn: &mut SqliteConnection, new_token: &NewToken) -> Result<(), VaultError> {
+    use crate::db::schema::token;
+
+    diesel::insert_into(token::table)
+        .values(new_token)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Return the token, if it did not expire yet
+pub fn find_token(
+    conn: &mut SqliteConnection,
+    token_hash: &str,
+    now: i64,
+) -> Result<Option<Token>, VaultError> {
+    use crate::db::schema::token;
+
+    let found = token::table
+        .filter(token::token_hash.eq(token_hash))
+        .filter(token::expires_at.gt(now))
+        .first::<Token>(conn)
+        .optional()?;
+
+    Ok(found)
+}
+
+/// Return the user's tokens that did not expire yet
+pub fn find_user_tokens(
+    conn: &mut SqliteConnection,
+    username: &str,
+    now: i64,
+) -> Result<Vec<Token>, VaultError> {
+    use crate::db::schema::token;
+
+    let tokens = token::table
+        .filter(token::username.eq(username))
+        .filter(token::expires_at.gt(now))
+        .order(token::created_at.desc())
+        .load::<Token>(conn)?;
+
+    Ok(tokens)
+}
+
+/// Record that the token has been used, possibly postponing its expiration
+pub fn touch_token(
+    conn: &mut SqliteConnection,
+    token_hash: &str,
+    expires_at: i64,
+    last_seen_at: i64,
+) -> Result<(), VaultError> {
+    use crate::db::schema::token;
+
+    diesel::update(token::table.filter(token::token_hash.eq(token_hash)))
+        .set((
+            token::expires_at.eq(expires_at),
+            token::last_seen_at.eq(last_seen_at),
+        ))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Delete the token, return false if there was no token to delete
+pub fn delete_token(conn: &mut SqliteConnection, token_hash: &str) -> Result<bool, VaultError> {
+    use crate::db::schema::token;
+
+    let deleted =
+        diesel::delete(token::table.filter(token::token_hash.eq(token_hash))).execute(conn)?;
+
+    Ok(deleted > 0)
+}
+
+/// Delete the user's token, return false if the user has no such token
+pub fn delete_user_token(
+    conn: &mut SqliteConnection,
+    username: &str,
+    token_hash: &str,
+) -> Result<bool, VaultError> {
+    use crate::db::schema::token;
+
+    let deleted = diesel::delete(
+        token::table
+            .filter(token::username.eq(username))
+            .filter(token::token_hash.eq(token_hash)),
+    )
+    .execute(conn)?;
+
+    Ok(deleted > 0)
+}
+
+pub fn delete_user_tokens(
+    conn: &mut SqliteConnection,
+    username: &str,
+) -> Result<usize, VaultError> {
+    use crate::db::schema::token;
+
+    let deleted =
+        diesel::delete(token::table.filter(token::username.eq(username))).execute(conn)?;
+
+    Ok(deleted)
+}
+
+pub fn delete_expired_tokens(conn: &mut SqliteConnection, now: i64) -> Result<usize, VaultError> {
+    use crate::db::schema::token;
+
+    let deleted = diesel::delete(token::table.filter(token::expires_at.le(now))).execute(conn)?;
+
+    Ok(deleted)
+}
+
+/// Revoke a single signed token, until it expires.
+/// Return false if the token was revoked already.
+pub fn revoke_signed_token(
+    conn: &mut SqliteConnection,
+    revoked_token: &NewRevokedToken,
+) -> Result<bool, VaultError> {
+    use crate::db::schema::revoked_token;
+
+    let inserted = diesel::insert_or_ignore_into(revoked_token::table)
+        .values(revoked_token)
+        .execute(conn)?;
+
+    Ok(inserted > 0)
+}
+
+/// Revoke every signed token issued to the user before `revoked_at`
+pub fn revoke_user_signed_tokens(
+    conn: &mut SqliteConnection,
+    revocation: &NewUserTokenRevocation,
+) -> Result<(), VaultError> {
+    use crate::db::schema::user_token_revocation;
+
+    diesel::insert_into(user_token_revocation::table)
+        .values(revocation)
+        .on_conflict(user_token_revocation::username)
+        .do_update()
+        .set(revocation)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Check if a signed token, identified by its jti, has been revoked,
+/// either by itself or because every user's token was.
+///
+/// A revocation of every user's token covers the tokens issued strictly
+/// before `revoked_at`: a token issued at `revoked_at` or later is valid,
+/// so a token issued right after a revocation must use a timestamp that
+/// is not earlier than the one recorded.
+pub fn is_signed_token_revoked(
+    conn: &mut SqliteConnection,
+    jti: &str,
+    username: &str,
+    issued_at: i64,
+) -> Result<bool, VaultError> {
+    use crate::db::schema::{revoked_token, user_token_revocation};
+
+    let token_revoked = diesel::select(diesel::dsl::exists(
+        revoked_token::table.filter(revoked_token::jti.eq(jti)),
+    ))
+    .get_result::<bool>(conn)?;
+
+    if token_revoked {
+        return Ok(true);
+    }
+
+    let user_revoked = diesel::select(diesel::dsl::exists(
+        user_token_revocation::table
+            .filter(user_token_revocation::username.eq(username))
+            .filter(user_token_revocation::revoked_at.gt(issued_at)),
+    ))
+    .get_result::<bool>(conn)?;
+
+    Ok(user_revoked)
+}
+
+/// Remove the revocations that can no longer match a valid token: the revoked
+/// tokens already expired, and the users' revocations older than `max_token_age`
+pub fn delete_stale_revocations(
+    conn: &mut SqliteConnection,
+    now: i64,
+    max_token_age: i64,
+) -> Result<usize, VaultError> {
+    use crate::db::schema::{revoked_token, user_token_revocation};
+
+    let deleted_tokens =
+        diesel::delete(revoked_token::table.filter(revoked_token::expires_at.le(now)))
+            .execute(conn)?;
+
+    let deleted_users = diesel::delete(
+        user_token_revocation::table
+            .filter(user_token_revocation::revoked_at.le(now - max_token_age)),
+    )
+    .execute(conn)?;
+
+    Ok(deleted_tokens + deleted_users)
+}
+
+/// Record that a client has been locked out, for the operators to review
+pub fn insert_login_lockout(
+    conn: &mut SqliteConnection,
+    lockout: &NewLoginLockout,
+) -> Result<(), VaultError> {
+    use crate::db::schema::login_lockout;
+
+    diesel::insert_into(login_lockout::table)
+        .values(lockout)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+pub fn insert_api_key(conn: &mut SqliteConnection, new_key: &NewApiKey) -> Result<(), VaultError> {
+    use crate::db::schema::api_key;
+
+    diesel::insert_into(api_key::table)
+        .values(new_key)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Return the API key, unless it's expired
+pub fn find_api_key(
+    conn: &mut SqliteConnection,
+    id: &str,
+    now: i64,
+) -> Result<Option<StoredApiKey>, VaultError> {
+    use crate::db::schema::api_key;
+
+    let key = api_key::table
+        .filter(api_key::id.eq(id))
+        .filter(
+            api_key::expires_at
+                .is_null()
+                .or(api_key::expires_at.gt(now)),
+        )
+        .first::<StoredApiKey>(conn)
+        .optional()?;
+
+    Ok(key)
+}
+
+/// Return the user's API keys, expired ones included, oldest first
+pub fn find_user_api_keys(
+    conn: &mut SqliteConnection,
+    username: &str,
+) -> Result<Vec<StoredApiKey>, VaultError> {
+    use crate::db::schema::api_key;
+
+    let keys = api_key::table
+        .filter(api_key::username.eq(username))
+        .order((api_key::created_at.asc(), api_key::id.asc()))
+        .load::<StoredApiKey>(conn)?;
+
+    Ok(keys)
+}
+
+/// Delete one of the user's API keys, return false if the user has no such key
+pub fn delete_user_api_key(
+    conn: &mut SqliteConnection,
+    username: &str,
+    id: &str,
+) -> Result<bool, VaultError> {
+    use crate::db::schema::api_key;
+
+    let deleted = diesel::delete(
+        api_key::table
+            .filter(api_key::username.eq(username))
+            .filter(api_key::id.eq(id)),
+    )
+    .execute(conn)?;
+
+    Ok(deleted > 0)
+}
+
+/// Return the user's TOTP secret, pending or confirmed
+pub fn find_user_totp(
+    conn: &mut SqliteConnection,
+    username: &str,
+) -> Result<Option<UserTotp>, VaultError> {
+    use crate::db::schema::user_totp;
+
+    let totp = user_totp::table
+        .filter(user_totp::username.eq(username))
+        .first::<UserTotp>(conn)
+        .optional()?;
+
+    Ok(totp)
+}
+
+/// Store a pending TOTP secret, replacing any other pending one.
+/// Fail if the user already has a confirmed one
+pub fn start_user_totp_enrollment(
+    conn: &mut SqliteConnection,
+    new_totp: &NewUserTotp,
+) -> Result<(), VaultError> {
+    use crate::db::schema::user_totp;
+
+    conn.transaction(|conn| {
+        if let Some(totp) = find_user_totp(conn, new_totp.username)? {
+            if totp.is_enabled() {
+                return Err(VaultError::TotpAlreadyEnabled);
+            }
+        }
+
+        diesel::replace_into(user_totp::table)
+            .values(new_totp)
+            .execute(conn)?;
+
+        Ok(())
+    })
+}
+
+/// Confirm the pending TOTP secret, recording the step of the code used to confirm it.
+/// Return false if there was no pending secret
+pub fn confirm_user_totp(
+    conn: &mut SqliteConnection,
+    username: &str,
+    step: i64,
+    now: i64,
+) -> Result<bool, VaultError> {
+    use crate::db::schema::user_totp;
+
+    let updated = diesel::update(
+        user_totp::table
+            .filter(user_totp::username.eq(username))
+            .filter(user_totp::confirmed_at.is_null()),
+    )
+    .set((
+        user_totp::confirmed_at.eq(now),
+        user_totp::last_used_step.eq(step),
+    ))
+    .execute(conn)?;
+
+    Ok(updated > 0)
+}
+
+/// Record that the code of a step has been used. Return false if a code of the same
+/// or a later step was used already, so that a code can be accepted only once
+pub fn use_user_totp_step(
+    conn: &mut SqliteConnection,
+    username: &str,
+    step: i64,
+) -> Result<bool, VaultError> {
+    use crate::db::schema::user_totp;
+
+    let updated = diesel::update(
+        user_totp::table
+            .filter(user_totp::username.eq(username))
+            .filter(
+                user_totp::last_used_step
+                    .is_null()
+                    .or(user_totp::last_used_step.lt(step)),
+            ),
+    )
+    .set(user_totp::last_used_step.eq(step))
+    .execute(conn)?;
+
+    Ok(updated > 0)
+}
+
+/// Remove the user's TOTP secret, return false if there was none
+pub fn delete_user_totp(conn: &mut SqliteConnection, username: &str) -> Result<bool, VaultError> {
+    use crate::db::schema::user_totp;
+
+    let deleted =
+        diesel::delete(user_totp::table.filter(user_totp::username.eq(username))).execute(conn)?;
+
+    Ok(deleted > 0)
+}
+
+pub fn insert_audit_entry(
+    conn: &mut SqliteConnection,
+    entry: &NewAuditEntry,
+) -> Result<(), VaultError> {
+    use crate::db::schema::audit_log;
+
+    diesel::insert_into(audit_log::table)
+        .values(entry)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// The user's most recent audit entries, newest first
+pub fn find_user_audit_entries(
+    conn: &mut SqliteConnection,
+    username: &str,
+    limit: i64,
+) -> Result<Vec<AuditEntry>, VaultError> {
+    use crate::db::schema::audit_log;
+
+    let entries = audit_log::table
+        .filter(audit_log::username.eq(username))
+        .order(audit_log::id.desc())
+        .limit(limit)
+        .load::<AuditEntry>(conn)?;
+
+    Ok(entries)
+}
+
+/// Every audit entry, oldest first, optionally only the ones of a user
+/// and the ones created since a time (in seconds since the unix epoch)
+pub fn find_audit_entries(
+    conn: &mut SqliteConnection,
+    username: Option<&str>,
+    since: Option<i64>,
+) -> Result<Vec<AuditEntry>, VaultError> {
+    use crate::db::schema::audit_log;
+
+    let mut query = audit_log::table.order(audit_log::id.asc()).into_boxed();
+
+    if let Some(username) = username {
+        query = query.filter(audit_log::username.eq(username));
+    }
+
+    if let Some(since) = since {
+        query = query.filter(audit_log::created_at.ge(since));
+    }
+
+    Ok(query.load::<AuditEntry>(conn)?)
+}
+
+/// Remove the audit entries created before a time, past the retention period
+pub fn delete_audit_entries_before(
+    conn: &mut SqliteConnection,
+    before: i64,
+) -> Result<usize, VaultError> {
+    use crate::db::schema::audit_log;
+
+    let deleted =
+        diesel::delete(audit_log::table.filter(audit_log::created_at.lt(before))).execute(conn)?;
+
+    Ok(deleted)
+}
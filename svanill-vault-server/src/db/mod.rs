@@ -1,3 +1,12 @@
 pub mod actions;
 pub mod models;
 pub mod schema;
+
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::SqliteConnection;
+use diesel_migrations::EmbeddedMigrations;
+
+pub type DbPool = Pool<ConnectionManager<SqliteConnection>>;
+
+/// Migrations of the SQLite db, embedded in the binary
+pub const DB_MIGRATIONS: EmbeddedMigrations = diesel_migrations::embed_migrations!();
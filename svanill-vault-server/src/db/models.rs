@@ -1,6 +1,210 @@
+use crate::db::schema::{
+    api_key, audit_log, login_lockout, revoked_token, token, user, user_key, user_token_revocation,
+    user_totp,
+};
+use crate::errors::VaultError;
+
 #[derive(Serialize, Queryable)]
 pub struct User {
     pub username: String,
     pub challenge: String,
     pub answer: String,
+    pub is_admin: bool,
+    pub disabled_at: Option<i64>,
+    pub disabled_reason: Option<String>,
+}
+
+impl User {
+    pub fn is_disabled(&self) -> bool {
+        self.disabled_at.is_some()
+    }
+
+    /// Fail with `UserDisabled`, telling why, if the user is disabled
+    pub fn ensure_enabled(&self) -> Result<(), VaultError> {
+        if self.is_disabled() {
+            return Err(VaultError::UserDisabled(self.disabled_reason.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = user)]
+pub struct NewUser<'a> {
+    pub username: &'a str,
+    pub challenge: &'a str,
+    pub answer: &'a str,
+    pub is_admin: bool,
+}
+
+/// Id of the challenge/answer pair stored in the user table
+pub const DEFAULT_KEY_ID: &str = "default";
+
+#[derive(Queryable)]
+pub struct UserKey {
+    pub username: String,
+    pub key_id: String,
+    pub challenge: String,
+    pub answer: String,
+    pub created_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = user_key)]
+pub struct NewUserKey<'a> {
+    pub username: &'a str,
+    pub key_id: &'a str,
+    pub challenge: &'a str,
+    pub answer: &'a str,
+    pub created_at: i64,
+}
+
+/// A challenge/answer pair of a user, either the default one or an additional key
+pub struct Credential {
+    pub key_id: String,
+    pub challenge: String,
+    pub answer: String,
+}
+
+impl From<User> for Credential {
+    fn from(user: User) -> Self {
+        Credential {
+            key_id: DEFAULT_KEY_ID.to_owned(),
+            challenge: user.challenge,
+            answer: user.answer,
+        }
+    }
+}
+
+impl From<UserKey> for Credential {
+    fn from(key: UserKey) -> Self {
+        Credential {
+            key_id: key.key_id,
+            challenge: key.challenge,
+            answer: key.answer,
+        }
+    }
+}
+
+#[derive(Queryable)]
+pub struct Token {
+    pub token_hash: String,
+    pub username: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub last_seen_at: i64,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = token)]
+pub struct NewToken<'a> {
+    pub token_hash: &'a str,
+    pub username: &'a str,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub last_seen_at: i64,
+    pub ip: Option<&'a str>,
+    pub user_agent: Option<&'a str>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = revoked_token)]
+pub struct NewRevokedToken<'a> {
+    pub jti: &'a str,
+    pub expires_at: i64,
+}
+
+#[derive(Insertable, AsChangeset)]
+#[diesel(table_name = user_token_revocation)]
+pub struct NewUserTokenRevocation<'a> {
+    pub username: &'a str,
+    pub revoked_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = login_lockout)]
+pub struct NewLoginLockout<'a> {
+    pub kind: &'a str,
+    pub subject: &'a str,
+    pub failures: i32,
+    pub locked_at: i64,
+    pub locked_until: i64,
+}
+
+#[derive(Queryable)]
+pub struct StoredApiKey {
+    pub id: String,
+    pub username: String,
+    pub name: String,
+    pub secret_hash: String,
+    pub scope: String,
+    pub prefix: Option<String>,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = api_key)]
+pub struct NewApiKey<'a> {
+    pub id: &'a str,
+    pub username: &'a str,
+    pub name: &'a str,
+    pub secret_hash: &'a str,
+    pub scope: &'a str,
+    pub prefix: Option<&'a str>,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Queryable)]
+pub struct UserTotp {
+    pub username: String,
+    pub secret: String,
+    pub created_at: i64,
+    pub confirmed_at: Option<i64>,
+    pub last_used_step: Option<i64>,
+}
+
+impl UserTotp {
+    /// Tell if the enrollment has been confirmed, so that logging in requires a code
+    pub fn is_enabled(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = user_totp)]
+pub struct NewUserTotp<'a> {
+    pub username: &'a str,
+    pub secret: &'a str,
+    pub created_at: i64,
+}
+
+#[derive(Serialize, Queryable)]
+pub struct AuditEntry {
+    pub id: i32,
+    pub created_at: i64,
+    pub username: String,
+    pub action: String,
+    pub filename: Option<String>,
+    pub ip: Option<String>,
+    pub request_id: String,
+    pub outcome: String,
+    pub error_code: Option<i32>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = audit_log)]
+pub struct NewAuditEntry<'a> {
+    pub created_at: i64,
+    pub username: &'a str,
+    pub action: &'a str,
+    pub filename: Option<&'a str>,
+    pub ip: Option<&'a str>,
+    pub request_id: &'a str,
+    pub outcome: &'a str,
+    pub error_code: Option<i32>,
 }
@@ -3,5 +3,92 @@ table! {
         username -> Text,
         challenge -> Text,
         answer -> Text,
+        is_admin -> Bool,
+        disabled_at -> Nullable<BigInt>,
+        disabled_reason -> Nullable<Text>,
+    }
+}
+
+table! {
+    user_key (username, key_id) {
+        username -> Text,
+        key_id -> Text,
+        challenge -> Text,
+        answer -> Text,
+        created_at -> BigInt,
+    }
+}
+
+table! {
+    token (token_hash) {
+        token_hash -> Text,
+        username -> Text,
+        created_at -> BigInt,
+        expires_at -> BigInt,
+        last_seen_at -> BigInt,
+        ip -> Nullable<Text>,
+        user_agent -> Nullable<Text>,
+    }
+}
+
+table! {
+    revoked_token (jti) {
+        jti -> Text,
+        expires_at -> BigInt,
+    }
+}
+
+table! {
+    user_token_revocation (username) {
+        username -> Text,
+        revoked_at -> BigInt,
+    }
+}
+
+table! {
+    login_lockout (id) {
+        id -> Integer,
+        kind -> Text,
+        subject -> Text,
+        failures -> Integer,
+        locked_at -> BigInt,
+        locked_until -> BigInt,
+    }
+}
+
+table! {
+    api_key (id) {
+        id -> Text,
+        username -> Text,
+        name -> Text,
+        secret_hash -> Text,
+        scope -> Text,
+        prefix -> Nullable<Text>,
+        created_at -> BigInt,
+        expires_at -> Nullable<BigInt>,
+    }
+}
+
+table! {
+    user_totp (username) {
+        username -> Text,
+        secret -> Text,
+        created_at -> BigInt,
+        confirmed_at -> Nullable<BigInt>,
+        last_used_step -> Nullable<BigInt>,
+    }
+}
+
+table! {
+    audit_log (id) {
+        id -> Integer,
+        created_at -> BigInt,
+        username -> Text,
+        action -> Text,
+        filename -> Nullable<Text>,
+        ip -> Nullable<Text>,
+        request_id -> Text,
+        outcome -> Text,
+        error_code -> Nullable<Integer>,
     }
 }
@@ -69,11 +69,26 @@ pub enum VaultError {
     NotFound,
     MethodNotAllowed,
     FieldRequired { field: String },
+    InvalidField { field: String, reason: String },
     GenericBadRequest(String),
     UserDoesNotExist,
+    UserAlreadyExists,
+    UserKeyAlreadyExists,
+    UserKeyDoesNotExist,
+    UserDisabled(Option<String>),
+    FilenameConflict,
+    AdminRequired,
+    InvalidSignedUrl,
+    RegistrationClosed,
+    InsufficientScope(String),
+    TotpRequired,
+    TotpMismatch,
+    TotpAlreadyEnabled,
+    InvalidInviteCode,
+    TooManyAttempts { retry_after: u64 },
     DatabaseError(#[from] diesel::result::Error),
     ChallengeMismatchError,
-    S3Error(Box<FileServerError>),
+    StorageError(Box<FileServerError>),
     UnexpectedError(String),
     PolicyDataError(Box<FileServerError>),
 }
@@ -96,6 +111,11 @@ impl From<&VaultError> for ApiError {
                 1002,
                 format!("This field is required: {field}"),
             ),
+            VaultError::InvalidField { field, reason } => ApiError::new(
+                StatusCode::BAD_REQUEST,
+                1004,
+                format!("This field is not valid: {field} ({reason})"),
+            ),
             VaultError::GenericBadRequest(msg) => {
                 ApiError::new(StatusCode::BAD_REQUEST, 1024, msg.to_owned())
             }
@@ -104,6 +124,77 @@ impl From<&VaultError> for ApiError {
                 1005,
                 String::from("The user does not exist"),
             ),
+            VaultError::UserAlreadyExists => ApiError::new(
+                StatusCode::CONFLICT,
+                1003,
+                String::from("The user already exists"),
+            ),
+            VaultError::UserKeyAlreadyExists => ApiError::new(
+                StatusCode::CONFLICT,
+                1010,
+                String::from("The key already exists"),
+            ),
+            VaultError::UserKeyDoesNotExist => ApiError::new(
+                StatusCode::NOT_FOUND,
+                1018,
+                String::from("The key does not exist"),
+            ),
+            VaultError::UserDisabled(reason) => ApiError::new(
+                StatusCode::FORBIDDEN,
+                1015,
+                match reason {
+                    Some(reason) => format!("The user is disabled: {reason}"),
+                    None => String::from("The user is disabled"),
+                },
+            ),
+            VaultError::FilenameConflict => ApiError::new(
+                StatusCode::CONFLICT,
+                1019,
+                String::from("A file and a directory cannot have the same name"),
+            ),
+            VaultError::AdminRequired => ApiError::new(
+                StatusCode::FORBIDDEN,
+                1016,
+                String::from("Admin privileges are required"),
+            ),
+            VaultError::InvalidSignedUrl => ApiError::new(
+                StatusCode::FORBIDDEN,
+                1017,
+                String::from("The url is not valid or it expired"),
+            ),
+            VaultError::RegistrationClosed => ApiError::new(
+                StatusCode::FORBIDDEN,
+                1007,
+                String::from("Registration is closed"),
+            ),
+            VaultError::InsufficientScope(msg) => {
+                ApiError::new(StatusCode::FORBIDDEN, 1011, msg.to_owned())
+            }
+            VaultError::TotpRequired => ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                1012,
+                String::from("A TOTP code is required"),
+            ),
+            VaultError::TotpMismatch => ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                1013,
+                String::from("The TOTP code is not valid"),
+            ),
+            VaultError::TotpAlreadyEnabled => ApiError::new(
+                StatusCode::CONFLICT,
+                1014,
+                String::from("TOTP is already enabled"),
+            ),
+            VaultError::InvalidInviteCode => ApiError::new(
+                StatusCode::FORBIDDEN,
+                1008,
+                String::from("The invite code is not valid"),
+            ),
+            VaultError::TooManyAttempts { retry_after } => ApiError::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                1009,
+                format!("Too many failed attempts, retry in {retry_after} seconds"),
+            ),
             VaultError::ChallengeMismatchError => ApiError::new(
                 StatusCode::UNAUTHORIZED,
                 1006,
@@ -114,7 +205,7 @@ impl From<&VaultError> for ApiError {
                 1021,
                 String::from("Internal Server Error"),
             ),
-            VaultError::S3Error(e) => {
+            VaultError::StorageError(e) => {
                 ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, 1022, e.to_string())
             }
             VaultError::UnexpectedError(_) => ApiError::new(
@@ -132,7 +223,15 @@ impl From<&VaultError> for ApiError {
 impl error::ResponseError for VaultError {
     fn error_response(&self) -> actix_web::HttpResponse {
         let as_api_err: ApiError = self.into();
-        as_api_err.error_response()
+        let mut response = as_api_err.error_response();
+
+        if let VaultError::TooManyAttempts { retry_after } = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, header::HeaderValue::from(*retry_after));
+        }
+
+        response
     }
 
     fn status_code(&self) -> StatusCode {
@@ -0,0 +1,622 @@
+use super::{
+    build_object_key, checksum, into_page, is_valid_object_key, FileServerError, FilesPage,
+    LocalStorage, SignedUrls, StorageBackend, StorageUsage, StoredFile, UploadGrant,
+};
+use actix_web::web;
+use async_trait::async_trait;
+use ring::rand::SecureRandom;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Where the files are written while being uploaded, relative to the root
+const UPLOADS_DIR: &str = ".uploads";
+
+/// Where the checksum of each file is kept, relative to the root,
+/// so that listing the files doesn't require reading them
+const CHECKSUMS_DIR: &str = ".checksums";
+
+/// The files are kept in a local directory, with the same layout of
+/// the S3 objects (`<root>/users/<username>/<filename>`).
+/// The checksum of each file is saved aside, in `<root>/.checksums/<object key>.md5`.
+/// Unlike S3, a file can't be stored where a directory is, and the other way round
+/// (e.g. both `a` and `a/b`): the second one is refused as a conflict
+pub struct FsStorage {
+    root: PathBuf,
+    urls: SignedUrls,
+}
+
+impl FsStorage {
+    pub fn new(root: PathBuf, urls: SignedUrls) -> FsStorage {
+        FsStorage { root, urls }
+    }
+
+    /// Where a file is stored, None for the keys that could point
+    /// outside of the user's directory
+    fn path(&self, object_key: &str) -> Option<PathBuf> {
+        is_valid_object_key(object_key).then(|| self.root.join(object_key))
+    }
+
+    /// Where the checksum of a file is stored
+    fn checksum_path(&self, object_key: &str) -> Option<PathBuf> {
+        is_valid_object_key(object_key).then(|| {
+            self.root
+                .join(CHECKSUMS_DIR)
+                .join(format!("{object_key}.md5"))
+        })
+    }
+
+    /// The path of a file and the path of its checksum
+    fn paths(&self, object_key: &str) -> Option<(PathBuf, PathBuf)> {
+        self.path(object_key).zip(self.checksum_path(object_key))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for FsStorage {
+    async fn list_page(
+        &self,
+        username: &str,
+        prefix: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<FilesPage, FileServerError> {
+        let (user_dir, checksums_dir) = self
+            .paths(&format!("users/{username}"))
+            .ok_or(FileServerError::InvalidFilename)?;
+        let prefix = prefix.unwrap_or_default().to_owned();
+        let after = after.map(str::to_owned);
+
+        blocking(move || {
+            let files = find_files(&user_dir, &prefix, after.as_deref(), limit + 1)?
+                .into_iter()
+                .map(|(filename, path)| {
+                    let checksum_path = checksums_dir.join(format!("{filename}.md5"));
+
+                    Ok(StoredFile {
+                        checksum: read_checksum(&path, &checksum_path)?,
+                        size: std::fs::metadata(&path)?.len() as i64,
+                        filename,
+                    })
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+
+            Ok(into_page(files, limit))
+        })
+        .await
+    }
+
+    async fn stat(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<Option<StoredFile>, FileServerError> {
+        let (path, checksum_path) = self
+            .paths(&build_object_key(username, filename))
+            .ok_or(FileServerError::InvalidFilename)?;
+        let filename = filename.to_owned();
+
+        blocking(move || {
+            let size = match std::fs::metadata(&path) {
+                Ok(metadata) if metadata.is_dir() => return Ok(None),
+                Ok(metadata) => metadata.len() as i64,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e),
+            };
+
+            Ok(Some(StoredFile {
+                filename,
+                checksum: read_checksum(&path, &checksum_path)?,
+                size,
+            }))
+        })
+        .await
+    }
+
+    async fn delete(&self, username: &str, filename: &str) -> Result<(), FileServerError> {
+        let (path, checksum_path) = self
+            .paths(&build_object_key(username, filename))
+            .ok_or(FileServerError::InvalidFilename)?;
+        let (user_dir, checksums_dir) = self
+            .paths(&format!("users/{username}"))
+            .ok_or(FileServerError::InvalidFilename)?;
+
+        blocking(move || {
+            // An empty directory left behind would conflict with a file of the same name
+            remove_file(&path)?;
+            remove_empty_parents(&path, &user_dir)?;
+            remove_file(&checksum_path)?;
+            remove_empty_parents(&checksum_path, &checksums_dir)
+        })
+        .await
+    }
+
+    async fn delete_all(&self, username: &str) -> Result<usize, FileServerError> {
+        let (user_dir, checksums_dir) = self
+            .paths(&format!("users/{username}"))
+            .ok_or(FileServerError::InvalidFilename)?;
+
+        blocking(move || {
+            let deleted = find_files(&user_dir, "", None, usize::MAX)?.len();
+
+            remove_dir_all(&user_dir)?;
+            remove_dir_all(&checksums_dir)?;
+
+            Ok(deleted)
+        })
+        .await
+    }
+
+    /// Count the files of the user and their total size, without reading them
+    async fn usage(&self, username: &str) -> Result<StorageUsage, FileServerError> {
+        let user_dir = self
+            .path(&format!("users/{username}"))
+            .ok_or(FileServerError::InvalidFilename)?;
+
+        blocking(move || {
+            let mut usage = StorageUsage::default();
+
+            for (_, path) in find_files(&user_dir, "", None, usize::MAX)? {
+                usage.files += 1;
+                usage.bytes += std::fs::metadata(path)?.len() as i64;
+            }
+
+            Ok(usage)
+        })
+        .await
+    }
+
+    async fn upload_grant(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<UploadGrant, FileServerError> {
+        let object_key = build_object_key(username, filename);
+
+        if !is_valid_object_key(&object_key) {
+            return Err(FileServerError::InvalidFilename);
+        }
+
+        Ok(self.urls.upload_grant(&object_key))
+    }
+
+    async fn download_grant(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<String, FileServerError> {
+        let object_key = build_object_key(username, filename);
+
+        if !is_valid_object_key(&object_key) {
+            return Err(FileServerError::InvalidFilename);
+        }
+
+        Ok(self.urls.download_url(&object_key))
+    }
+}
+
+#[async_trait]
+impl LocalStorage for FsStorage {
+    fn urls(&self) -> &SignedUrls {
+        &self.urls
+    }
+
+    async fn store(&self, object_key: &str, content: Vec<u8>) -> Result<String, FileServerError> {
+        let (path, checksum_path) = self
+            .paths(object_key)
+            .ok_or(FileServerError::InvalidFilename)?;
+        let uploads_dir = self.root.join(UPLOADS_DIR);
+        let root = self.root.clone();
+
+        blocking(move || {
+            if is_conflicting(&path, &root) {
+                return Ok(None);
+            }
+
+            let checksum = checksum(&content);
+
+            write_file(&uploads_dir, &path, &content)?;
+            write_file(&uploads_dir, &checksum_path, checksum.as_bytes())?;
+
+            Ok(Some(checksum))
+        })
+        .await?
+        .ok_or(FileServerError::FilenameConflict)
+    }
+
+    async fn read(&self, object_key: &str) -> Result<Option<(Vec<u8>, String)>, FileServerError> {
+        let path = self
+            .path(object_key)
+            .ok_or(FileServerError::InvalidFilename)?;
+
+        blocking(move || match std::fs::read(&path) {
+            Ok(content) => {
+                let checksum = checksum(&content);
+                Ok(Some((content, checksum)))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound || path.is_dir() => Ok(None),
+            Err(e) => Err(e),
+        })
+        .await
+    }
+}
+
+/// Write the file aside first, then move it in place,
+/// so that nobody can read a partial file
+fn write_file(uploads_dir: &Path, path: &Path, content: &[u8]) -> io::Result<()> {
+    std::fs::create_dir_all(uploads_dir)?;
+    let tmp_path = uploads_dir.join(random_name()?);
+    std::fs::write(&tmp_path, content)?;
+
+    let parent = path.parent().expect("object keys are never empty");
+    std::fs::create_dir_all(parent)
+        .and_then(|_| std::fs::rename(&tmp_path, path))
+        .inspect_err(|_| {
+            let _ = std::fs::remove_file(&tmp_path);
+        })
+}
+
+/// Whether a directory is where the file would be stored,
+/// or a file is where one of its directories would be created
+fn is_conflicting(path: &Path, root: &Path) -> bool {
+    path.is_dir()
+        || path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| *dir != root)
+            .any(Path::is_file)
+}
+
+/// Remove the directories of a removed file that were left empty,
+/// up to `dir` (excluded)
+fn remove_empty_parents(path: &Path, dir: &Path) -> io::Result<()> {
+    for parent in path.ancestors().skip(1).take_while(|parent| *parent != dir) {
+        match std::fs::remove_dir(parent) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            // not empty, and so are its parents
+            Err(_) => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// The checksum saved when the file was stored. The files stored
+/// before the checksums were saved aside have it computed instead.
+fn read_checksum(path: &Path, checksum_path: &Path) -> io::Result<String> {
+    match std::fs::read_to_string(checksum_path) {
+        Ok(checksum) => Ok(checksum),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(checksum(&std::fs::read(path)?)),
+        Err(e) => Err(e),
+    }
+}
+
+fn remove_file(path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn remove_dir_all(path: &Path) -> io::Result<()> {
+    match std::fs::remove_dir_all(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn random_name() -> io::Result<String> {
+    let mut bytes = [0u8; 16];
+    ring::rand::SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| io::Error::other("cannot generate a random name"))?;
+    Ok(hex::encode(bytes))
+}
+
+/// The first `limit` files under `dir` whose path relative to `dir` (the file name)
+/// starts with `prefix` and sorts after `after`, with their full path, sorted by file name.
+/// The directories that can't hold any of them aren't read at all.
+fn find_files(
+    dir: &Path,
+    prefix: &str,
+    after: Option<&str>,
+    limit: usize,
+) -> io::Result<Vec<(String, PathBuf)>> {
+    struct Walk<'a> {
+        prefix: &'a str,
+        after: Option<&'a str>,
+        limit: usize,
+        files: Vec<(String, PathBuf)>,
+    }
+
+    impl Walk<'_> {
+        /// Whether a file name, or a directory name ending with '/', can match or hold matches
+        fn is_wanted(&self, name: &str) -> bool {
+            let is_dir = name.ends_with('/');
+
+            let in_prefix =
+                name.starts_with(self.prefix) || (is_dir && self.prefix.starts_with(name));
+            let is_after = self
+                .after
+                .is_none_or(|after| name > after || (is_dir && after.starts_with(name)));
+
+            in_prefix && is_after
+        }
+
+        fn walk(&mut self, dir: &Path, dir_name: &str) -> io::Result<()> {
+            let entries = match std::fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+                Err(e) => return Err(e),
+            };
+
+            let mut names = Vec::new();
+
+            for entry in entries {
+                let entry = entry?;
+
+                // we never store names that are not valid utf8
+                let Ok(name) = entry.file_name().into_string() else {
+                    continue;
+                };
+
+                let file_type = entry.file_type()?;
+
+                // The files of a directory sort as their names, prefixed by "<dir>/"
+                let name = if file_type.is_dir() {
+                    format!("{dir_name}{name}/")
+                } else if file_type.is_file() {
+                    format!("{dir_name}{name}")
+                } else {
+                    continue;
+                };
+
+                if self.is_wanted(&name) {
+                    names.push((name, entry.path()));
+                }
+            }
+
+            names.sort();
+
+            for (name, path) in names {
+                if self.files.len() >= self.limit {
+                    break;
+                }
+
+                if name.ends_with('/') {
+                    self.walk(&path, &name)?;
+                } else {
+                    self.files.push((name, path));
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    let mut walk = Walk {
+        prefix,
+        after,
+        limit,
+        files: Vec::new(),
+    };
+    walk.walk(dir, "")?;
+
+    Ok(walk.files)
+}
+
+/// Run blocking filesystem operations on the thread pool
+async fn blocking<F, T>(f: F) -> Result<T, FileServerError>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    web::block(f)
+        .await
+        .map_err(|e| FileServerError::Io(io::Error::other(e.to_string())))?
+        .map_err(FileServerError::Io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::token_keys::TokenKeys;
+    use ring::hmac;
+    use std::time::Duration;
+
+    fn storage(name: &str) -> FsStorage {
+        let root = std::env::temp_dir().join(format!(
+            "svanill-vault-fs-storage-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        let urls = SignedUrls::new(
+            "http://localhost/",
+            TokenKeys::new(hmac::Key::new(hmac::HMAC_SHA256, &[7u8; 32]), vec![]),
+            Duration::from_secs(60),
+        );
+
+        FsStorage::new(root, urls)
+    }
+
+    #[actix_rt::test]
+    async fn refuse_keys_outside_of_the_root() {
+        let storage = storage("paths");
+
+        assert!(storage.path("users/foo/bar/baz").is_some());
+        assert!(storage.path("users/foo/../../bar").is_none());
+        assert!(storage.upload_grant("foo", "../bar").await.is_err());
+        assert!(storage.download_grant("foo", "/etc/passwd").await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn store_list_and_delete_files() {
+        let storage = storage("files");
+        let content = b"some content".to_vec();
+
+        let checksum = storage
+            .store("users/foo/dir/bar", content.clone())
+            .await
+            .unwrap();
+        storage
+            .store("users/foo/baz", content.clone())
+            .await
+            .unwrap();
+        storage
+            .store("users/other/baz", content.clone())
+            .await
+            .unwrap();
+
+        assert_eq!("\"9893532233caff98cd083a116b013c0b\"", checksum);
+
+        let files = storage.list("foo").await.unwrap();
+        assert_eq!(
+            vec!["baz", "dir/bar"],
+            files
+                .iter()
+                .map(|f| f.filename.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(12, files[0].size);
+        assert_eq!(checksum, files[0].checksum);
+        assert_eq!(
+            checksum,
+            std::fs::read_to_string(storage.checksum_path("users/foo/baz").unwrap()).unwrap()
+        );
+        assert_eq!(
+            Some(files[0].clone()),
+            storage.stat("foo", "baz").await.unwrap()
+        );
+
+        // files stored without a checksum aside still have one
+        std::fs::remove_file(storage.checksum_path("users/foo/baz").unwrap()).unwrap();
+        assert_eq!(
+            Some(files[0].clone()),
+            storage.stat("foo", "baz").await.unwrap()
+        );
+
+        let page = storage
+            .list_page("foo", None, Some("baz"), 1)
+            .await
+            .unwrap();
+        assert_eq!(vec![files[1].clone()], page.files);
+        assert_eq!(None, page.next);
+
+        let page = storage
+            .list_page("foo", Some("dir/"), None, 1)
+            .await
+            .unwrap();
+        assert_eq!(vec![files[1].clone()], page.files);
+        assert_eq!(None, page.next);
+
+        assert_eq!(
+            StorageUsage {
+                files: 2,
+                bytes: 24
+            },
+            storage.usage("foo").await.unwrap()
+        );
+        assert_eq!(
+            Some((content, checksum)),
+            storage.read("users/foo/dir/bar").await.unwrap()
+        );
+
+        storage.delete("foo", "dir/bar").await.unwrap();
+        storage.delete("foo", "dir/bar").await.unwrap();
+        assert_eq!(None, storage.stat("foo", "dir/bar").await.unwrap());
+        assert!(!storage.checksum_path("users/foo/dir/bar").unwrap().exists());
+
+        assert_eq!(1, storage.delete_all("foo").await.unwrap());
+        assert!(storage.list("foo").await.unwrap().is_empty());
+        assert_eq!(1, storage.list("other").await.unwrap().len());
+
+        let _ = std::fs::remove_dir_all(&storage.root);
+    }
+
+    #[actix_rt::test]
+    async fn refuse_a_file_where_a_directory_is() {
+        let storage = storage("conflicts");
+        let content = b"some content".to_vec();
+
+        storage.store("users/foo/a", content.clone()).await.unwrap();
+        assert!(matches!(
+            storage.store("users/foo/a/b", content.clone()).await,
+            Err(FileServerError::FilenameConflict)
+        ));
+
+        storage
+            .store("users/foo/c/d", content.clone())
+            .await
+            .unwrap();
+        assert!(matches!(
+            storage.store("users/foo/c", content.clone()).await,
+            Err(FileServerError::FilenameConflict)
+        ));
+        assert_eq!(None, storage.stat("foo", "c").await.unwrap());
+        assert_eq!(None, storage.read("users/foo/c").await.unwrap());
+
+        // once the directory is emptied, the name is free again
+        storage.delete("foo", "c/d").await.unwrap();
+        storage.store("users/foo/c", content.clone()).await.unwrap();
+
+        assert_eq!(
+            vec!["a", "c"],
+            storage
+                .list("foo")
+                .await
+                .unwrap()
+                .iter()
+                .map(|f| f.filename.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        let _ = std::fs::remove_dir_all(&storage.root);
+    }
+
+    #[actix_rt::test]
+    async fn list_the_files_in_pages() {
+        let storage = storage("pages");
+        let content = b"some content".to_vec();
+
+        // "a-b" sorts before "a/b", even if the directory "a" sorts before "a-b"
+        for filename in ["a/c", "a-b", "a/b", "b", "a/d/e", "c/f"] {
+            storage
+                .store(&format!("users/foo/{filename}"), content.clone())
+                .await
+                .unwrap();
+        }
+
+        let mut filenames = Vec::new();
+        let mut after = None;
+
+        loop {
+            let page = storage
+                .list_page("foo", None, after.as_deref(), 2)
+                .await
+                .unwrap();
+            filenames.extend(page.files.into_iter().map(|f| f.filename));
+
+            match page.next {
+                Some(next) => after = Some(next),
+                None => break,
+            }
+        }
+
+        assert_eq!(vec!["a-b", "a/b", "a/c", "a/d/e", "b", "c/f"], filenames);
+
+        let page = storage
+            .list_page("foo", Some("a/"), Some("a/b"), 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            vec!["a/c", "a/d/e"],
+            page.files
+                .iter()
+                .map(|f| f.filename.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        let _ = std::fs::remove_dir_all(&storage.root);
+    }
+}
@@ -0,0 +1,236 @@
+use super::{
+    build_object_key, checksum, into_page, is_valid_object_key, FileServerError, FilesPage,
+    LocalStorage, SignedUrls, StorageBackend, StoredFile, UploadGrant,
+};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::RwLock;
+
+/// The files are kept in memory, by object key, and lost on restart.
+/// Meant for the tests and for ephemeral servers, that need no external service.
+pub struct MemoryStorage {
+    files: RwLock<BTreeMap<String, Vec<u8>>>,
+    urls: SignedUrls,
+}
+
+impl MemoryStorage {
+    pub fn new(urls: SignedUrls) -> MemoryStorage {
+        MemoryStorage {
+            files: RwLock::new(BTreeMap::new()),
+            urls,
+        }
+    }
+}
+
+#[async_trait]
+impl StorageBackend for MemoryStorage {
+    async fn list_page(
+        &self,
+        username: &str,
+        prefix: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<FilesPage, FileServerError> {
+        let user_prefix = build_object_key(username, "");
+        let prefix = build_object_key(username, prefix.unwrap_or_default());
+        let start = match after.map(|after| build_object_key(username, after)) {
+            Some(after) if after >= prefix => Bound::Excluded(after),
+            _ => Bound::Included(prefix.clone()),
+        };
+        let files = self.files.read().unwrap();
+
+        // the keys are sorted, so the matching files are all next to each other
+        let files = files
+            .range::<String, _>((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .take(limit + 1)
+            .map(|(key, content)| StoredFile {
+                filename: key[user_prefix.len()..].to_owned(),
+                checksum: checksum(content),
+                size: content.len() as i64,
+            })
+            .collect();
+
+        Ok(into_page(files, limit))
+    }
+
+    async fn stat(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<Option<StoredFile>, FileServerError> {
+        let files = self.files.read().unwrap();
+
+        Ok(files
+            .get(&build_object_key(username, filename))
+            .map(|content| StoredFile {
+                filename: filename.to_owned(),
+                checksum: checksum(content),
+                size: content.len() as i64,
+            }))
+    }
+
+    async fn delete(&self, username: &str, filename: &str) -> Result<(), FileServerError> {
+        self.files
+            .write()
+            .unwrap()
+            .remove(&build_object_key(username, filename));
+
+        Ok(())
+    }
+
+    async fn delete_all(&self, username: &str) -> Result<usize, FileServerError> {
+        let prefix = build_object_key(username, "");
+        let mut files = self.files.write().unwrap();
+
+        let before = files.len();
+        files.retain(|key, _| !key.starts_with(&prefix));
+
+        Ok(before - files.len())
+    }
+
+    async fn upload_grant(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<UploadGrant, FileServerError> {
+        let object_key = build_object_key(username, filename);
+
+        if !is_valid_object_key(&object_key) {
+            return Err(FileServerError::InvalidFilename);
+        }
+
+        Ok(self.urls.upload_grant(&object_key))
+    }
+
+    async fn download_grant(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<String, FileServerError> {
+        let object_key = build_object_key(username, filename);
+
+        if !is_valid_object_key(&object_key) {
+            return Err(FileServerError::InvalidFilename);
+        }
+
+        Ok(self.urls.download_url(&object_key))
+    }
+}
+
+#[async_trait]
+impl LocalStorage for MemoryStorage {
+    fn urls(&self) -> &SignedUrls {
+        &self.urls
+    }
+
+    async fn store(&self, object_key: &str, content: Vec<u8>) -> Result<String, FileServerError> {
+        if !is_valid_object_key(object_key) {
+            return Err(FileServerError::InvalidFilename);
+        }
+
+        let checksum = checksum(&content);
+        self.files
+            .write()
+            .unwrap()
+            .insert(object_key.to_owned(), content);
+
+        Ok(checksum)
+    }
+
+    async fn read(&self, object_key: &str) -> Result<Option<(Vec<u8>, String)>, FileServerError> {
+        let files = self.files.read().unwrap();
+
+        Ok(files
+            .get(object_key)
+            .map(|content| (content.clone(), checksum(content))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::token_keys::TokenKeys;
+    use crate::file_server::StorageUsage;
+    use ring::hmac;
+    use std::time::Duration;
+
+    fn storage() -> MemoryStorage {
+        MemoryStorage::new(SignedUrls::new(
+            "http://localhost",
+            TokenKeys::new(hmac::Key::new(hmac::HMAC_SHA256, &[7u8; 32]), vec![]),
+            Duration::from_secs(60),
+        ))
+    }
+
+    #[actix_rt::test]
+    async fn store_list_and_delete_files() {
+        let storage = storage();
+        let content = b"some content".to_vec();
+
+        storage
+            .store("users/foo/dir/bar", content.clone())
+            .await
+            .unwrap();
+        storage
+            .store("users/foo/baz", content.clone())
+            .await
+            .unwrap();
+        // a different user, whose name starts like the other one
+        storage
+            .store("users/foobar/baz", content.clone())
+            .await
+            .unwrap();
+
+        assert!(storage.store("users/foo/../baz", content).await.is_err());
+
+        let files = storage.list("foo").await.unwrap();
+        assert_eq!(
+            vec!["baz", "dir/bar"],
+            files
+                .iter()
+                .map(|f| f.filename.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("\"9893532233caff98cd083a116b013c0b\"", files[0].checksum);
+
+        let page = storage.list_page("foo", None, None, 1).await.unwrap();
+        assert_eq!(vec![files[0].clone()], page.files);
+        assert_eq!(Some("baz".to_owned()), page.next);
+
+        let page = storage
+            .list_page("foo", None, Some("baz"), 1)
+            .await
+            .unwrap();
+        assert_eq!(vec![files[1].clone()], page.files);
+        assert_eq!(None, page.next);
+
+        let page = storage
+            .list_page("foo", Some("dir/"), None, 1)
+            .await
+            .unwrap();
+        assert_eq!(vec![files[1].clone()], page.files);
+        assert_eq!(None, page.next);
+        let page = storage
+            .list_page("foo", Some("dir/"), Some("baz"), 1)
+            .await
+            .unwrap();
+        assert_eq!(vec![files[1].clone()], page.files);
+        assert_eq!(
+            StorageUsage {
+                files: 2,
+                bytes: 24
+            },
+            storage.usage("foo").await.unwrap()
+        );
+
+        storage.delete("foo", "baz").await.unwrap();
+        assert_eq!(None, storage.stat("foo", "baz").await.unwrap());
+        assert_eq!(None, storage.read("users/foo/baz").await.unwrap());
+
+        assert_eq!(1, storage.delete_all("foo").await.unwrap());
+        assert!(storage.list("foo").await.unwrap().is_empty());
+        assert_eq!(1, storage.list("foobar").await.unwrap().len());
+    }
+}
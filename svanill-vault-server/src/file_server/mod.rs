@@ -0,0 +1,413 @@
+mod fs;
+mod memory;
+mod s3;
+mod signed_urls;
+
+pub use fs::FsStorage;
+pub use memory::MemoryStorage;
+pub use s3::S3Storage;
+pub use signed_urls::{SignedUrlAction, SignedUrls};
+
+use async_trait::async_trait;
+use aws_credential_types::provider::error::CredentialsError;
+use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::operation::delete_object::DeleteObjectError;
+use aws_sdk_s3::operation::delete_objects::DeleteObjectsError;
+use aws_sdk_s3::operation::head_object::HeadObjectError;
+use aws_sdk_s3::operation::list_objects_v2::ListObjectsV2Error;
+use aws_smithy_types::error::operation::BuildError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use md5::{Digest, Md5};
+use std::collections::HashMap;
+use std::sync::Arc;
+use svanill_vault_openapi::RetrieveListOfUserFilesResponseContentItemContent;
+use thiserror::Error;
+
+type FileDTO = RetrieveListOfUserFilesResponseContentItemContent;
+
+/// Smallest file that can be uploaded, in bytes
+pub const MIN_FILE_SIZE: u64 = 10;
+/// Biggest file that can be uploaded, in bytes
+pub const MAX_FILE_SIZE: u64 = 1_048_576;
+/// Most files listed in a single page, the same limit of S3
+pub const MAX_LIST_PAGE_SIZE: usize = 1000;
+/// Most requests sent to the storage at once, for the files of a single page
+/// (e.g. to sign their urls, or to get the ETags missing from a listing)
+pub const MAX_CONCURRENT_REQUESTS: usize = 8;
+
+#[derive(Error, Debug)]
+pub enum FileServerError {
+    #[error("cannot retrieve object metadata")]
+    CannotRetrieveMetadata(#[from] SdkError<HeadObjectError>),
+    #[error("cannot retrieve files list")]
+    CannotRetrieveFilesList(#[from] SdkError<ListObjectsV2Error>),
+    #[error("failed to obtain S3 credentials")]
+    CredentialsError(#[from] CredentialsError),
+    #[error("missing credentials provider")]
+    MissingCredentialsProviderError,
+    #[error("cannot delete file")]
+    CannotDelete(#[from] SdkError<DeleteObjectError>),
+    #[error("cannot delete files")]
+    CannotDeleteMany(#[from] SdkError<DeleteObjectsError>),
+    #[error("cannot delete {0} files")]
+    PartialDeletion(usize),
+    #[error("cannot build S3 request")]
+    CannotBuildRequest(#[from] BuildError),
+    #[error("cannot generate policy data form")]
+    PolicyDataError(String),
+    #[error("cannot configure S3 region")]
+    RegionNotConfigured,
+    #[error("cannot sign request")]
+    CannotSignRequest,
+    #[error("cannot access the storage directory")]
+    Io(#[from] std::io::Error),
+    #[error("the file name cannot be stored")]
+    InvalidFilename,
+    #[error("a file and a directory cannot have the same name")]
+    FilenameConflict,
+    #[error("the cursor is not valid")]
+    InvalidCursor,
+}
+
+/// How many files a user has, and how many bytes they take
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StorageUsage {
+    pub files: i64,
+    pub bytes: i64,
+}
+
+/// A file of the user, as found in the storage
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredFile {
+    pub filename: String,
+    pub checksum: String,
+    pub size: i64,
+}
+
+/// Some files of a user, sorted by file name
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FilesPage {
+    pub files: Vec<StoredFile>,
+    /// The file name to list after to get the next page, None on the last page
+    pub next: Option<String>,
+}
+
+/// Where and how a client can upload a file, by posting
+/// the form data along with the file's content
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UploadGrant {
+    pub url: String,
+    pub form_data: HashMap<String, String>,
+}
+
+/// Where the files of the users are stored.
+///
+/// The files never go through the vault's API: the clients get
+/// expiring urls to upload and download them from the storage.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// At most `limit` files of the user whose name starts with `prefix`,
+    /// sorted by file name, starting after the file named `after`
+    async fn list_page(
+        &self,
+        username: &str,
+        prefix: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<FilesPage, FileServerError>;
+
+    /// Every file of the user, one page at a time
+    async fn list(&self, username: &str) -> Result<Vec<StoredFile>, FileServerError> {
+        let mut files = Vec::new();
+        let mut after: Option<String> = None;
+
+        loop {
+            let page = self
+                .list_page(username, None, after.as_deref(), MAX_LIST_PAGE_SIZE)
+                .await?;
+            files.extend(page.files);
+
+            match page.next {
+                Some(next) => after = Some(next),
+                None => return Ok(files),
+            }
+        }
+    }
+
+    /// A single file of the user, None if it does not exist
+    async fn stat(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<Option<StoredFile>, FileServerError>;
+
+    /// Remove a file, it's not an error if it does not exist
+    async fn delete(&self, username: &str, filename: &str) -> Result<(), FileServerError>;
+
+    /// Remove every file of the user, return how many were removed
+    async fn delete_all(&self, username: &str) -> Result<usize, FileServerError> {
+        let files = self.list(username).await?;
+
+        for file in files.iter() {
+            self.delete(username, &file.filename).await?;
+        }
+
+        Ok(files.len())
+    }
+
+    /// How many files the user has, and how many bytes they take
+    async fn usage(&self, username: &str) -> Result<StorageUsage, FileServerError> {
+        let files = self.list(username).await?;
+
+        Ok(StorageUsage {
+            files: files.len() as i64,
+            bytes: files.iter().map(|f| f.size).sum(),
+        })
+    }
+
+    /// Allow to upload a file, for a limited time
+    async fn upload_grant(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<UploadGrant, FileServerError>;
+
+    /// An url to download a file, valid for a limited time
+    async fn download_grant(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<String, FileServerError>;
+}
+
+/// A storage that the server itself serves, through the signed
+/// urls of its `/storage` routes
+#[async_trait]
+pub trait LocalStorage: StorageBackend {
+    fn urls(&self) -> &SignedUrls;
+
+    /// Write the content of an uploaded file, replacing the previous one
+    /// if any. Return the file's checksum.
+    async fn store(&self, object_key: &str, content: Vec<u8>) -> Result<String, FileServerError>;
+
+    /// The content of a file and its checksum, None if it does not exist
+    async fn read(&self, object_key: &str) -> Result<Option<(Vec<u8>, String)>, FileServerError>;
+}
+
+/// Access the files of the users, whatever the storage they are kept in
+pub struct FileServer {
+    backend: Arc<dyn StorageBackend>,
+    /// Set when the server must serve the files itself
+    local_storage: Option<Arc<dyn LocalStorage>>,
+}
+
+impl FileServer {
+    pub fn new(backend: impl StorageBackend + 'static) -> FileServer {
+        FileServer {
+            backend: Arc::new(backend),
+            local_storage: None,
+        }
+    }
+
+    pub fn with_local_storage(storage: impl LocalStorage + 'static) -> FileServer {
+        let storage = Arc::new(storage);
+
+        FileServer {
+            backend: storage.clone(),
+            local_storage: Some(storage),
+        }
+    }
+
+    /// The storage to serve the files from, if the server must do it itself
+    pub fn local_storage(&self) -> Option<&dyn LocalStorage> {
+        self.local_storage.as_deref()
+    }
+
+    /// A page of at most `limit` files of the user, starting at the (opaque) `cursor`.
+    /// Only the files whose name starts with `prefix` are listed, if given.
+    /// Return the files and the cursor of the next page, if any.
+    /// The download urls are signed only `with_urls`.
+    pub async fn get_files_list(
+        &self,
+        username: &str,
+        prefix: Option<&str>,
+        cursor: Option<&str>,
+        limit: usize,
+        with_urls: bool,
+    ) -> Result<(Vec<FileDTO>, Option<String>), FileServerError> {
+        let after = match cursor {
+            Some(cursor) => Some(decode_cursor(cursor).ok_or(FileServerError::InvalidCursor)?),
+            None => None,
+        };
+
+        let page = self
+            .backend
+            .list_page(username, prefix, after.as_deref(), limit)
+            .await?;
+
+        let files: Vec<FileDTO> = stream::iter(page.files)
+            .map(|f| async move {
+                let url = if with_urls {
+                    Some(self.backend.download_grant(username, &f.filename).await?)
+                } else {
+                    None
+                };
+
+                Ok::<FileDTO, FileServerError>(FileDTO {
+                    filename: f.filename,
+                    checksum: f.checksum,
+                    size: f.size as i32,
+                    url,
+                })
+            })
+            .buffered(MAX_CONCURRENT_REQUESTS)
+            .try_collect()
+            .await?;
+
+        Ok((files, page.next.as_deref().map(encode_cursor)))
+    }
+
+    /// A single file of the user, with its download url. None if it does not exist.
+    pub async fn get_file_url(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<Option<FileDTO>, FileServerError> {
+        let Some(file) = self.backend.stat(username, filename).await? else {
+            return Ok(None);
+        };
+
+        let url = self.backend.download_grant(username, filename).await?;
+
+        Ok(Some(FileDTO {
+            filename: file.filename,
+            checksum: file.checksum,
+            size: file.size as i32,
+            url: Some(url),
+        }))
+    }
+
+    /// A single file of the user, None if it does not exist
+    pub async fn stat_file(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<Option<StoredFile>, FileServerError> {
+        self.backend.stat(username, filename).await
+    }
+
+    pub async fn remove_file(&self, username: &str, filename: &str) -> Result<(), FileServerError> {
+        self.backend.delete(username, filename).await
+    }
+
+    pub async fn get_storage_usage(&self, username: &str) -> Result<StorageUsage, FileServerError> {
+        self.backend.usage(username).await
+    }
+
+    /// Delete every file of the user. Return the number of deleted files.
+    pub async fn remove_all_files(&self, username: &str) -> Result<usize, FileServerError> {
+        self.backend.delete_all(username).await
+    }
+
+    /// Return the url to upload the file, the url to retrieve it
+    /// and the form data to post along with the file
+    pub async fn get_post_policy_data(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<(String, String, HashMap<String, String>), FileServerError> {
+        let grant = self.backend.upload_grant(username, filename).await?;
+        let retrieve_url = self.backend.download_grant(username, filename).await?;
+
+        Ok((grant.url, retrieve_url, grant.form_data))
+    }
+}
+
+fn build_object_key(username: &str, filename: &str) -> String {
+    format!("users/{username}/{filename}")
+}
+
+/// Whether the object key can be used as a relative path,
+/// without pointing outside of the user's directory
+fn is_valid_object_key(key: &str) -> bool {
+    key.split('/').all(|component| {
+        !component.is_empty()
+            && component != "."
+            && component != ".."
+            && !component.contains(['\\', '\0'])
+    })
+}
+
+/// Same format of the S3 ETags, the quoted md5 of the content
+fn checksum(content: &[u8]) -> String {
+    format!("\"{}\"", hex::encode(Md5::digest(content)))
+}
+
+/// Given up to `limit + 1` sorted files, keep the first `limit` ones
+/// and tell whether there are more
+fn into_page(mut files: Vec<StoredFile>, limit: usize) -> FilesPage {
+    let next = if files.len() > limit {
+        files.truncate(limit);
+        files.last().map(|f| f.filename.clone())
+    } else {
+        None
+    };
+
+    FilesPage { files, next }
+}
+
+/// The cursors hide the file names, so that the clients
+/// don't rely on how the pages are made
+fn encode_cursor(after: &str) -> String {
+    URL_SAFE_NO_PAD.encode(after)
+}
+
+fn decode_cursor(cursor: &str) -> Option<String> {
+    String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()
+}
+
+fn split_object_key<'a>(username: &str, key: &'a str) -> Option<(&'a str, &'a str)> {
+    let prefix_len = "users/".len() + username.len() + "/".len();
+
+    if prefix_len > key.len() {
+        None
+    } else {
+        Some(key.split_at(prefix_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_object_key, decode_cursor, encode_cursor, is_valid_object_key};
+
+    #[test]
+    fn can_format_an_object_key() {
+        assert_eq!("users/foo/bar", build_object_key("foo", "bar"));
+    }
+
+    #[test]
+    fn refuse_keys_outside_of_the_user_directory() {
+        assert!(is_valid_object_key("users/foo/bar/baz"));
+
+        for key in [
+            "users/foo/../bar",
+            "users/foo/./bar",
+            "users/foo//bar",
+            "/users/foo/bar",
+            "users/foo/",
+            "users/foo/..\\bar",
+        ] {
+            assert!(!is_valid_object_key(key), "{key}");
+        }
+    }
+
+    #[test]
+    fn cursors_can_be_decoded() {
+        let cursor = encode_cursor("some/file name");
+
+        assert_eq!(Some("some/file name".to_owned()), decode_cursor(&cursor));
+        assert_eq!(None, decode_cursor("not a cursor"));
+    }
+}
@@ -0,0 +1,337 @@
+use super::{
+    build_object_key, split_object_key, FileServerError, FilesPage, StorageBackend, StorageUsage,
+    StoredFile, UploadGrant, MAX_CONCURRENT_REQUESTS, MAX_FILE_SIZE, MIN_FILE_SIZE,
+};
+use crate::post_policy::PostPolicy;
+use async_trait::async_trait;
+use aws_sdk_s3::config::Config as S3Config;
+use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::presigning::PresignedRequest;
+use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::types::{Delete, ObjectIdentifier};
+use aws_smithy_types::date_time::DateTime;
+use aws_types::region::Region;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::default::Default;
+use std::time::SystemTime;
+
+/// The files are kept in a S3 (compatible) bucket
+pub struct S3Storage {
+    pub region: Region,
+    pub bucket: String,
+    pub client: aws_sdk_s3::Client,
+    pub credentials: aws_credential_types::Credentials,
+    pub presigned_url_timeout: std::time::Duration,
+}
+
+impl S3Storage {
+    pub async fn new(
+        aws_s3_conf: S3Config,
+        credentials: aws_credential_types::Credentials,
+        bucket: String,
+        presigned_url_timeout: std::time::Duration,
+    ) -> Result<S3Storage, FileServerError> {
+        let region = aws_s3_conf
+            .region()
+            .ok_or(FileServerError::RegionNotConfigured)?
+            .to_owned();
+
+        let client = aws_sdk_s3::Client::from_conf(aws_s3_conf);
+
+        Ok(S3Storage {
+            region,
+            bucket,
+            client,
+            credentials,
+            presigned_url_timeout,
+        })
+    }
+
+    async fn get_presigned_retrieve_url_as_req(
+        &self,
+        key: &str,
+    ) -> Result<PresignedRequest, FileServerError> {
+        let conf = PresigningConfig::expires_in(self.presigned_url_timeout)
+            .map_err(|_| FileServerError::CannotSignRequest)?;
+
+        self.client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .presigned(conf)
+            .await
+            .map_err(|_| FileServerError::CannotSignRequest)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for S3Storage {
+    async fn list_page(
+        &self,
+        username: &str,
+        prefix: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<FilesPage, FileServerError> {
+        let s3_objects = self
+            .client
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .prefix(build_object_key(username, prefix.unwrap_or_default()))
+            .set_start_after(after.map(|filename| build_object_key(username, filename)))
+            .max_keys(limit as i32)
+            .send()
+            .await
+            .map_err(FileServerError::CannotRetrieveFilesList)?;
+
+        let next = if s3_objects.is_truncated().unwrap_or_default() {
+            s3_objects
+                .contents()
+                .last()
+                .and_then(|obj| obj.key())
+                .and_then(|key| split_object_key(username, key))
+                .map(|(_, filename)| filename.to_owned())
+        } else {
+            None
+        };
+
+        let files: Vec<StoredFile> = stream::iter(
+            s3_objects
+                .contents
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|x| x.key.is_some() && x.size().is_some())
+                .map(|obj| async move {
+                    let key = obj.key.unwrap(); // always ok, we filtered for Some(key) only
+                    let size = obj.size.unwrap(); // always ok, we filtered for Some(size) only
+
+                    let etag = if let Some(etag) = obj.e_tag {
+                        etag
+                    } else {
+                        self.client
+                            .head_object()
+                            .bucket(&self.bucket)
+                            .key(&key)
+                            .send()
+                            .await?
+                            .e_tag
+                            .unwrap_or_default()
+                    };
+
+                    let (_, filename) = split_object_key(username, &key)
+                        .expect("object key does not match user prefix");
+
+                    Ok::<StoredFile, FileServerError>(StoredFile {
+                        filename: filename.to_owned(),
+                        checksum: etag,
+                        size,
+                    })
+                }),
+        )
+        .buffered(MAX_CONCURRENT_REQUESTS)
+        .try_collect()
+        .await?;
+
+        Ok(FilesPage { files, next })
+    }
+
+    async fn stat(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<Option<StoredFile>, FileServerError> {
+        let key = build_object_key(username, filename);
+
+        let res = self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await;
+
+        match res {
+            Ok(obj) => Ok(Some(StoredFile {
+                filename: filename.to_owned(),
+                checksum: obj.e_tag.unwrap_or_default(),
+                size: obj.content_length.unwrap_or_default(),
+            })),
+            Err(SdkError::ServiceError(e)) if e.err().is_not_found() => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, username: &str, filename: &str) -> Result<(), FileServerError> {
+        let key = build_object_key(username, filename);
+
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete every file of the user, one page of objects at a time.
+    /// Return the number of deleted files.
+    async fn delete_all(&self, username: &str) -> Result<usize, FileServerError> {
+        let mut deleted = 0;
+        let mut continuation_token: Option<String> = None;
+
+        loop {
+            let s3_objects = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(format!("users/{username}/"))
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(FileServerError::CannotRetrieveFilesList)?;
+
+            let objects = s3_objects
+                .contents()
+                .iter()
+                .filter_map(|obj| obj.key())
+                .map(|key| ObjectIdentifier::builder().key(key).build())
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if !objects.is_empty() {
+                let objects_count = objects.len();
+
+                let res = self
+                    .client
+                    .delete_objects()
+                    .bucket(&self.bucket)
+                    .delete(
+                        Delete::builder()
+                            .set_objects(Some(objects))
+                            .quiet(true)
+                            .build()?,
+                    )
+                    .send()
+                    .await?;
+
+                if !res.errors().is_empty() {
+                    return Err(FileServerError::PartialDeletion(res.errors().len()));
+                }
+
+                deleted += objects_count;
+            }
+
+            continuation_token = match s3_objects.next_continuation_token() {
+                Some(token) if s3_objects.is_truncated().unwrap_or_default() => {
+                    Some(token.to_owned())
+                }
+                _ => break,
+            };
+        }
+
+        Ok(deleted)
+    }
+
+    /// Count the files of the user and their total size, one page of objects at a time
+    async fn usage(&self, username: &str) -> Result<StorageUsage, FileServerError> {
+        let mut usage = StorageUsage::default();
+        let mut continuation_token: Option<String> = None;
+
+        loop {
+            let s3_objects = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(format!("users/{username}/"))
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(FileServerError::CannotRetrieveFilesList)?;
+
+            for obj in s3_objects.contents() {
+                usage.files += 1;
+                usage.bytes += obj.size().unwrap_or_default();
+            }
+
+            continuation_token = match s3_objects.next_continuation_token() {
+                Some(token) if s3_objects.is_truncated().unwrap_or_default() => {
+                    Some(token.to_owned())
+                }
+                _ => break,
+            };
+        }
+
+        Ok(usage)
+    }
+
+    async fn upload_grant(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<UploadGrant, FileServerError> {
+        let key = build_object_key(username, filename);
+
+        let expiration_date = DateTime::from(
+            SystemTime::now()
+                .checked_add(self.presigned_url_timeout)
+                .expect("time overflow"),
+        );
+
+        let form_data = PostPolicy::default()
+            .set_bucket_name(&self.bucket)
+            .set_region(&self.region)
+            .set_access_key_id(self.credentials.access_key_id())
+            .set_secret_access_key(self.credentials.secret_access_key())
+            .set_key(&key)
+            .set_content_length_range(MIN_FILE_SIZE, MAX_FILE_SIZE)
+            .set_expiration(&expiration_date)
+            .set_checksum_algorithm_sha256()
+            .build_form_data()
+            .map_err(FileServerError::PolicyDataError)?;
+
+        let retrieve_url_as_req = self.get_presigned_retrieve_url_as_req(&key).await?;
+        let retrieve_url = retrieve_url_as_req.uri();
+
+        // This is terrible, but it gets the job done in our and most configuration
+        // We need to prepend the service and stop at the domain.
+        // E.g. from
+        // https://s3.eu-central-1.amazonaws.com/some-bucketname/...&X-Amz-Algorithm...
+        // to https://some-bucketname.s3.eu-central-1.amazonaws.com
+        // Protocol could be http/https, domain could also be an ip with port, etc...
+        // There must be an sdk function to get it, but I can't find it.
+        let v: Vec<&str> = retrieve_url.splitn(4, '/').collect();
+
+        if let [protocol, _, mut domain, _] = &v[..] {
+            let final_domain: String;
+
+            if !domain.starts_with(&self.bucket) {
+                final_domain = format!("{}.{}", &self.bucket, &domain);
+                domain = &final_domain;
+            }
+
+            let upload_url = [protocol, "//", domain].join("");
+            Ok(UploadGrant {
+                url: upload_url,
+                form_data,
+            })
+        } else {
+            Err(FileServerError::PolicyDataError(
+                "Cannot parse s3 url".to_owned(),
+            ))
+        }
+    }
+
+    async fn download_grant(
+        &self,
+        username: &str,
+        filename: &str,
+    ) -> Result<String, FileServerError> {
+        let key = build_object_key(username, filename);
+
+        Ok(self
+            .get_presigned_retrieve_url_as_req(&key)
+            .await?
+            .uri()
+            .to_string())
+    }
+}
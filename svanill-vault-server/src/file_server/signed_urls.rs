@@ -0,0 +1,209 @@
+use super::UploadGrant;
+use crate::auth::token_keys::TokenKeys;
+use crate::utils::unix_timestamp;
+use ring::hmac;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// What a signed url allows to do
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignedUrlAction {
+    Upload,
+    Download,
+}
+
+impl SignedUrlAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SignedUrlAction::Upload => "upload",
+            SignedUrlAction::Download => "download",
+        }
+    }
+}
+
+/// The urls of the files kept in a local storage, that the server itself
+/// serves through its `/storage` routes. They are signed, so that they
+/// can't be forged nor used once expired.
+pub struct SignedUrls {
+    public_url: String,
+    keys: TokenKeys,
+    url_timeout: Duration,
+}
+
+impl SignedUrls {
+    /// `public_url` is where the clients can reach the server, the current
+    /// one of `keys` signs the urls, which expire after `url_timeout`.
+    /// The urls signed by any of `keys` are valid, so that the outstanding
+    /// ones survive a rotation of the keys.
+    pub fn new(public_url: &str, keys: TokenKeys, url_timeout: Duration) -> SignedUrls {
+        SignedUrls {
+            public_url: public_url.trim_end_matches('/').to_owned(),
+            keys,
+            url_timeout,
+        }
+    }
+
+    /// Check that the signature of an url is valid and that it did not expire
+    pub fn verify(
+        &self,
+        action: SignedUrlAction,
+        object_key: &str,
+        expires: i64,
+        signature: &str,
+    ) -> bool {
+        if expires <= unix_timestamp() {
+            return false;
+        }
+
+        match hex::decode(signature) {
+            Ok(tag) => self
+                .keys
+                .verify(signed_data(action, object_key, expires).as_bytes(), &tag),
+            Err(_) => false,
+        }
+    }
+
+    /// Same form data of a S3 post policy: the object key, along
+    /// with the expiration and the signature
+    pub fn upload_grant(&self, object_key: &str) -> UploadGrant {
+        let (expires, signature) = self.sign(SignedUrlAction::Upload, object_key);
+
+        UploadGrant {
+            url: format!("{}/storage/upload", self.public_url),
+            form_data: HashMap::from([
+                ("key".to_owned(), object_key.to_owned()),
+                ("expires".to_owned(), expires.to_string()),
+                ("signature".to_owned(), signature),
+            ]),
+        }
+    }
+
+    pub fn download_url(&self, object_key: &str) -> String {
+        let (expires, signature) = self.sign(SignedUrlAction::Download, object_key);
+
+        let query = serde_urlencoded::to_string([
+            ("key", object_key),
+            ("expires", &expires.to_string()),
+            ("signature", &signature),
+        ])
+        .expect("the query is always serializable");
+
+        format!("{}/storage/download?{}", self.public_url, query)
+    }
+
+    /// The expiration and the signature of a new url
+    fn sign(&self, action: SignedUrlAction, object_key: &str) -> (i64, String) {
+        let expires = unix_timestamp() + self.url_timeout.as_secs() as i64;
+        let tag = hmac::sign(
+            self.keys.signing_key(),
+            signed_data(action, object_key, expires).as_bytes(),
+        );
+
+        (expires, hex::encode(tag))
+    }
+}
+
+fn signed_data(action: SignedUrlAction, object_key: &str, expires: i64) -> String {
+    format!("{}\n{}\n{}", action.as_str(), object_key, expires)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed_urls() -> SignedUrls {
+        SignedUrls::new(
+            "http://localhost/",
+            TokenKeys::new(hmac::Key::new(hmac::HMAC_SHA256, &[7u8; 32]), vec![]),
+            Duration::from_secs(60),
+        )
+    }
+
+    #[test]
+    fn signed_urls_can_be_verified() {
+        let urls = signed_urls();
+        let (expires, signature) = urls.sign(SignedUrlAction::Upload, "users/foo/bar");
+
+        assert!(urls.verify(
+            SignedUrlAction::Upload,
+            "users/foo/bar",
+            expires,
+            &signature
+        ));
+        assert!(!urls.verify(
+            SignedUrlAction::Download,
+            "users/foo/bar",
+            expires,
+            &signature
+        ));
+        assert!(!urls.verify(
+            SignedUrlAction::Upload,
+            "users/foo/baz",
+            expires,
+            &signature
+        ));
+        assert!(!urls.verify(
+            SignedUrlAction::Upload,
+            "users/foo/bar",
+            expires + 1,
+            &signature
+        ));
+        assert!(!urls.verify(SignedUrlAction::Upload, "users/foo/bar", expires, "nothex"));
+    }
+
+    #[test]
+    fn urls_signed_by_an_old_key_are_valid() {
+        let old_urls = signed_urls();
+        let (expires, signature) = old_urls.sign(SignedUrlAction::Download, "users/foo/bar");
+
+        let urls = SignedUrls::new(
+            "http://localhost/",
+            TokenKeys::new(
+                hmac::Key::new(hmac::HMAC_SHA256, &[8u8; 32]),
+                vec![hmac::Key::new(hmac::HMAC_SHA256, &[7u8; 32])],
+            ),
+            Duration::from_secs(60),
+        );
+
+        assert!(urls.verify(
+            SignedUrlAction::Download,
+            "users/foo/bar",
+            expires,
+            &signature
+        ));
+        assert_ne!(
+            signature,
+            urls.sign(SignedUrlAction::Download, "users/foo/bar").1
+        );
+    }
+
+    #[test]
+    fn expired_urls_are_rejected() {
+        let urls = signed_urls();
+        let expires = unix_timestamp() - 1;
+        let tag = hmac::sign(
+            urls.keys.signing_key(),
+            signed_data(SignedUrlAction::Download, "users/foo/bar", expires).as_bytes(),
+        );
+
+        assert!(!urls.verify(
+            SignedUrlAction::Download,
+            "users/foo/bar",
+            expires,
+            &hex::encode(tag)
+        ));
+    }
+
+    #[test]
+    fn build_the_urls() {
+        let urls = signed_urls();
+
+        assert_eq!(
+            "http://localhost/storage/upload",
+            urls.upload_grant("users/foo/bar").url
+        );
+        assert!(urls
+            .download_url("users/foo/a b")
+            .starts_with("http://localhost/storage/download?key=users%2Ffoo%2Fa+b&expires="));
+    }
+}
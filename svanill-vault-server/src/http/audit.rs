@@ -0,0 +1,95 @@
+use super::request_id::RequestId;
+use super::tokens::client_info;
+use crate::db::models::NewAuditEntry;
+use crate::db::{self, DbPool};
+use crate::errors::{ApiError, VaultError};
+use crate::utils::unix_timestamp;
+use actix_web::{web, Error, HttpMessage, HttpRequest, HttpResponse};
+
+/// What the user did, as recorded in the audit log
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditAction {
+    Login,
+    RequestUploadUrl,
+    ListFiles,
+    RetrieveFile,
+    RetrieveFileUrl,
+    StatFile,
+    RemoveFile,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Login => "login",
+            AuditAction::RequestUploadUrl => "request_upload_url",
+            AuditAction::ListFiles => "list_files",
+            AuditAction::RetrieveFile => "retrieve_file",
+            AuditAction::RetrieveFileUrl => "retrieve_file_url",
+            AuditAction::StatFile => "stat_file",
+            AuditAction::RemoveFile => "remove_file",
+        }
+    }
+}
+
+/// The code of the error the request failed with: the one of the api errors,
+/// or the http status for the errors raised by actix (e.g. a malformed body)
+fn error_code(e: &Error) -> i32 {
+    let code = match e.as_error::<VaultError>() {
+        Some(e) => ApiError::from(e).error.code,
+        None => e.as_response_error().status_code().as_u16().into(),
+    };
+
+    code as i32
+}
+
+/// Append an entry to the audit log, telling how the request went.
+/// Failing to write it is logged, but doesn't fail the request
+pub async fn record(
+    req: &HttpRequest,
+    pool: &web::Data<DbPool>,
+    username: &str,
+    action: AuditAction,
+    filename: Option<&str>,
+    result: &Result<HttpResponse, Error>,
+) {
+    let (outcome, error_code) = match result {
+        Ok(resp) if resp.status().is_success() => ("success", None),
+        Ok(resp) => ("failure", Some(resp.status().as_u16().into())),
+        Err(e) => ("failure", Some(error_code(e))),
+    };
+
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.clone())
+        .unwrap_or_default();
+    let ip = client_info(req).ip;
+    let username = username.to_owned();
+    let filename = filename.map(String::from);
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+    let inserted = web::block(move || {
+        db::actions::insert_audit_entry(
+            &mut conn,
+            &NewAuditEntry {
+                created_at: unix_timestamp(),
+                username: &username,
+                action: action.as_str(),
+                filename: filename.as_deref(),
+                ip: ip.as_deref(),
+                request_id: &request_id,
+                outcome,
+                error_code,
+            },
+        )
+    })
+    .await;
+
+    match inserted {
+        Ok(Ok(())) => (),
+        Ok(Err(e)) => log::error!("Cannot write the audit log: {e}"),
+        Err(e) => log::error!("Cannot write the audit log: {e}"),
+    }
+}
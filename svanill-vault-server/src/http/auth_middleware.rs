@@ -1,39 +1,107 @@
+use super::tokens::{validate_api_key, validate_token};
+use crate::auth::api_key::API_KEY_PREFIX;
 use crate::auth::auth_token::AuthToken;
-use crate::auth::tokens_cache::TokensCache;
-use crate::auth::Username;
-use crate::errors::ApiError;
+use crate::auth::token_keys::TokenKeys;
+use crate::auth::token_store::SharedTokenStore;
+use crate::auth::TokenMode;
+use crate::db::models::User;
+use crate::db::DbPool;
+use crate::errors::{ApiError, VaultError};
 use actix_web::{dev::ServiceRequest, http::StatusCode, web, Error, HttpMessage};
 use actix_web_httpauth::extractors::bearer::BearerAuth;
 use anyhow::Result;
-use std::sync::{Arc, RwLock};
-
-fn validate_token(
-    tokens_cache: &web::Data<Arc<RwLock<TokensCache>>>,
-    token: AuthToken,
-) -> Option<Username> {
-    tokens_cache
-        .write()
-        .unwrap() // PANIC on token's lock poisoned
-        .get_username(&token)
-        .map(Username)
-}
+use std::sync::Arc;
 
 pub async fn auth_validator(
     req: ServiceRequest,
     credentials: BearerAuth,
 ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
-    let maybe_tokens_cache = req.app_data::<web::Data<Arc<RwLock<TokensCache>>>>();
-    let tokens_cache =
-        maybe_tokens_cache.expect("the tokens_cache have not been setup to this route");
+    authenticate(req, credentials).await.map(|(req, _)| req)
+}
+
+/// Validate the token, then look up its user: the tokens of a disabled
+/// user are rejected straight away, even before they are revoked
+async fn authenticate(
+    req: ServiceRequest,
+    credentials: BearerAuth,
+) -> Result<(ServiceRequest, Option<User>), (Error, ServiceRequest)> {
+    let maybe_token_store = req.app_data::<web::Data<SharedTokenStore>>();
+    let token_store = maybe_token_store.expect("the token_store have not been setup to this route");
+    let maybe_token_keys = req.app_data::<web::Data<Arc<TokenKeys>>>();
+    let token_keys = maybe_token_keys.expect("the token_keys have not been setup to this route");
+    let maybe_token_mode = req.app_data::<web::Data<TokenMode>>();
+    let token_mode = maybe_token_mode.expect("the token_mode have not been setup to this route");
+    let maybe_pool = req.app_data::<web::Data<DbPool>>();
+    let pool = maybe_pool.expect("the pool have not been setup to this route");
+
+    let token = AuthToken(credentials.token().to_owned());
 
-    match validate_token(tokens_cache, AuthToken(credentials.token().to_owned())) {
-        Some(user) => {
+    let username =
+        match validate_token(token_mode, token_keys, token_store, pool, token.clone()).await {
+            Ok(Some(username)) => username,
+            Ok(None) => {
+                return Err((
+                    ApiError::new(StatusCode::UNAUTHORIZED, 401, "Unhauthorized".to_owned()).into(),
+                    req,
+                ))
+            }
+            Err(e) => return Err((e, req)),
+        };
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let maybe_user = {
+        let username = username.0.clone();
+        web::block(move || crate::db::actions::find_user_by_username(&mut conn, &username)).await
+    };
+
+    match maybe_user {
+        Ok(Ok(maybe_user)) => match maybe_user.as_ref().map(User::ensure_enabled) {
+            Some(Err(e)) => Err((e.into(), req)),
+            _ => {
+                req.extensions_mut().insert(username);
+                req.extensions_mut().insert(token);
+                Ok((req, maybe_user))
+            }
+        },
+        Ok(Err(e)) => Err((e.into(), req)),
+        Err(e) => Err((e.into(), req)),
+    }
+}
+
+/// Like `auth_validator`, but API keys are accepted too. The grant of the key
+/// is added to the request, for the handlers to enforce it
+pub async fn files_auth_validator(
+    req: ServiceRequest,
+    credentials: BearerAuth,
+) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    if !credentials.token().starts_with(API_KEY_PREFIX) {
+        return auth_validator(req, credentials).await;
+    }
+
+    let maybe_pool = req.app_data::<web::Data<DbPool>>();
+    let pool = maybe_pool.expect("the pool have not been setup to this route");
+
+    match validate_api_key(pool, credentials.token()).await {
+        Ok(Some((user, grant))) => {
             req.extensions_mut().insert(user);
+            req.extensions_mut().insert(grant);
             Ok(req)
         }
-        None => Err((
+        Ok(None) => Err((
             ApiError::new(StatusCode::UNAUTHORIZED, 401, "Unhauthorized".to_owned()).into(),
             req,
         )),
+        Err(e) => Err((e, req)),
+    }
+}
+
+/// Like `auth_validator`, but only admins are let through
+pub async fn admin_auth_validator(
+    req: ServiceRequest,
+    credentials: BearerAuth,
+) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    match authenticate(req, credentials).await? {
+        (req, Some(user)) if user.is_admin => Ok(req),
+        (req, _) => Err((VaultError::AdminRequired.into(), req)),
     }
 }
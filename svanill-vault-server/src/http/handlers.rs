@@ -1,259 +1,2095 @@
-use super::auth_middleware::auth_validator;
+use super::audit::{self, AuditAction};
+use super::auth_middleware::{admin_auth_validator, auth_validator, files_auth_validator};
 use super::handlers;
+use super::multipart;
+use super::tokens::{
+    client_info, issue_token, list_sessions, refresh_token, revoke_other_sessions, revoke_session,
+    revoke_token, revoke_user_tokens,
+};
+use crate::auth::answer_hash::{hash_answer, verify_answer, AnswerMatch};
+use crate::auth::api_key::{ApiKey, ApiKeyGrant, ApiKeyScope};
 use crate::auth::auth_token::AuthToken;
-use crate::auth::tokens_cache::TokensCache;
-use crate::auth::Username;
-use crate::file_server;
+use crate::auth::decoy_challenge::DecoyChallenges;
+use crate::auth::login_throttle::SharedLoginThrottle;
+use crate::auth::token_keys::TokenKeys;
+use crate::auth::token_store::SharedTokenStore;
+use crate::auth::totp::TotpSecret;
+use crate::auth::{TokenMode, Username};
+use crate::db::models::{
+    Credential, NewApiKey, NewLoginLockout, NewUser, NewUserKey, NewUserTotp, User, UserTotp,
+    DEFAULT_KEY_ID,
+};
+use crate::errors::ApiError;
+use crate::file_server::{
+    self, FileServerError, SignedUrlAction, StorageUsage, MAX_CONCURRENT_REQUESTS, MAX_FILE_SIZE,
+    MAX_LIST_PAGE_SIZE, MIN_FILE_SIZE,
+};
+use crate::registration::RegistrationMode;
+use crate::utils::unix_timestamp;
+use crate::validation::{validate_filename, validate_key_id, validate_username};
 use crate::{db, errors::VaultError};
 use actix_http::HttpMessage;
 use actix_web::middleware::ErrorHandlerResponse;
 use actix_web::{
-    delete, dev::ServiceResponse, get, guard, http, post, web, Error, HttpRequest, HttpResponse,
-    ResponseError,
+    delete, dev::ServiceResponse, get, guard, head, http, post, web, Error, HttpRequest,
+    HttpResponse, ResponseError,
 };
 use actix_web_httpauth::middleware::HttpAuthentication;
 use diesel::prelude::*;
 use diesel::r2d2::{self, ConnectionManager};
+use futures::stream::{self, StreamExt};
 use serde::Deserialize;
 use serde_json::json;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+use std::time::Instant;
 use svanill_vault_openapi::{
+    AddUserKeyRequest, AddUserKeyResponse, AdminCreateUserRequest, AdminDeleteUserResponse,
+    AdminDisableUserRequest, AdminLogoutUserResponse, AdminUserResponse,
     AnswerUserChallengeRequest, AnswerUserChallengeResponse, AskForTheChallengeResponse,
-    GetStartingEndpointsResponse, RemoveFileResponse, RequestUploadUrlRequestBody,
-    RequestUploadUrlResponse, RetrieveListOfUserFilesResponse,
-    RetrieveListOfUserFilesResponseContentItemContent,
+    ChangeChallengeRequest, ChangeChallengeResponse, ConfirmTotpRequest, ConfirmTotpResponse,
+    CreateApiKeyRequest, CreateApiKeyResponse, CreateUserRequest, CreateUserResponse,
+    DeleteUserResponse, DisableTotpRequest, DisableTotpResponse, EnableTotpResponse,
+    GetStartingEndpointsResponse, ListActivityResponse, ListAdminUsersResponse,
+    ListApiKeysResponse, ListSessionsResponse, ListUserKeysResponse, LogoutResponse,
+    RemoveApiKeyResponse, RemoveFileResponse, RemoveUserKeyResponse, RequestUploadUrlRequestBody,
+    RequestUploadUrlResponse, RetrieveFileResponse, RetrieveFileUrlResponse,
+    RetrieveListOfUserFilesResponse, RetrieveListOfUserFilesResponseContentItemContent,
 };
 
 type DbPool = r2d2::Pool<ConnectionManager<SqliteConnection>>;
 
-#[get("/")]
-async fn index(req: HttpRequest) -> HttpResponse {
-    HttpResponse::Ok().json(
-        serde_json::from_value::<GetStartingEndpointsResponse>(json!({
-            "status": 200,
-            "links": {
-                "request_auth_challenge": hateoas_auth_user_request_challenge(&req),
-                "create_user": hateoas_new_user(&req)
-            }
-        }))
-        .unwrap(),
+#[get("/")]
+async fn index(req: HttpRequest) -> HttpResponse {
+    HttpResponse::Ok().json(
+        serde_json::from_value::<GetStartingEndpointsResponse>(json!({
+            "status": 200,
+            "links": {
+                "request_auth_challenge": hateoas_auth_user_request_challenge(&req),
+                "create_user": hateoas_new_user(&req)
+            }
+        }))
+        .unwrap(),
+    )
+}
+
+#[get("/favicon.ico")]
+async fn favicon() -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header((http::header::CONTENT_TYPE, "image/svg+xml"))
+        .body(include_str!("../../favicon.svg"))
+}
+
+#[derive(Deserialize)]
+pub struct AuthRequestChallengeQueryFields {
+    // XXX this is optional, but it shouldn't be. Maybe make it part of the URI?
+    username: Option<String>,
+    key_id: Option<String>,
+}
+
+#[get("/auth/request-challenge")]
+async fn auth_user_request_challenge(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    decoy_challenges: web::Data<Arc<Option<DecoyChallenges>>>,
+    q: web::Query<AuthRequestChallengeQueryFields>,
+) -> Result<HttpResponse, Error> {
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+    let username = match &q.username {
+        Some(username) => username.clone(),
+        None => {
+            return Err(VaultError::FieldRequired {
+                field: "username".into(),
+            }
+            .into())
+        }
+    };
+
+    let key_id = q.key_id.clone();
+
+    let maybe_credentials = {
+        let username = username.clone();
+        let key_id = key_id.clone();
+        // A disabled user gets the challenge too, and is told so only after answering.
+        // None if the user does not exist, no credentials if the user lacks the key
+        web::block(move || -> Result<Option<Vec<Credential>>, VaultError> {
+            if db::actions::find_user_by_username(&mut conn, &username)?.is_none() {
+                return Ok(None);
+            }
+
+            match key_id {
+                Some(key_id) => db::actions::find_credential(&mut conn, &username, &key_id)
+                    .map(|found| Some(found.into_iter().collect())),
+                None => db::actions::find_credentials(&mut conn, &username).map(Some),
+            }
+        })
+        .await??
+    };
+
+    let credentials = match (maybe_credentials, decoy_challenges.as_ref().as_ref()) {
+        (Some(credentials), _) if !credentials.is_empty() => credentials,
+        // Pretend that the user, or the key, exists
+        (_, Some(decoys)) => {
+            let key_id = key_id.clone().unwrap_or_else(|| DEFAULT_KEY_ID.to_owned());
+            let challenge = if key_id == DEFAULT_KEY_ID {
+                decoys.challenge_for(&username)
+            } else {
+                decoys.challenge_for_key(&username, &key_id)
+            };
+            vec![Credential {
+                key_id,
+                challenge,
+                answer: String::new(),
+            }]
+        }
+        (None, None) => return Err(VaultError::UserDoesNotExist.into()),
+        (Some(_), None) => return Err(VaultError::UserKeyDoesNotExist.into()),
+    };
+
+    let mut content = json!({
+        "challenge": credentials[0].challenge,
+        "key_id": credentials[0].key_id,
+    });
+
+    if key_id.is_none() {
+        // A decoy has only the default key, so with decoys on the other keys of the
+        // real users aren't listed either: they must be asked for by their key id
+        let listed = if decoy_challenges.is_some() {
+            &credentials[..1]
+        } else {
+            &credentials[..]
+        };
+
+        content["challenges"] = listed
+            .iter()
+            .map(|c| json!({"key_id": c.key_id, "challenge": c.challenge}))
+            .collect();
+    }
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<AskForTheChallengeResponse>(json!({
+            "status": 200,
+            "content": content,
+            "links": {
+                "answer_auth_challenge": hateoas_auth_user_answer_challenge(&req),
+                "create_user": hateoas_new_user(&req)
+            }
+        }))
+        .unwrap(),
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn auth_user_answer_challenge(
+    req: HttpRequest,
+    payload: web::Json<AnswerUserChallengeRequest>,
+    pool: web::Data<DbPool>,
+    token_keys: web::Data<Arc<TokenKeys>>,
+    token_mode: web::Data<TokenMode>,
+    token_store: web::Data<SharedTokenStore>,
+    login_throttle: web::Data<SharedLoginThrottle>,
+    decoy_challenges: web::Data<Arc<Option<DecoyChallenges>>>,
+) -> Result<HttpResponse, Error> {
+    let audited_username = payload.username.clone();
+
+    // Every way out of the block, early returns included, is audited
+    let result: Result<HttpResponse, Error> = async {
+        let client = client_info(&req);
+        let payload = payload.into_inner();
+        let username = payload.username.clone();
+        let answer = payload.answer.clone();
+        let totp_code = payload.totp_code.clone();
+
+        check_login_throttle(&login_throttle, &username, client.ip.as_deref())?;
+
+        let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+        let key_id = payload.key_id.unwrap_or_else(|| DEFAULT_KEY_ID.to_owned());
+
+        let maybe_user = {
+            let key_id = key_id.clone();
+            web::block(move || {
+                let user = db::actions::find_user_by_username(&mut conn, &payload.username)?;
+                let (credential, totp) = match user {
+                    Some(_) => (
+                        db::actions::find_credential(&mut conn, &payload.username, &key_id)?,
+                        db::actions::find_user_totp(&mut conn, &payload.username)?,
+                    ),
+                    None => (None, None),
+                };
+                Ok::<_, VaultError>(user.map(|user| (user, credential, totp)))
+            })
+            .await?
+        };
+
+        if let Ok(Some((user, maybe_credential, maybe_totp))) = maybe_user {
+            let given_answer = answer.clone();
+            let answer_match = web::block(move || match maybe_credential {
+                Some(credential) => verify_answer(&credential.answer, &given_answer),
+                None => {
+                    // A key the user doesn't have, fail as a wrong answer would
+                    let _ = hash_answer(&given_answer);
+                    AnswerMatch::Invalid
+                }
+            })
+            .await?;
+
+            if answer_match == AnswerMatch::Invalid {
+                record_login_failure(&login_throttle, &pool, &username, client.ip.as_deref())
+                    .await?;
+                return Err(VaultError::ChallengeMismatchError.into());
+            }
+
+            user.ensure_enabled()?;
+
+            if let Some(totp) = maybe_totp.filter(|totp| totp.is_enabled()) {
+                // Only now that the answer is known to be right, tell that a code is needed
+                let code = totp_code.ok_or(VaultError::TotpRequired)?;
+
+                if !verify_totp_code(&pool, &totp, &code).await? {
+                    record_login_failure(&login_throttle, &pool, &username, client.ip.as_deref())
+                        .await?;
+                    return Err(VaultError::TotpMismatch.into());
+                }
+            }
+
+            if answer_match == AnswerMatch::ValidNeedsRehash {
+                // Transparently upgrade the stored answer, it's the only time we know it
+                let mut conn = pool.get().expect("couldn't get db connection from pool");
+                let username = user.username.clone();
+
+                let rehash = web::block(move || {
+                    let hashed_answer = hash_answer(&answer)?;
+                    db::actions::update_credential_answer(
+                        &mut conn,
+                        &username,
+                        &key_id,
+                        &hashed_answer,
+                    )
+                })
+                .await?;
+
+                if let Err(e) = rehash {
+                    log::error!("Cannot upgrade the answer of user {}: {}", user.username, e);
+                }
+            }
+
+            login_throttle.lock().unwrap().record_success(&username);
+
+            // Generate a new signed token
+            let token = issue_token(
+                &token_mode,
+                &token_keys,
+                &token_store,
+                user.username,
+                client,
+            )
+            .await?;
+
+            Ok(answer_user_challenge_response(&req, &token))
+        } else {
+            record_login_failure(&login_throttle, &pool, &username, client.ip.as_deref()).await?;
+
+            if decoy_challenges.is_some() {
+                // Fail like a wrong answer would, taking as long as checking it
+                web::block(move || hash_answer(&answer)).await??;
+                return Err(VaultError::ChallengeMismatchError.into());
+            }
+
+            Err(VaultError::UserDoesNotExist.into())
+        }
+    }
+    .await;
+
+    audit::record(
+        &req,
+        &pool,
+        &audited_username,
+        AuditAction::Login,
+        None,
+        &result,
+    )
+    .await;
+
+    result
+}
+
+/// Check a code of the user's TOTP secret, accepting it only once
+async fn verify_totp_code(pool: &DbPool, totp: &UserTotp, code: &str) -> Result<bool, Error> {
+    let secret = TotpSecret::from_hex(&totp.secret).ok_or_else(|| {
+        VaultError::UnexpectedError(format!("invalid TOTP secret for user {}", totp.username))
+    })?;
+
+    let step = match secret.verify(code, unix_timestamp(), totp.last_used_step) {
+        Some(step) => step,
+        None => return Ok(false),
+    };
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let username = totp.username.clone();
+
+    // Another request may have used a code of the same step in the meantime
+    let accepted =
+        web::block(move || db::actions::use_user_totp_step(&mut conn, &username, step)).await??;
+
+    Ok(accepted)
+}
+
+/// Fail if the client must wait before trying again to answer the challenge
+fn check_login_throttle(
+    login_throttle: &SharedLoginThrottle,
+    username: &str,
+    ip: Option<&str>,
+) -> Result<(), VaultError> {
+    let maybe_wait = login_throttle
+        .lock()
+        .unwrap()
+        .check(username, ip, Instant::now());
+
+    match maybe_wait {
+        Some(wait) => Err(VaultError::TooManyAttempts {
+            retry_after: wait.as_secs_f64().ceil() as u64,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Count a failed attempt to answer the challenge, recording the lockouts it caused
+async fn record_login_failure(
+    login_throttle: &SharedLoginThrottle,
+    pool: &DbPool,
+    username: &str,
+    ip: Option<&str>,
+) -> Result<(), Error> {
+    let lockouts = login_throttle
+        .lock()
+        .unwrap()
+        .record_failure(username, ip, Instant::now());
+
+    if lockouts.is_empty() {
+        return Ok(());
+    }
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+    web::block(move || {
+        let locked_at = unix_timestamp();
+
+        for lockout in lockouts {
+            log::warn!(
+                "Locked out {} {} for {} seconds, after {} failed attempts to answer the challenge",
+                lockout.subject.as_str(),
+                lockout.value,
+                lockout.duration.as_secs(),
+                lockout.failures
+            );
+
+            db::actions::insert_login_lockout(
+                &mut conn,
+                &NewLoginLockout {
+                    kind: lockout.subject.as_str(),
+                    subject: &lockout.value,
+                    failures: lockout.failures as i32,
+                    locked_at,
+                    locked_until: locked_at + lockout.duration.as_secs() as i64,
+                },
+            )?;
+        }
+
+        Ok::<(), VaultError>(())
+    })
+    .await??;
+
+    Ok(())
+}
+
+fn answer_user_challenge_response(req: &HttpRequest, token: &AuthToken) -> HttpResponse {
+    HttpResponse::Ok().json(
+        serde_json::from_value::<AnswerUserChallengeResponse>(json!({
+            "content": {
+                "token": token.to_string()
+            },
+            "links": {
+                "files_list": hateoas_list_user_files(req),
+                "request_upload_url": hateoas_request_upload_url(req),
+                "refresh": hateoas_refresh_token(req),
+                "logout": hateoas_logout(req),
+                "logout_everywhere": hateoas_logout_everywhere(req),
+                "change_challenge": hateoas_change_challenge(req),
+            },
+            "status":200
+        }))
+        .unwrap(),
+    )
+}
+
+#[post("/auth/refresh")]
+async fn refresh_auth_token(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    token_keys: web::Data<Arc<TokenKeys>>,
+    token_mode: web::Data<TokenMode>,
+    token_store: web::Data<SharedTokenStore>,
+) -> Result<HttpResponse, Error> {
+    let token = req.extensions().get::<AuthToken>().unwrap().clone();
+
+    match refresh_token(&token_mode, &token_keys, &token_store, &pool, token).await? {
+        Some(new_token) => Ok(answer_user_challenge_response(&req, &new_token)),
+        None => Err(ApiError::new(
+            http::StatusCode::UNAUTHORIZED,
+            401,
+            "Unhauthorized".to_owned(),
+        )
+        .into()),
+    }
+}
+
+fn logout_response(req: &HttpRequest) -> HttpResponse {
+    HttpResponse::Ok().json(
+        serde_json::from_value::<LogoutResponse>(json!({
+            "links": {
+                "request_auth_challenge": hateoas_auth_user_request_challenge(req),
+            },
+            "status": 200
+        }))
+        .unwrap(),
+    )
+}
+
+#[post("/auth/logout")]
+async fn logout(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    token_keys: web::Data<Arc<TokenKeys>>,
+    token_mode: web::Data<TokenMode>,
+    token_store: web::Data<SharedTokenStore>,
+) -> Result<HttpResponse, Error> {
+    let token = req.extensions().get::<AuthToken>().unwrap().clone();
+
+    revoke_token(&token_mode, &token_keys, &token_store, &pool, token).await?;
+
+    Ok(logout_response(&req))
+}
+
+#[delete("/auth/sessions")]
+async fn logout_everywhere(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    token_mode: web::Data<TokenMode>,
+    token_store: web::Data<SharedTokenStore>,
+) -> Result<HttpResponse, Error> {
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+
+    revoke_user_tokens(&token_mode, &token_store, &pool, username).await?;
+
+    Ok(logout_response(&req))
+}
+
+#[post("/auth/change-challenge")]
+#[allow(clippy::too_many_arguments)]
+async fn change_challenge(
+    req: HttpRequest,
+    payload: web::Json<ChangeChallengeRequest>,
+    pool: web::Data<DbPool>,
+    token_keys: web::Data<Arc<TokenKeys>>,
+    token_mode: web::Data<TokenMode>,
+    token_store: web::Data<SharedTokenStore>,
+    login_throttle: web::Data<SharedLoginThrottle>,
+) -> Result<HttpResponse, Error> {
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    let client = client_info(&req);
+    let payload = payload.into_inner();
+
+    if payload.new_challenge.is_empty() {
+        return Err(VaultError::FieldRequired {
+            field: "new_challenge".into(),
+        }
+        .into());
+    };
+
+    if payload.new_answer.is_empty() {
+        return Err(VaultError::FieldRequired {
+            field: "new_answer".into(),
+        }
+        .into());
+    };
+
+    // Guessing the current answer here is as good as on login
+    check_login_throttle(&login_throttle, &username, client.ip.as_deref())?;
+
+    let key_id = payload
+        .key_id
+        .clone()
+        .unwrap_or_else(|| DEFAULT_KEY_ID.to_owned());
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let credential = {
+        let username = username.clone();
+        let key_id = key_id.clone();
+        web::block(move || db::actions::find_credential(&mut conn, &username, &key_id))
+            .await??
+            .ok_or(VaultError::NotFound)?
+    };
+
+    let stored_answer = credential.answer.clone();
+    let answer = payload.answer.clone();
+    let answer_match = web::block(move || verify_answer(&stored_answer, &answer)).await?;
+
+    if answer_match == AnswerMatch::Invalid {
+        record_login_failure(&login_throttle, &pool, &username, client.ip.as_deref()).await?;
+        return Err(VaultError::ChallengeMismatchError.into());
+    }
+
+    login_throttle.lock().unwrap().record_success(&username);
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let changed = {
+        let username = username.clone();
+        let new_challenge = payload.new_challenge.clone();
+
+        web::block(move || {
+            let new_answer = hash_answer(&payload.new_answer)?;
+            db::actions::change_credential(
+                &mut conn,
+                &username,
+                &key_id,
+                &credential.answer,
+                &new_challenge,
+                &new_answer,
+            )
+        })
+        .await??
+    };
+
+    if !changed {
+        // the answer has been changed in the meantime
+        return Err(VaultError::ChallengeMismatchError.into());
+    }
+
+    let new_token = if payload.revoke_other_sessions.unwrap_or(false) {
+        let token = revoke_other_sessions(
+            &token_mode,
+            &token_keys,
+            &token_store,
+            &pool,
+            username,
+            client,
+        )
+        .await?;
+        Some(token.to_string())
+    } else {
+        None
+    };
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<ChangeChallengeResponse>(json!({
+            "status": 200,
+            "content": {
+                "challenge": payload.new_challenge,
+                "token": new_token,
+            },
+            "links": {
+                "files_list": hateoas_list_user_files(&req),
+            }
+        }))
+        .unwrap(),
+    ))
+}
+
+#[get("/auth/sessions")]
+async fn list_user_sessions(
+    req: HttpRequest,
+    token_keys: web::Data<Arc<TokenKeys>>,
+    token_mode: web::Data<TokenMode>,
+    token_store: web::Data<SharedTokenStore>,
+) -> Result<HttpResponse, Error> {
+    let (token, username) = {
+        let exts = req.extensions();
+        (
+            exts.get::<AuthToken>().unwrap().clone(),
+            exts.get::<Username>().unwrap().0.clone(),
+        )
+    };
+
+    let sessions = list_sessions(
+        &token_mode,
+        &token_keys,
+        &token_store,
+        &token,
+        username,
+        client_info(&req),
+    )
+    .await?;
+
+    let content: Vec<serde_json::Value> = sessions
+        .into_iter()
+        .map(|(session, is_current)| {
+            json!({
+                "content": {
+                    "id": session.id,
+                    "created_at": session.created_at,
+                    "last_seen_at": session.last_seen_at,
+                    "current": is_current,
+                    "ip": session.client.ip,
+                    "user_agent": session.client.user_agent,
+                },
+                "links": {
+                    "delete": hateoas_session_delete(&req, &session.id),
+                }
+            })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<ListSessionsResponse>(json!({
+            "content": content,
+            "status": 200
+        }))
+        .unwrap(),
+    ))
+}
+
+#[delete("/auth/sessions/{session_id}")]
+async fn revoke_user_session(
+    req: HttpRequest,
+    session_id: web::Path<String>,
+    pool: web::Data<DbPool>,
+    token_keys: web::Data<Arc<TokenKeys>>,
+    token_mode: web::Data<TokenMode>,
+    token_store: web::Data<SharedTokenStore>,
+) -> Result<HttpResponse, Error> {
+    let (token, username) = {
+        let exts = req.extensions();
+        (
+            exts.get::<AuthToken>().unwrap().clone(),
+            exts.get::<Username>().unwrap().0.clone(),
+        )
+    };
+
+    let revoked = revoke_session(
+        &token_mode,
+        &token_keys,
+        &token_store,
+        &pool,
+        token,
+        username,
+        session_id.into_inner(),
+    )
+    .await?;
+
+    if !revoked {
+        return Err(VaultError::NotFound.into());
+    }
+
+    Ok(logout_response(&req))
+}
+
+#[post("/users/")]
+async fn new_user(
+    req: HttpRequest,
+    payload: web::Json<CreateUserRequest>,
+    pool: web::Data<DbPool>,
+    token_keys: web::Data<Arc<TokenKeys>>,
+    token_mode: web::Data<TokenMode>,
+    token_store: web::Data<SharedTokenStore>,
+    registration: web::Data<RegistrationMode>,
+) -> Result<HttpResponse, Error> {
+    registration.check(payload.invite_code.as_deref())?;
+
+    validate_username(&payload.username)?;
+
+    if payload.challenge.is_empty() {
+        return Err(VaultError::FieldRequired {
+            field: "challenge".into(),
+        }
+        .into());
+    };
+
+    if payload.answer.is_empty() {
+        return Err(VaultError::FieldRequired {
+            field: "answer".into(),
+        }
+        .into());
+    };
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let payload = payload.into_inner();
+    let username = payload.username.clone();
+    let challenge = payload.challenge.clone();
+
+    web::block(move || {
+        db::actions::create_user(
+            &mut conn,
+            &NewUser {
+                username: &payload.username,
+                challenge: &payload.challenge,
+                answer: &hash_answer(&payload.answer)?,
+                is_admin: false,
+            },
+        )
+    })
+    .await??;
+
+    // The new user is logged in straight away
+    let token = issue_token(
+        &token_mode,
+        &token_keys,
+        &token_store,
+        username,
+        client_info(&req),
+    )
+    .await?;
+    let token_as_string = token.to_string();
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<CreateUserResponse>(json!({
+            "content": {
+                "challenge": challenge,
+                "token": token_as_string
+            },
+            "links": {
+                "files_list": hateoas_list_user_files(&req),
+                "request_upload_url": hateoas_request_upload_url(&req),
+            },
+            "status":200
+        }))
+        .unwrap(),
+    ))
+}
+
+#[delete("/users/")]
+async fn delete_user(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    token_mode: web::Data<TokenMode>,
+    token_store: web::Data<SharedTokenStore>,
+    file_server: web::Data<Arc<file_server::FileServer>>,
+) -> Result<HttpResponse, Error> {
+    let username = {
+        let exts = req.extensions();
+        exts.get::<Username>().unwrap().0.clone()
+    };
+
+    // Log out the user first, so that no new file can be uploaded while
+    // we are deleting the existing ones. If the deletion fails the account
+    // is still there and the user can log in again and retry.
+    revoke_user_tokens(&token_mode, &token_store, &pool, username.clone()).await?;
+
+    file_server
+        .remove_all_files(&username)
+        .await
+        .map_err(|e| VaultError::StorageError(Box::new(e)))?;
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+    web::block(move || db::actions::delete_user(&mut conn, &username)).await??;
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<DeleteUserResponse>(json!({
+            "links": {
+                "create_user": hateoas_new_user(&req),
+            },
+            "status": 200
+        }))
+        .unwrap(),
+    ))
+}
+
+#[get("/users/me/keys")]
+async fn list_user_keys(req: HttpRequest, pool: web::Data<DbPool>) -> Result<HttpResponse, Error> {
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let credentials =
+        web::block(move || db::actions::find_credentials(&mut conn, &username)).await??;
+
+    let content: Vec<serde_json::Value> = credentials
+        .into_iter()
+        .map(|credential| {
+            // the default key can be changed but not removed
+            let links = if credential.key_id == DEFAULT_KEY_ID {
+                json!({})
+            } else {
+                json!({ "delete": hateoas_user_key_delete(&req, &credential.key_id) })
+            };
+
+            json!({
+                "content": {
+                    "key_id": credential.key_id,
+                    "challenge": credential.challenge,
+                },
+                "links": links
+            })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<ListUserKeysResponse>(json!({
+            "content": content,
+            "status": 200
+        }))
+        .unwrap(),
+    ))
+}
+
+#[post("/users/me/keys")]
+async fn add_user_key(
+    req: HttpRequest,
+    payload: web::Json<AddUserKeyRequest>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, Error> {
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    let payload = payload.into_inner();
+
+    validate_key_id(&payload.key_id)?;
+
+    if payload.key_id == DEFAULT_KEY_ID {
+        return Err(VaultError::UserKeyAlreadyExists.into());
+    }
+
+    if payload.challenge.is_empty() {
+        return Err(VaultError::FieldRequired {
+            field: "challenge".into(),
+        }
+        .into());
+    };
+
+    if payload.answer.is_empty() {
+        return Err(VaultError::FieldRequired {
+            field: "answer".into(),
+        }
+        .into());
+    };
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let key_id = payload.key_id.clone();
+    let challenge = payload.challenge.clone();
+
+    web::block(move || {
+        db::actions::insert_user_key(
+            &mut conn,
+            &NewUserKey {
+                username: &username,
+                key_id: &payload.key_id,
+                challenge: &payload.challenge,
+                answer: &hash_answer(&payload.answer)?,
+                created_at: unix_timestamp(),
+            },
+        )
+    })
+    .await??;
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<AddUserKeyResponse>(json!({
+            "content": {
+                "key_id": key_id,
+                "challenge": challenge,
+            },
+            "links": {
+                "delete": hateoas_user_key_delete(&req, &key_id),
+            },
+            "status": 200
+        }))
+        .unwrap(),
+    ))
+}
+
+#[delete("/users/me/keys/{key_id}")]
+async fn remove_user_key(
+    req: HttpRequest,
+    key_id: web::Path<String>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, Error> {
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    let key_id = key_id.into_inner();
+
+    if key_id == DEFAULT_KEY_ID {
+        return Err(VaultError::GenericBadRequest(
+            "The default key can't be removed, only changed".into(),
+        )
+        .into());
+    }
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let deleted =
+        web::block(move || db::actions::delete_user_key(&mut conn, &username, &key_id)).await??;
+
+    if !deleted {
+        return Err(VaultError::NotFound.into());
+    }
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<RemoveUserKeyResponse>(json!({
+            "status": 200,
+        }))
+        .unwrap(),
+    ))
+}
+
+#[get("/users/me/api-keys")]
+async fn list_api_keys(req: HttpRequest, pool: web::Data<DbPool>) -> Result<HttpResponse, Error> {
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let keys = web::block(move || db::actions::find_user_api_keys(&mut conn, &username)).await??;
+
+    let content: Vec<serde_json::Value> = keys
+        .into_iter()
+        .map(|key| {
+            json!({
+                "content": {
+                    "id": key.id,
+                    "name": key.name,
+                    "scope": key.scope,
+                    "prefix": key.prefix,
+                    "created_at": key.created_at,
+                    "expires_at": key.expires_at,
+                },
+                "links": {
+                    "delete": hateoas_api_key_delete(&req, &key.id),
+                }
+            })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<ListApiKeysResponse>(json!({
+            "content": content,
+            "status": 200
+        }))
+        .unwrap(),
+    ))
+}
+
+#[post("/users/me/api-keys")]
+async fn create_api_key(
+    req: HttpRequest,
+    payload: web::Json<CreateApiKeyRequest>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, Error> {
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    let payload = payload.into_inner();
+    let now = unix_timestamp();
+
+    if payload.name.is_empty() {
+        return Err(VaultError::FieldRequired {
+            field: "name".into(),
+        }
+        .into());
+    };
+
+    let scope =
+        payload
+            .scope
+            .parse::<ApiKeyScope>()
+            .map_err(|reason| VaultError::InvalidField {
+                field: "scope".into(),
+                reason,
+            })?;
+
+    if payload.prefix.as_deref() == Some("") {
+        return Err(VaultError::InvalidField {
+            field: "prefix".into(),
+            reason: "must not be empty".into(),
+        }
+        .into());
+    }
+
+    if matches!(payload.expires_at, Some(expires_at) if expires_at <= now) {
+        return Err(VaultError::InvalidField {
+            field: "expires_at".into(),
+            reason: "must be in the future".into(),
+        }
+        .into());
+    }
+
+    let key = ApiKey::generate()?;
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+    {
+        let id = key.id.clone();
+        let secret_hash = key.secret_hash();
+        let payload = payload.clone();
+
+        web::block(move || {
+            db::actions::insert_api_key(
+                &mut conn,
+                &NewApiKey {
+                    id: &id,
+                    username: &username,
+                    name: &payload.name,
+                    secret_hash: &secret_hash,
+                    scope: scope.as_str(),
+                    prefix: payload.prefix.as_deref(),
+                    created_at: now,
+                    expires_at: payload.expires_at,
+                },
+            )
+        })
+        .await??;
+    }
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<CreateApiKeyResponse>(json!({
+            "content": {
+                "id": key.id,
+                "name": payload.name,
+                "scope": scope.as_str(),
+                "prefix": payload.prefix,
+                "created_at": now,
+                "expires_at": payload.expires_at,
+                "key": key.to_string(),
+            },
+            "links": {
+                "delete": hateoas_api_key_delete(&req, &key.id),
+            },
+            "status": 200
+        }))
+        .unwrap(),
+    ))
+}
+
+#[delete("/users/me/api-keys/{api_key_id}")]
+async fn remove_api_key(
+    req: HttpRequest,
+    api_key_id: web::Path<String>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, Error> {
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    let id = api_key_id.into_inner();
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let deleted =
+        web::block(move || db::actions::delete_user_api_key(&mut conn, &username, &id)).await??;
+
+    if !deleted {
+        return Err(VaultError::NotFound.into());
+    }
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<RemoveApiKeyResponse>(json!({
+            "status": 200,
+        }))
+        .unwrap(),
+    ))
+}
+
+/// Name of the service shown by the authenticator apps
+const TOTP_ISSUER: &str = "svanill-vault";
+
+#[post("/users/me/totp")]
+async fn enable_totp(req: HttpRequest, pool: web::Data<DbPool>) -> Result<HttpResponse, Error> {
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    let secret = TotpSecret::generate()?;
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+    {
+        let username = username.clone();
+        let secret = secret.to_hex();
+
+        web::block(move || {
+            db::actions::start_user_totp_enrollment(
+                &mut conn,
+                &NewUserTotp {
+                    username: &username,
+                    secret: &secret,
+                    created_at: unix_timestamp(),
+                },
+            )
+        })
+        .await??;
+    }
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<EnableTotpResponse>(json!({
+            "content": {
+                "secret": secret.to_base32(),
+                "otpauth_uri": secret.otpauth_uri(TOTP_ISSUER, &username),
+            },
+            "links": {
+                "confirm": hateoas_totp_confirm(&req),
+            },
+            "status": 200
+        }))
+        .unwrap(),
+    ))
+}
+
+#[post("/users/me/totp/confirm")]
+async fn confirm_totp(
+    req: HttpRequest,
+    payload: web::Json<ConfirmTotpRequest>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, Error> {
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    let code = payload.into_inner().code;
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+    let totp = {
+        let username = username.clone();
+        web::block(move || db::actions::find_user_totp(&mut conn, &username))
+            .await??
+            .ok_or(VaultError::NotFound)?
+    };
+
+    if totp.is_enabled() {
+        return Err(VaultError::TotpAlreadyEnabled.into());
+    }
+
+    let now = unix_timestamp();
+    let step = TotpSecret::from_hex(&totp.secret)
+        .and_then(|secret| secret.verify(&code, now, None))
+        .ok_or(VaultError::TotpMismatch)?;
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let confirmed =
+        web::block(move || db::actions::confirm_user_totp(&mut conn, &username, step, now))
+            .await??;
+
+    if !confirmed {
+        return Err(VaultError::NotFound.into());
+    }
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<ConfirmTotpResponse>(json!({
+            "status": 200,
+        }))
+        .unwrap(),
+    ))
+}
+
+#[delete("/users/me/totp")]
+async fn disable_totp(
+    req: HttpRequest,
+    payload: web::Json<DisableTotpRequest>,
+    pool: web::Data<DbPool>,
+    login_throttle: web::Data<SharedLoginThrottle>,
+) -> Result<HttpResponse, Error> {
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    let client = client_info(&req);
+    let code = payload.into_inner().code;
+
+    // Guessing the code is throttled as answering the challenge is
+    check_login_throttle(&login_throttle, &username, client.ip.as_deref())?;
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+    let totp = {
+        let username = username.clone();
+        web::block(move || db::actions::find_user_totp(&mut conn, &username))
+            .await??
+            .ok_or(VaultError::NotFound)?
+    };
+
+    // A pending secret can be dropped without a code, it never protected anything
+    if totp.is_enabled() && !verify_totp_code(&pool, &totp, &code).await? {
+        record_login_failure(&login_throttle, &pool, &username, client.ip.as_deref()).await?;
+        return Err(VaultError::TotpMismatch.into());
+    }
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let deleted = web::block(move || db::actions::delete_user_totp(&mut conn, &username)).await??;
+
+    if !deleted {
+        return Err(VaultError::NotFound.into());
+    }
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<DisableTotpResponse>(json!({
+            "status": 200,
+        }))
+        .unwrap(),
+    ))
+}
+
+/// How many audit entries are listed when the limit is missing
+const ACTIVITY_DEFAULT_LIMIT: i64 = 50;
+/// Most audit entries listed at once
+const ACTIVITY_MAX_LIMIT: i64 = 500;
+
+#[derive(Deserialize)]
+pub struct ListActivityQueryFields {
+    limit: Option<i64>,
+}
+
+#[get("/users/me/activity")]
+async fn list_activity(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    q: web::Query<ListActivityQueryFields>,
+) -> Result<HttpResponse, Error> {
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    let limit = q
+        .limit
+        .unwrap_or(ACTIVITY_DEFAULT_LIMIT)
+        .clamp(1, ACTIVITY_MAX_LIMIT);
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let entries =
+        web::block(move || db::actions::find_user_audit_entries(&mut conn, &username, limit))
+            .await??;
+
+    let content: Vec<serde_json::Value> = entries
+        .into_iter()
+        .map(|entry| {
+            json!({
+                "created_at": entry.created_at,
+                "action": entry.action,
+                "filename": entry.filename,
+                "ip": entry.ip,
+                "request_id": entry.request_id,
+                "outcome": entry.outcome,
+                "error_code": entry.error_code,
+            })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<ListActivityResponse>(json!({
+            "content": content,
+            "status": 200
+        }))
+        .unwrap(),
+    ))
+}
+
+/// Longest reason to disable a user we accept
+const DISABLED_REASON_MAX_LENGTH: usize = 255;
+
+/// Admins can't disable or delete their own account, not to lock themselves out
+fn check_not_current_user(req: &HttpRequest, username: &str) -> Result<(), VaultError> {
+    if req.extensions().get::<Username>().unwrap().0 == username {
+        return Err(VaultError::GenericBadRequest(
+            "Admins can't disable or delete their own account".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// The user as shown to the admins, with the links to manage it.
+/// Without the usage, the user's files and bytes are left out
+fn admin_user_json(
+    req: &HttpRequest,
+    user: &User,
+    usage: Option<StorageUsage>,
+) -> serde_json::Value {
+    let mut links = json!({
+        "delete": hateoas_admin_user(req, "admin_delete_user", &user.username),
+        "logout": hateoas_admin_user(req, "admin_logout_user", &user.username),
+    });
+
+    if user.is_disabled() {
+        links["enable"] = hateoas_admin_user(req, "admin_enable_user", &user.username);
+    } else {
+        links["disable"] = hateoas_admin_user(req, "admin_disable_user", &user.username);
+    }
+
+    json!({
+        "content": {
+            "username": user.username,
+            "is_admin": user.is_admin,
+            "disabled_at": user.disabled_at,
+            "disabled_reason": user.disabled_reason,
+            "files": usage.map(|usage| usage.files),
+            "bytes": usage.map(|usage| usage.bytes),
+        },
+        "links": links
+    })
+}
+
+/// Respond with the user's current state, as shown to the admins
+async fn admin_user_response(
+    req: &HttpRequest,
+    pool: &DbPool,
+    file_server: &file_server::FileServer,
+    username: String,
+) -> Result<HttpResponse, Error> {
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let user = web::block(move || db::actions::find_user_by_username(&mut conn, &username))
+        .await??
+        .ok_or(VaultError::NotFound)?;
+
+    let usage = file_server
+        .get_storage_usage(&user.username)
+        .await
+        .map_err(|e| VaultError::StorageError(Box::new(e)))?;
+
+    let mut body = admin_user_json(req, &user, Some(usage));
+    body["status"] = json!(200);
+
+    Ok(HttpResponse::Ok().json(serde_json::from_value::<AdminUserResponse>(body).unwrap()))
+}
+
+/// Fail with NotFound unless the user exists
+async fn check_user_exists(pool: &DbPool, username: &str) -> Result<(), Error> {
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let username = username.to_owned();
+
+    web::block(move || db::actions::find_user_by_username(&mut conn, &username))
+        .await??
+        .ok_or(VaultError::NotFound)?;
+
+    Ok(())
+}
+
+#[get("/users")]
+async fn admin_list_users(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    file_server: web::Data<Arc<file_server::FileServer>>,
+) -> Result<HttpResponse, Error> {
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let users = web::block(move || db::actions::find_users(&mut conn)).await??;
+
+    // A storage failure hides the usage of that user only, instead of failing the whole list
+    let content: Vec<serde_json::Value> = stream::iter(users.iter())
+        .map(|user| async {
+            let usage = match file_server.get_storage_usage(&user.username).await {
+                Ok(usage) => Some(usage),
+                Err(e) => {
+                    log::error!(
+                        "Cannot get the storage usage of user {}: {}",
+                        user.username,
+                        e
+                    );
+                    None
+                }
+            };
+
+            admin_user_json(&req, user, usage)
+        })
+        .buffered(MAX_CONCURRENT_REQUESTS)
+        .collect()
+        .await;
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<ListAdminUsersResponse>(json!({
+            "content": content,
+            "status": 200
+        }))
+        .unwrap(),
+    ))
+}
+
+#[post("/users")]
+async fn admin_create_user(
+    req: HttpRequest,
+    payload: web::Json<AdminCreateUserRequest>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, Error> {
+    let payload = payload.into_inner();
+
+    validate_username(&payload.username)?;
+
+    if payload.challenge.is_empty() {
+        return Err(VaultError::FieldRequired {
+            field: "challenge".into(),
+        }
+        .into());
+    };
+
+    if payload.answer.is_empty() {
+        return Err(VaultError::FieldRequired {
+            field: "answer".into(),
+        }
+        .into());
+    };
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+    let user = web::block(move || {
+        db::actions::create_user(
+            &mut conn,
+            &NewUser {
+                username: &payload.username,
+                challenge: &payload.challenge,
+                answer: &hash_answer(&payload.answer)?,
+                is_admin: payload.is_admin.unwrap_or_default(),
+            },
+        )?;
+
+        db::actions::find_user_by_username(&mut conn, &payload.username)
+    })
+    .await??
+    .ok_or(VaultError::NotFound)?;
+
+    // A new user has no files yet
+    let mut body = admin_user_json(&req, &user, Some(StorageUsage::default()));
+    body["status"] = json!(200);
+
+    Ok(HttpResponse::Ok().json(serde_json::from_value::<AdminUserResponse>(body).unwrap()))
+}
+
+#[delete("/users/{username}")]
+async fn admin_delete_user(
+    req: HttpRequest,
+    username: web::Path<String>,
+    pool: web::Data<DbPool>,
+    token_mode: web::Data<TokenMode>,
+    token_store: web::Data<SharedTokenStore>,
+    file_server: web::Data<Arc<file_server::FileServer>>,
+) -> Result<HttpResponse, Error> {
+    let username = username.into_inner();
+
+    check_not_current_user(&req, &username)?;
+    check_user_exists(&pool, &username).await?;
+
+    // Same as when users delete their own account
+    revoke_user_tokens(&token_mode, &token_store, &pool, username.clone()).await?;
+
+    file_server
+        .remove_all_files(&username)
+        .await
+        .map_err(|e| VaultError::StorageError(Box::new(e)))?;
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+    web::block(move || db::actions::delete_user(&mut conn, &username)).await??;
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<AdminDeleteUserResponse>(json!({
+            "status": 200,
+        }))
+        .unwrap(),
+    ))
+}
+
+#[post("/users/{username}/disable")]
+async fn admin_disable_user(
+    req: HttpRequest,
+    username: web::Path<String>,
+    payload: Option<web::Json<AdminDisableUserRequest>>,
+    pool: web::Data<DbPool>,
+    token_mode: web::Data<TokenMode>,
+    token_store: web::Data<SharedTokenStore>,
+    file_server: web::Data<Arc<file_server::FileServer>>,
+) -> Result<HttpResponse, Error> {
+    let username = username.into_inner();
+
+    let reason = payload
+        .and_then(|payload| payload.into_inner().reason)
+        .filter(|reason| !reason.is_empty());
+
+    check_not_current_user(&req, &username)?;
+
+    if reason
+        .as_ref()
+        .is_some_and(|reason| reason.chars().count() > DISABLED_REASON_MAX_LENGTH)
+    {
+        return Err(VaultError::InvalidField {
+            field: "reason".into(),
+            reason: format!("must be at most {DISABLED_REASON_MAX_LENGTH} characters long"),
+        }
+        .into());
+    }
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let updated = {
+        let username = username.clone();
+        web::block(move || {
+            db::actions::set_user_disabled_at(
+                &mut conn,
+                &username,
+                Some(unix_timestamp()),
+                reason.as_deref(),
+            )
+        })
+        .await??
+    };
+
+    if !updated {
+        return Err(VaultError::NotFound.into());
+    }
+
+    // Disabling logs the user out everywhere
+    revoke_user_tokens(&token_mode, &token_store, &pool, username.clone()).await?;
+
+    admin_user_response(&req, &pool, &file_server, username).await
+}
+
+#[post("/users/{username}/enable")]
+async fn admin_enable_user(
+    req: HttpRequest,
+    username: web::Path<String>,
+    pool: web::Data<DbPool>,
+    file_server: web::Data<Arc<file_server::FileServer>>,
+) -> Result<HttpResponse, Error> {
+    let username = username.into_inner();
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let updated = {
+        let username = username.clone();
+        web::block(move || db::actions::set_user_disabled_at(&mut conn, &username, None, None))
+            .await??
+    };
+
+    if !updated {
+        return Err(VaultError::NotFound.into());
+    }
+
+    admin_user_response(&req, &pool, &file_server, username).await
+}
+
+#[post("/users/{username}/logout")]
+async fn admin_logout_user(
+    username: web::Path<String>,
+    pool: web::Data<DbPool>,
+    token_mode: web::Data<TokenMode>,
+    token_store: web::Data<SharedTokenStore>,
+) -> Result<HttpResponse, Error> {
+    let username = username.into_inner();
+
+    check_user_exists(&pool, &username).await?;
+    revoke_user_tokens(&token_mode, &token_store, &pool, username).await?;
+
+    Ok(HttpResponse::Ok().json(
+        serde_json::from_value::<AdminLogoutUserResponse>(json!({
+            "status": 200,
+        }))
+        .unwrap(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct ExportAuditLogQueryFields {
+    username: Option<String>,
+    since: Option<i64>,
+}
+
+/// Export the audit log as JSONL, one entry per line
+#[get("/audit-log")]
+async fn admin_export_audit_log(
+    pool: web::Data<DbPool>,
+    q: web::Query<ExportAuditLogQueryFields>,
+) -> Result<HttpResponse, Error> {
+    let q = q.into_inner();
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let entries = web::block(move || {
+        db::actions::find_audit_entries(&mut conn, q.username.as_deref(), q.since)
+    })
+    .await??;
+
+    let mut body = String::new();
+
+    for entry in entries {
+        body.push_str(&serde_json::to_string(&entry).unwrap());
+        body.push('\n');
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .body(body))
+}
+
+#[post("/request-upload-url")]
+async fn request_upload_url(
+    req: HttpRequest,
+    payload: web::Json<RequestUploadUrlRequestBody>,
+    file_server: web::Data<Arc<file_server::FileServer>>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, Error> {
+    let audited_filename = payload.filename.clone();
+
+    let result: Result<HttpResponse, Error> = async {
+        let filename = validate_filename(&payload.filename)?;
+
+        let exts = std::rc::Rc::new(req.extensions());
+        let exts = exts.clone();
+        let username = &exts.get::<Username>().unwrap().0;
+
+        if let Some(grant) = exts.get::<ApiKeyGrant>() {
+            grant.check(ApiKeyScope::Write, &filename)?;
+        }
+
+        let (upload_url, retrieve_url, form_data) = file_server
+            .get_post_policy_data(username, &filename)
+            .await
+            .map_err(|e| VaultError::PolicyDataError(Box::new(e)))?;
+
+        Ok(HttpResponse::Ok().json(
+            serde_json::from_value::<RequestUploadUrlResponse>(json!({
+                "links": {
+                    "retrieve_url": {
+                        "href": retrieve_url,
+                        "rel": "file",
+                    },
+                    "upload_url": {
+                        "form_data": form_data,
+                        "href": upload_url,
+                        "rel": "file",
+                    }
+                },
+                "status":200
+            }))
+            .unwrap(),
+        ))
+    }
+    .await;
+
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    audit::record(
+        &req,
+        &pool,
+        &username,
+        AuditAction::RequestUploadUrl,
+        Some(&audited_filename),
+        &result,
     )
-}
+    .await;
 
-#[get("/favicon.ico")]
-async fn favicon() -> HttpResponse {
-    HttpResponse::Ok()
-        .insert_header((http::header::CONTENT_TYPE, "image/svg+xml"))
-        .body(include_str!("../../favicon.svg"))
+    result
 }
 
 #[derive(Deserialize)]
-pub struct AuthRequestChallengeQueryFields {
-    // XXX this is optional, but it shouldn't be. Maybe make it part of the URI?
-    username: Option<String>,
+pub struct ListUserFilesQueryFields {
+    limit: Option<usize>,
+    cursor: Option<String>,
+    urls: Option<bool>,
 }
 
-#[get("/auth/request-challenge")]
-async fn auth_user_request_challenge(
+#[get("/")]
+async fn list_user_files(
     req: HttpRequest,
+    file_server: web::Data<Arc<file_server::FileServer>>,
     pool: web::Data<DbPool>,
-    q: web::Query<AuthRequestChallengeQueryFields>,
+    q: web::Query<ListUserFilesQueryFields>,
 ) -> Result<HttpResponse, Error> {
-    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let result: Result<HttpResponse, Error> = async {
+        let (username, maybe_grant) = {
+            // Free early req.extensions() so that others (like
+            // url_for_static in hateoas_file_delete) can mutably borrow it
+            let exts = req.extensions();
+            (
+                exts.get::<Username>().unwrap().0.clone(),
+                exts.get::<ApiKeyGrant>().cloned(),
+            )
+        };
 
-    if q.username.is_none() {
-        return Err(VaultError::FieldRequired {
-            field: "username".into(),
+        if let Some(grant) = &maybe_grant {
+            grant.check_scope(ApiKeyScope::Read)?;
         }
-        .into());
-    };
 
-    let maybe_user = web::block(move || {
-        db::actions::find_user_by_username(&mut conn, q.username.as_ref().unwrap())
-    })
-    .await?;
+        let limit = q
+            .limit
+            .unwrap_or(MAX_LIST_PAGE_SIZE)
+            .clamp(1, MAX_LIST_PAGE_SIZE);
 
-    if let Ok(Some(user)) = maybe_user {
-        Ok(HttpResponse::Ok().json(
-            serde_json::from_value::<AskForTheChallengeResponse>(json!({
-                "status": 200,
-                "content": {
-                    "challenge": user.challenge,
+        // API keys restricted to a prefix see only the files under it
+        let prefix = maybe_grant
+            .as_ref()
+            .and_then(|grant| grant.prefix.as_deref());
+
+        let (files, next_cursor) = file_server
+            .get_files_list(
+                &username,
+                prefix,
+                q.cursor.as_deref(),
+                limit,
+                q.urls.unwrap_or(true),
+            )
+            .await
+            .map_err(|e| match e {
+                FileServerError::InvalidCursor => VaultError::InvalidField {
+                    field: "cursor".into(),
+                    reason: "is not valid".into(),
                 },
-                "links": {
-                    "answer_auth_challenge": hateoas_auth_user_answer_challenge(&req),
-                    "create_user": hateoas_new_user(&req)
-                }
+                e => VaultError::StorageError(Box::new(e)),
+            })?;
+
+        Ok(HttpResponse::Ok().json(
+            serde_json::from_value::<RetrieveListOfUserFilesResponse>(json!({
+                "content": files.iter().map(|f| {
+                    json!({
+                        "content": f,
+                        "links": hateoas_file_links(&req, f),
+                        "status":200
+                    })
+                }).collect::<Vec<serde_json::value::Value>>(),
+                "links": next_cursor.map(|cursor| json!({
+                    "next": hateoas_list_user_files_page(&req, &cursor, limit),
+                })),
+                "status":200,
             }))
             .unwrap(),
         ))
-    } else {
-        Err(VaultError::UserDoesNotExist.into())
     }
+    .await;
+
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    audit::record(
+        &req,
+        &pool,
+        &username,
+        AuditAction::ListFiles,
+        None,
+        &result,
+    )
+    .await;
+
+    result
 }
 
-async fn auth_user_answer_challenge(
+/// A file of the user, with its download url, if the user
+/// (or the API key) is allowed to read it
+async fn find_user_file(
+    req: &HttpRequest,
+    file_server: &file_server::FileServer,
+    filename: &str,
+) -> Result<RetrieveListOfUserFilesResponseContentItemContent, Error> {
+    let filename = validate_filename(filename)?;
+
+    let username = {
+        let exts = req.extensions();
+
+        if let Some(grant) = exts.get::<ApiKeyGrant>() {
+            grant.check(ApiKeyScope::Read, &filename)?;
+        }
+
+        exts.get::<Username>().unwrap().0.clone()
+    };
+
+    let file = file_server
+        .get_file_url(&username, &filename)
+        .await
+        .map_err(|e| VaultError::StorageError(Box::new(e)))?
+        .ok_or(VaultError::NotFound)?;
+
+    Ok(file)
+}
+
+#[get("/{filename:.+}/url")]
+async fn retrieve_file_url(
     req: HttpRequest,
-    payload: web::Json<AnswerUserChallengeRequest>,
+    file_server: web::Data<Arc<file_server::FileServer>>,
     pool: web::Data<DbPool>,
-    crypto_key: web::Data<std::sync::Arc<ring::hmac::Key>>,
-    tokens_cache: web::Data<Arc<RwLock<TokensCache>>>,
+    filename: web::Path<String>,
 ) -> Result<HttpResponse, Error> {
-    let mut conn = pool.get().expect("couldn't get db connection from pool");
-    let answer = payload.answer.clone();
+    let filename = filename.into_inner();
 
-    let maybe_user =
-        web::block(move || db::actions::find_user_by_username(&mut conn, &payload.username))
-            .await?;
+    let result: Result<HttpResponse, Error> = async {
+        let file = find_user_file(&req, &file_server, &filename).await?;
 
-    if let Ok(Some(user)) = maybe_user {
-        let correct_answer = user.answer;
+        Ok(HttpResponse::Ok().json(
+            serde_json::from_value::<RetrieveFileUrlResponse>(json!({
+                "content": file,
+                "status": 200,
+            }))
+            .unwrap(),
+        ))
+    }
+    .await;
 
-        if answer != correct_answer {
-            return Err(VaultError::ChallengeMismatchError.into());
-        }
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    audit::record(
+        &req,
+        &pool,
+        &username,
+        AuditAction::RetrieveFileUrl,
+        Some(&filename),
+        &result,
+    )
+    .await;
+
+    result
+}
 
-        // Generate a new signed token
-        let token = AuthToken::new(&crypto_key);
-        let token_as_string = token.to_string();
+#[get("/{filename:.+}")]
+async fn retrieve_file(
+    req: HttpRequest,
+    file_server: web::Data<Arc<file_server::FileServer>>,
+    pool: web::Data<DbPool>,
+    filename: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let filename = filename.into_inner();
 
-        // Store the token, alongside the user it represent
-        tokens_cache.write().unwrap().insert(token, user.username);
+    let result: Result<HttpResponse, Error> = async {
+        let file = find_user_file(&req, &file_server, &filename).await?;
 
         Ok(HttpResponse::Ok().json(
-            serde_json::from_value::<AnswerUserChallengeResponse>(json!({
-                "content": {
-                    "token": token_as_string
-                },
-                "links": {
-                    "files_list": hateoas_list_user_files(&req),
-                    "request_upload_url": hateoas_request_upload_url(&req),
-                },
-                "status":200
+            serde_json::from_value::<RetrieveFileResponse>(json!({
+                "content": file,
+                "links": hateoas_file_links(&req, &file),
+                "status": 200,
             }))
             .unwrap(),
         ))
-    } else {
-        Err(VaultError::UserDoesNotExist.into())
     }
-}
+    .await;
+
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    audit::record(
+        &req,
+        &pool,
+        &username,
+        AuditAction::RetrieveFile,
+        Some(&filename),
+        &result,
+    )
+    .await;
 
-#[get("/users/")]
-async fn new_user() -> Result<HttpResponse, Error> {
-    unimplemented!()
+    result
 }
 
-#[post("/files/request-upload-url")]
-async fn request_upload_url(
+/// Tell if a file exists, and its checksum in the ETag header
+#[head("/{filename:.+}")]
+async fn stat_file(
     req: HttpRequest,
-    payload: web::Json<RequestUploadUrlRequestBody>,
-    s3_fs: web::Data<Arc<file_server::FileServer>>,
+    file_server: web::Data<Arc<file_server::FileServer>>,
+    pool: web::Data<DbPool>,
+    filename: web::Path<String>,
 ) -> Result<HttpResponse, Error> {
-    let filename = &payload.filename;
+    let filename = filename.into_inner();
 
-    if filename.is_empty() {
-        return Err(VaultError::FieldRequired {
-            field: "username".into(),
-        }
-        .into());
-    };
+    let result: Result<HttpResponse, Error> = async {
+        let valid_filename = validate_filename(&filename)?;
 
-    let exts = std::rc::Rc::new(req.extensions());
-    let exts = exts.clone();
-    let username = &exts.get::<Username>().unwrap().0;
+        let username = {
+            let exts = req.extensions();
 
-    let (upload_url, retrieve_url, form_data) = s3_fs
-        .get_post_policy_data(username, filename)
-        .await
-        .map_err(|e| VaultError::PolicyDataError(Box::new(e)))?;
+            if let Some(grant) = exts.get::<ApiKeyGrant>() {
+                grant.check(ApiKeyScope::Read, &valid_filename)?;
+            }
 
-    Ok(HttpResponse::Ok().json(
-        serde_json::from_value::<RequestUploadUrlResponse>(json!({
-            "links": {
-                "retrieve_url": {
-                    "href": retrieve_url,
-                    "rel": "file",
-                },
-                "upload_url": {
-                    "form_data": form_data,
-                    "href": upload_url,
-                    "rel": "file",
-                }
-            },
-            "status":200
-        }))
-        .unwrap(),
-    ))
+            exts.get::<Username>().unwrap().0.clone()
+        };
+
+        let file = file_server
+            .stat_file(&username, &valid_filename)
+            .await
+            .map_err(|e| VaultError::StorageError(Box::new(e)))?
+            .ok_or(VaultError::NotFound)?;
+
+        Ok(HttpResponse::Ok()
+            .insert_header((http::header::ETAG, file.checksum))
+            .finish())
+    }
+    .await;
+
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    audit::record(
+        &req,
+        &pool,
+        &username,
+        AuditAction::StatFile,
+        Some(&filename),
+        &result,
+    )
+    .await;
+
+    result
 }
 
-#[get("/files/")]
-async fn list_user_files(
-    req: HttpRequest,
-    s3_fs: web::Data<Arc<file_server::FileServer>>,
+async fn remove_user_file(
+    req: &HttpRequest,
+    file_server: &file_server::FileServer,
+    filename: &str,
 ) -> Result<HttpResponse, Error> {
+    let filename = validate_filename(filename)?;
+
     let username = {
-        // Free early req.extensions() so that others (like
-        // url_for_static in hateoas_file_delete) can mutably borrow it
         let exts = req.extensions();
+
+        if let Some(grant) = exts.get::<ApiKeyGrant>() {
+            grant.check(ApiKeyScope::Delete, &filename)?;
+        }
+
         exts.get::<Username>().unwrap().0.clone()
     };
 
-    let files = s3_fs
-        .get_files_list(&username)
+    file_server
+        .remove_file(&username, &filename)
         .await
-        .map_err(|e| VaultError::S3Error(Box::new(e)))?;
+        .map_err(|e| VaultError::StorageError(Box::new(e)))?;
 
     Ok(HttpResponse::Ok().json(
-        serde_json::from_value::<RetrieveListOfUserFilesResponse>(json!({
-            "content": files.iter().map(|f| {
-                json!({
-                    "content": f,
-                    "links": {
-                        "delete": hateoas_file_delete(&req, &f.filename),
-                        "read": hateoas_file_read(f),
-                    },
-                    "status":200
-                })
-            }).collect::<Vec<serde_json::value::Value>>(),
-            "status":200,
+        serde_json::from_value::<RemoveFileResponse>(json!({
+            "status": 200,
         }))
         .unwrap(),
     ))
 }
 
+#[delete("/{filename:.+}")]
+async fn delete_file(
+    req: HttpRequest,
+    file_server: web::Data<Arc<file_server::FileServer>>,
+    pool: web::Data<DbPool>,
+    filename: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let filename = filename.into_inner();
+    let result = remove_user_file(&req, &file_server, &filename).await;
+
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    audit::record(
+        &req,
+        &pool,
+        &username,
+        AuditAction::RemoveFile,
+        Some(&filename),
+        &result,
+    )
+    .await;
+
+    result
+}
+
 #[derive(Deserialize)]
 pub struct RemoveFileQueryFields {
-    // XXX this is optional, but it shouldn't be. Maybe make it part of the URI?
     filename: Option<String>,
 }
 
-#[delete("/files/")]
+/// Deprecated, use `DELETE /files/{filename}`
+#[delete("/")]
 async fn remove_file(
     req: HttpRequest,
-    s3_fs: web::Data<Arc<file_server::FileServer>>,
+    file_server: web::Data<Arc<file_server::FileServer>>,
+    pool: web::Data<DbPool>,
     q: web::Query<RemoveFileQueryFields>,
 ) -> Result<HttpResponse, Error> {
-    if q.filename.is_none() {
-        return Err(VaultError::FieldRequired {
-            field: "username".into(),
+    let result = match &q.filename {
+        Some(filename) => remove_user_file(&req, &file_server, filename)
+            .await
+            .map(deprecated),
+        None => Err(VaultError::FieldRequired {
+            field: "filename".into(),
         }
-        .into());
+        .into()),
+    };
+
+    let username = req.extensions().get::<Username>().unwrap().0.clone();
+    audit::record(
+        &req,
+        &pool,
+        &username,
+        AuditAction::RemoveFile,
+        q.filename.as_deref(),
+        &result,
+    )
+    .await;
+
+    result
+}
+
+/// Flag the response of a deprecated route
+fn deprecated(mut resp: HttpResponse) -> HttpResponse {
+    resp.headers_mut().insert(
+        http::header::HeaderName::from_static("deprecation"),
+        http::header::HeaderValue::from_static("true"),
+    );
+    resp
+}
+
+/// Room for the form fields and the multipart boundaries, besides the file
+const STORAGE_UPLOAD_FORM_OVERHEAD: usize = 16 * 1024;
+
+/// Receive a file uploaded to a signed url of the local storage (filesystem or memory).
+/// The form data is the one returned by request-upload-url, the same
+/// way the files are posted to a S3 bucket.
+#[post("/storage/upload")]
+async fn storage_upload(
+    req: HttpRequest,
+    mut payload: web::Payload,
+    file_server: web::Data<Arc<file_server::FileServer>>,
+) -> Result<HttpResponse, Error> {
+    let storage = file_server.local_storage().ok_or(VaultError::NotFound)?;
+
+    let boundary = multipart::boundary(req.headers()).ok_or_else(|| {
+        VaultError::GenericBadRequest("Expected a multipart/form-data body".into())
+    })?;
+
+    let mut body = web::BytesMut::new();
+
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk?;
+
+        if body.len() + chunk.len() > MAX_FILE_SIZE as usize + STORAGE_UPLOAD_FORM_OVERHEAD {
+            return Err(actix_web::error::PayloadError::Overflow.into());
+        }
+
+        body.extend_from_slice(&chunk);
+    }
+
+    let mut fields = multipart::parse(&body, &boundary).ok_or_else(|| {
+        VaultError::GenericBadRequest("Malformed multipart/form-data body".into())
+    })?;
+
+    let mut field = |name: &str| {
+        fields
+            .remove(name)
+            .ok_or_else(|| VaultError::FieldRequired { field: name.into() })
     };
 
-    let exts = std::rc::Rc::new(req.extensions());
-    let username = &exts.get::<Username>().unwrap().0;
+    let content = field("file")?;
+    let (key, expires, signature) = (field("key")?, field("expires")?, field("signature")?);
+
+    let key = String::from_utf8(key).map_err(|_| VaultError::InvalidSignedUrl)?;
+    let expires = std::str::from_utf8(&expires)
+        .ok()
+        .and_then(|expires| expires.parse::<i64>().ok())
+        .ok_or(VaultError::InvalidSignedUrl)?;
+    let signature = String::from_utf8(signature).map_err(|_| VaultError::InvalidSignedUrl)?;
+
+    if !storage
+        .urls()
+        .verify(SignedUrlAction::Upload, &key, expires, &signature)
+    {
+        return Err(VaultError::InvalidSignedUrl.into());
+    }
+
+    if !(MIN_FILE_SIZE..=MAX_FILE_SIZE).contains(&(content.len() as u64)) {
+        return Err(VaultError::InvalidField {
+            field: "file".into(),
+            reason: format!("it must be between {MIN_FILE_SIZE} and {MAX_FILE_SIZE} bytes"),
+        }
+        .into());
+    }
+
+    let checksum = storage.store(&key, content).await.map_err(|e| match e {
+        FileServerError::FilenameConflict => VaultError::FilenameConflict,
+        e => VaultError::StorageError(Box::new(e)),
+    })?;
+
+    Ok(HttpResponse::NoContent()
+        .insert_header((http::header::ETAG, checksum))
+        .finish())
+}
+
+#[derive(Deserialize)]
+pub struct StorageDownloadQueryFields {
+    key: String,
+    expires: i64,
+    signature: String,
+}
+
+/// Serve a file from a signed url of the local storage
+#[get("/storage/download")]
+async fn storage_download(
+    q: web::Query<StorageDownloadQueryFields>,
+    file_server: web::Data<Arc<file_server::FileServer>>,
+) -> Result<HttpResponse, Error> {
+    let storage = file_server.local_storage().ok_or(VaultError::NotFound)?;
+
+    if !storage
+        .urls()
+        .verify(SignedUrlAction::Download, &q.key, q.expires, &q.signature)
+    {
+        return Err(VaultError::InvalidSignedUrl.into());
+    }
 
-    s3_fs
-        .remove_file(username, q.filename.as_ref().unwrap())
+    let (content, checksum) = storage
+        .read(&q.key)
         .await
-        .map_err(|e| VaultError::S3Error(Box::new(e)))?;
+        .map_err(|e| VaultError::StorageError(Box::new(e)))?
+        .ok_or(VaultError::NotFound)?;
 
-    Ok(HttpResponse::Ok().json(
-        serde_json::from_value::<RemoveFileResponse>(json!({
-            "status": 200,
-        }))
-        .unwrap(),
-    ))
+    Ok(HttpResponse::Ok()
+        .content_type("application/octet-stream")
+        .insert_header((http::header::ETAG, checksum))
+        .body(content))
 }
 
 fn hateoas_new_user(req: &HttpRequest) -> serde_json::Value {
@@ -296,6 +2132,78 @@ fn hateoas_auth_user_request_challenge(req: &HttpRequest) -> serde_json::Value {
     })
 }
 
+fn hateoas_refresh_token(req: &HttpRequest) -> serde_json::Value {
+    let url = req.url_for_static("refresh_auth_token").unwrap();
+    json!({
+        "href": url.as_str(),
+        "rel": "auth"
+    })
+}
+
+fn hateoas_logout(req: &HttpRequest) -> serde_json::Value {
+    let url = req.url_for_static("logout").unwrap();
+    json!({
+        "href": url.as_str(),
+        "rel": "auth"
+    })
+}
+
+fn hateoas_logout_everywhere(req: &HttpRequest) -> serde_json::Value {
+    let url = req.url_for_static("logout_everywhere").unwrap();
+    json!({
+        "href": url.as_str(),
+        "rel": "auth"
+    })
+}
+
+fn hateoas_change_challenge(req: &HttpRequest) -> serde_json::Value {
+    let url = req.url_for_static("change_challenge").unwrap();
+    json!({
+        "href": url.as_str(),
+        "rel": "auth"
+    })
+}
+
+fn hateoas_session_delete(req: &HttpRequest, session_id: &str) -> serde_json::Value {
+    let url = req.url_for("revoke_user_session", [session_id]).unwrap();
+    json!({
+        "href": url.as_str(),
+        "rel": "auth"
+    })
+}
+
+fn hateoas_user_key_delete(req: &HttpRequest, key_id: &str) -> serde_json::Value {
+    let url = req.url_for("remove_user_key", [key_id]).unwrap();
+    json!({
+        "href": url.as_str(),
+        "rel": "user"
+    })
+}
+
+fn hateoas_api_key_delete(req: &HttpRequest, api_key_id: &str) -> serde_json::Value {
+    let url = req.url_for("remove_api_key", [api_key_id]).unwrap();
+    json!({
+        "href": url.as_str(),
+        "rel": "user"
+    })
+}
+
+fn hateoas_admin_user(req: &HttpRequest, name: &str, username: &str) -> serde_json::Value {
+    let url = req.url_for(name, [username]).unwrap();
+    json!({
+        "href": url.as_str(),
+        "rel": "admin"
+    })
+}
+
+fn hateoas_totp_confirm(req: &HttpRequest) -> serde_json::Value {
+    let url = req.url_for_static("confirm_totp").unwrap();
+    json!({
+        "href": url.as_str(),
+        "rel": "user"
+    })
+}
+
 fn hateoas_list_user_files(req: &HttpRequest) -> serde_json::Value {
     let url = req.url_for_static("list_user_files").unwrap();
     json!({
@@ -304,6 +2212,21 @@ fn hateoas_list_user_files(req: &HttpRequest) -> serde_json::Value {
     })
 }
 
+fn hateoas_list_user_files_page(
+    req: &HttpRequest,
+    cursor: &str,
+    limit: usize,
+) -> serde_json::Value {
+    let url = req.url_for_static("list_user_files").unwrap();
+    let query =
+        serde_urlencoded::to_string([("limit", limit.to_string().as_str()), ("cursor", cursor)])
+            .expect("the query is always serializable");
+    json!({
+        "href": format!("{}?{}", url.as_str(), query),
+        "rel": "file"
+    })
+}
+
 fn hateoas_request_upload_url(req: &HttpRequest) -> serde_json::Value {
     let url = req.url_for_static("request_upload_url").unwrap();
     json!({
@@ -312,17 +2235,51 @@ fn hateoas_request_upload_url(req: &HttpRequest) -> serde_json::Value {
     })
 }
 
+/// Missing when the file was listed without its url
 fn hateoas_file_read(f: &RetrieveListOfUserFilesResponseContentItemContent) -> serde_json::Value {
+    match &f.url {
+        Some(url) => json!({
+            "href": url,
+            "rel": "file"
+        }),
+        None => serde_json::Value::Null,
+    }
+}
+
+fn hateoas_file_url(req: &HttpRequest, filename: &str) -> serde_json::Value {
     json!({
-        "href": &f.url,
+        "href": file_url(req, filename, &["url"]),
         "rel": "file"
     })
 }
 
+fn hateoas_file_links(
+    req: &HttpRequest,
+    f: &RetrieveListOfUserFilesResponseContentItemContent,
+) -> serde_json::Value {
+    json!({
+        "delete": hateoas_file_delete(req, &f.filename),
+        "read": hateoas_file_read(f),
+        "url": hateoas_file_url(req, &f.filename),
+    })
+}
+
+/// The url of a file, `/files/{filename}`, followed by `segments`.
+/// The filename is percent-encoded as a single path segment,
+/// whatever the characters it contains.
+fn file_url(req: &HttpRequest, filename: &str, segments: &[&str]) -> String {
+    let mut url = req.url_for_static("list_user_files").unwrap();
+    url.path_segments_mut()
+        .expect("the files url is an http url")
+        .pop_if_empty()
+        .push(filename)
+        .extend(segments);
+    url.into()
+}
+
 fn hateoas_file_delete(req: &HttpRequest, filename: &str) -> serde_json::Value {
-    let url = req.url_for_static("remove_file").unwrap();
     json!({
-        "href": format!("{}?filename={}", url.as_str(), filename),
+        "href": file_url(req, filename, &[]),
         "rel": "file"
     })
 }
@@ -419,6 +2376,10 @@ pub fn render_40x<B>(mut res: ServiceResponse<B>) -> actix_web::Result<ErrorHand
 pub fn config_handlers(cfg: &mut web::ServiceConfig) {
     // Setup authentication middleware
     let auth = HttpAuthentication::bearer(auth_validator);
+    // The files can be accessed with an API key too
+    let files_auth = HttpAuthentication::bearer(files_auth_validator);
+    // Only admins can manage the other users
+    let admin_auth = HttpAuthentication::bearer(admin_auth_validator);
 
     cfg.service(handlers::favicon)
         .service(handlers::index)
@@ -429,13 +2390,58 @@ pub fn config_handlers(cfg: &mut web::ServiceConfig) {
                 .name("auth_user_answer_challenge")
                 .app_data(web::JsonConfig::default().limit(512)),
         )
+        .service(handlers::new_user)
+        .service(handlers::storage_upload)
+        .service(handlers::storage_download)
         .service(
-            web::scope("")
-                .wrap(auth)
-                .service(handlers::new_user)
+            web::scope("/files")
+                .wrap(files_auth)
                 .service(handlers::request_upload_url)
                 .service(handlers::list_user_files)
+                .service(handlers::retrieve_file_url)
+                .service(handlers::retrieve_file)
+                .service(handlers::stat_file)
                 .service(handlers::remove_file)
+                .service(handlers::delete_file)
+                // the paths above, with a method that none of them accepts
+                .service(
+                    web::resource(["/", "/request-upload-url", "/{filename:.+}"])
+                        .route(web::route().to(handlers::method_not_allowed)),
+                )
+                .default_service(web::route().to(handlers::p404)),
+        )
+        .service(
+            web::scope("/admin")
+                .wrap(admin_auth)
+                .service(handlers::admin_list_users)
+                .service(handlers::admin_create_user)
+                .service(handlers::admin_delete_user)
+                .service(handlers::admin_disable_user)
+                .service(handlers::admin_enable_user)
+                .service(handlers::admin_logout_user)
+                .service(handlers::admin_export_audit_log)
+                .default_service(web::route().to(handlers::p404)),
+        )
+        .service(
+            web::scope("")
+                .wrap(auth)
+                .service(handlers::refresh_auth_token)
+                .service(handlers::logout)
+                .service(handlers::logout_everywhere)
+                .service(handlers::change_challenge)
+                .service(handlers::list_user_sessions)
+                .service(handlers::revoke_user_session)
+                .service(handlers::delete_user)
+                .service(handlers::list_user_keys)
+                .service(handlers::add_user_key)
+                .service(handlers::remove_user_key)
+                .service(handlers::list_api_keys)
+                .service(handlers::create_api_key)
+                .service(handlers::remove_api_key)
+                .service(handlers::enable_totp)
+                .service(handlers::confirm_totp)
+                .service(handlers::disable_totp)
+                .service(handlers::list_activity)
                 .service(
                     web::resource("/{whatever:.*}")
                         .route(web::get().to(handlers::p404))
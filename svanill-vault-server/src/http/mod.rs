@@ -1,2 +1,6 @@
+mod audit;
 mod auth_middleware;
 pub mod handlers;
+mod multipart;
+pub mod request_id;
+pub(crate) mod tokens;
@@ -0,0 +1,147 @@
+use actix_web::http::header::{HeaderMap, CONTENT_TYPE};
+use std::collections::HashMap;
+
+/// The boundary of a multipart/form-data request, None if the
+/// request has a different content type
+pub fn boundary(headers: &HeaderMap) -> Option<String> {
+    let content_type = headers.get(CONTENT_TYPE)?.to_str().ok()?;
+    let (mime, params) = content_type.split_once(';')?;
+
+    if !mime.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+
+    params
+        .split(';')
+        .find_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            name.trim()
+                .eq_ignore_ascii_case("boundary")
+                .then(|| value.trim().trim_matches('"').to_owned())
+        })
+        .filter(|boundary| !boundary.is_empty())
+}
+
+/// The fields of a multipart/form-data body (RFC 7578), by name.
+/// None if the body is malformed.
+///
+/// The whole body must be in memory, which is fine for
+/// the small files that the vault accepts.
+pub fn parse(body: &[u8], boundary: &str) -> Option<HashMap<String, Vec<u8>>> {
+    let delimiter = format!("--{boundary}");
+    let content_end_marker = format!("\r\n{delimiter}");
+
+    let mut fields = HashMap::new();
+    let mut pos = find(body, delimiter.as_bytes(), 0)? + delimiter.len();
+
+    loop {
+        let rest = &body[pos..];
+
+        if rest.starts_with(b"--") {
+            return Some(fields);
+        } else if !rest.starts_with(b"\r\n") {
+            return None;
+        }
+
+        let headers_start = pos + 2;
+        let headers_end = find(body, b"\r\n\r\n", headers_start)?;
+        let name = field_name(&body[headers_start..headers_end])?;
+
+        let content_start = headers_end + 4;
+        let content_end = find(body, content_end_marker.as_bytes(), content_start)?;
+
+        fields.insert(name, body[content_start..content_end].to_vec());
+        pos = content_end + content_end_marker.len();
+    }
+}
+
+/// The name of the field, from the part's Content-Disposition header
+fn field_name(headers: &[u8]) -> Option<String> {
+    std::str::from_utf8(headers)
+        .ok()?
+        .split("\r\n")
+        .find_map(|line| {
+            let (header, value) = line.split_once(':')?;
+
+            if !header.trim().eq_ignore_ascii_case("content-disposition") {
+                return None;
+            }
+
+            value.split(';').find_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                (name.trim() == "name").then(|| value.trim().trim_matches('"').to_owned())
+            })
+        })
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|idx| idx + from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::HeaderValue;
+
+    #[test]
+    fn read_the_boundary() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(None, boundary(&headers));
+
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("multipart/form-data; boundary=\"abc\""),
+        );
+        assert_eq!(Some("abc".to_owned()), boundary(&headers));
+
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded; boundary=abc"),
+        );
+        assert_eq!(None, boundary(&headers));
+    }
+
+    #[test]
+    fn parse_the_fields() {
+        let body = b"--xyz\r\n\
+            Content-Disposition: form-data; name=\"key\"\r\n\
+            \r\n\
+            users/foo/bar\r\n\
+            --xyz\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"bar\"\r\n\
+            Content-Type: text/plain\r\n\
+            \r\n\
+            line 1\r\nline 2\r\n\
+            --xyz--\r\n";
+
+        let fields = parse(body, "xyz").unwrap();
+
+        assert_eq!(2, fields.len());
+        assert_eq!(b"users/foo/bar".to_vec(), fields["key"]);
+        assert_eq!(b"line 1\r\nline 2".to_vec(), fields["file"]);
+    }
+
+    #[test]
+    fn reject_malformed_bodies() {
+        assert_eq!(None, parse(b"", "xyz"));
+        assert_eq!(None, parse(b"--xyz\r\nno headers end", "xyz"));
+        assert_eq!(
+            None,
+            parse(
+                b"--xyz\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nnever closed",
+                "xyz"
+            )
+        );
+        assert_eq!(
+            None,
+            parse(
+                b"--xyz\r\nContent-Type: text/plain\r\n\r\nno name\r\n--xyz--",
+                "xyz"
+            )
+        );
+    }
+}
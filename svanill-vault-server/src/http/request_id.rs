@@ -0,0 +1,75 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpMessage};
+use ring::rand::{SecureRandom, SystemRandom};
+
+/// Header carrying the id of the request, both in the request and in the response
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest request id accepted from the client
+const REQUEST_ID_MAX_LENGTH: usize = 64;
+
+/// Id of the request, to match the audit entries with the logs of the clients and the proxies
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= REQUEST_ID_MAX_LENGTH
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_' || b == b'.')
+}
+
+fn generate_request_id() -> String {
+    let mut id = [0u8; 16];
+    SystemRandom::new().fill(&mut id).unwrap();
+    hex::encode(id)
+}
+
+/// Tag every request with an id, reusing the one sent by the client (e.g. set
+/// by a proxy) when valid, and send it back in the response
+pub async fn request_id_middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(String::from)
+        .unwrap_or_else(generate_request_id);
+
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let mut res = next.call(req).await?;
+
+    res.headers_mut().insert(
+        HeaderName::from_static(REQUEST_ID_HEADER),
+        HeaderValue::from_str(&id).expect("the request id is a valid header value"),
+    );
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_ids_are_validated() {
+        assert!(is_valid_request_id("7f3c2a9e-1b4d-4c8e-9f2a-3d5e6b7c8a9f"));
+        assert!(is_valid_request_id("req_1.2"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("with space"));
+        assert!(!is_valid_request_id(&"a".repeat(REQUEST_ID_MAX_LENGTH + 1)));
+    }
+
+    #[test]
+    fn generated_request_ids_are_valid() {
+        assert!(is_valid_request_id(&generate_request_id()));
+    }
+}
@@ -0,0 +1,484 @@
+use crate::auth::api_key::{ApiKey, ApiKeyGrant};
+use crate::auth::auth_token::AuthToken;
+use crate::auth::signed_token::{self, Claims, DEFAULT_SCOPE};
+use crate::auth::token_keys::TokenKeys;
+use crate::auth::token_store::{ClientInfo, Session, SharedTokenStore};
+use crate::auth::{TokenMode, Username};
+use crate::db::models::{NewRevokedToken, NewUserTokenRevocation};
+use crate::db::{self, DbPool};
+use crate::utils::{unix_timestamp, unix_timestamp_ms};
+use actix_web::{http::header, web, Error, HttpRequest};
+use std::net::{IpAddr, SocketAddr};
+
+/// Longest User-Agent we bother to keep
+const USER_AGENT_MAX_LENGTH: usize = 255;
+
+/// Whether the server is reached through a reverse proxy that tells the address
+/// of the client, in the `Forwarded` or `X-Forwarded-For` header
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrustedProxy(pub bool);
+
+/// The ip of the client. Behind a trusted proxy it's the one the proxy forwarded,
+/// the address of the peer otherwise (or when the forwarded one is not valid)
+fn client_ip(req: &HttpRequest) -> Option<IpAddr> {
+    let peer_ip = req.peer_addr().map(|addr| addr.ip());
+
+    if !req
+        .app_data::<web::Data<TrustedProxy>>()
+        .is_some_and(|trusted_proxy| trusted_proxy.0)
+    {
+        return peer_ip;
+    }
+
+    let forwarded_ip = req.connection_info().realip_remote_addr().and_then(|addr| {
+        // the address can come with a port, and IPv6 ones in brackets
+        addr.parse::<SocketAddr>()
+            .map(|addr| addr.ip())
+            .or_else(|_| addr.trim_matches(['[', ']']).parse())
+            .ok()
+    });
+
+    forwarded_ip.or(peer_ip)
+}
+
+/// What we know about the client making the request
+pub fn client_info(req: &HttpRequest) -> ClientInfo {
+    ClientInfo {
+        ip: client_ip(req).map(|ip| ip.to_string()),
+        user_agent: req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.chars().take(USER_AGENT_MAX_LENGTH).collect()),
+    }
+}
+
+/// Issue a new token for the user. Opaque tokens are kept in the token store,
+/// signed tokens carry everything needed to verify them.
+pub async fn issue_token(
+    token_mode: &TokenMode,
+    token_keys: &TokenKeys,
+    token_store: &web::Data<SharedTokenStore>,
+    username: String,
+    client: ClientInfo,
+) -> Result<AuthToken, Error> {
+    issue_token_at(
+        token_mode,
+        token_keys,
+        token_store,
+        username,
+        client,
+        unix_timestamp_ms(),
+    )
+    .await
+}
+
+/// Issue a token, as if it was `now` (in milliseconds since the unix epoch)
+async fn issue_token_at(
+    token_mode: &TokenMode,
+    token_keys: &TokenKeys,
+    token_store: &web::Data<SharedTokenStore>,
+    username: String,
+    client: ClientInfo,
+    now: i64,
+) -> Result<AuthToken, Error> {
+    match token_mode {
+        TokenMode::Opaque => {
+            let token = AuthToken::new(token_keys);
+            let token_to_store = token.clone();
+            let token_store = token_store.clone();
+
+            web::block(move || token_store.insert(token_to_store, username, client)).await??;
+
+            Ok(token)
+        }
+        TokenMode::Signed {
+            lifetime,
+            max_lifetime,
+        } => {
+            let claims = Claims::new(&username, now, *lifetime, *max_lifetime);
+            Ok(signed_token::sign(&claims, token_keys))
+        }
+    }
+}
+
+/// Exchange a valid token for a new one, belonging to the same session.
+/// The old token is not valid anymore. Return None if the token could
+/// not be refreshed (e.g. it expired in the meantime).
+pub async fn refresh_token(
+    token_mode: &TokenMode,
+    token_keys: &TokenKeys,
+    token_store: &web::Data<SharedTokenStore>,
+    pool: &web::Data<DbPool>,
+    token: AuthToken,
+) -> Result<Option<AuthToken>, Error> {
+    match token_mode {
+        TokenMode::Opaque => {
+            let new_token = AuthToken::new(token_keys);
+            let new_token_to_store = new_token.clone();
+            let token_store = token_store.clone();
+
+            let maybe_username =
+                web::block(move || token_store.refresh(&token, new_token_to_store)).await??;
+
+            Ok(maybe_username.map(|_| new_token))
+        }
+        TokenMode::Signed {
+            lifetime,
+            max_lifetime,
+        } => {
+            let now = unix_timestamp_ms();
+
+            let claims = match signed_token::verify(&token, token_keys, now) {
+                Some(claims) => claims,
+                None => return Ok(None),
+            };
+
+            let new_claims = match claims.refreshed(now, *lifetime, *max_lifetime) {
+                Some(new_claims) => new_claims,
+                None => return Ok(None),
+            };
+
+            // The old token must not be used anymore. Revoking it is also what tells
+            // that it wasn't refreshed already, e.g. by a concurrent request
+            let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+            let revoked = web::block(move || {
+                db::actions::revoke_signed_token(
+                    &mut conn,
+                    &NewRevokedToken {
+                        jti: &claims.jti,
+                        expires_at: claims.exp,
+                    },
+                )
+            })
+            .await??;
+
+            Ok(revoked.then(|| signed_token::sign(&new_claims, token_keys)))
+        }
+    }
+}
+
+/// Return the user the token belongs to, if the token is still valid
+pub async fn validate_token(
+    token_mode: &TokenMode,
+    token_keys: &TokenKeys,
+    token_store: &web::Data<SharedTokenStore>,
+    pool: &web::Data<DbPool>,
+    token: AuthToken,
+) -> Result<Option<Username>, Error> {
+    match token_mode {
+        TokenMode::Opaque => {
+            // Forged or garbage tokens are rejected without touching the token store
+            if !token.has_valid_signature(token_keys) {
+                return Ok(None);
+            }
+
+            let token_store = token_store.clone();
+
+            let maybe_username = web::block(move || token_store.get_username(&token)).await??;
+
+            Ok(maybe_username.map(Username))
+        }
+        TokenMode::Signed { .. } => {
+            let claims = match signed_token::verify(&token, token_keys, unix_timestamp_ms()) {
+                Some(claims) if claims.scope == DEFAULT_SCOPE => claims,
+                _ => return Ok(None),
+            };
+
+            let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+            let revoked = web::block(move || {
+                db::actions::is_signed_token_revoked(
+                    &mut conn,
+                    &claims.jti,
+                    &claims.sub,
+                    claims.iat,
+                )
+                .map(|revoked| (revoked, claims.sub))
+            })
+            .await??;
+
+            Ok(match revoked {
+                (false, username) => Some(Username(username)),
+                (true, _) => None,
+            })
+        }
+    }
+}
+
+/// Return the owner of the API key and what the key allows, unless the key is not valid
+pub async fn validate_api_key(
+    pool: &web::Data<DbPool>,
+    key: &str,
+) -> Result<Option<(Username, ApiKeyGrant)>, Error> {
+    let key = match ApiKey::parse(key) {
+        Some(key) => key,
+        None => return Ok(None),
+    };
+
+    let mut conn = pool.get().expect("couldn't get db connection from pool");
+    let id = key.id.clone();
+
+    let maybe_stored = web::block(move || {
+        let stored = db::actions::find_api_key(&mut conn, &id, unix_timestamp())?;
+
+        match stored {
+            Some(stored) => db::actions::find_user_by_username(&mut conn, &stored.username)
+                .map(|user| user.map(|user| (stored, user))),
+            None => Ok(None),
+        }
+    })
+    .await??;
+
+    let stored = match maybe_stored {
+        Some((stored, user)) if key.matches(&stored.secret_hash) => {
+            // The keys of a disabled user stop working too
+            user.ensure_enabled()?;
+            stored
+        }
+        _ => return Ok(None),
+    };
+
+    match stored.scope.parse() {
+        Ok(scope) => Ok(Some((
+            Username(stored.username),
+            ApiKeyGrant {
+                scope,
+                prefix: stored.prefix,
+            },
+        ))),
+        Err(e) => {
+            log::error!("Cannot use the API key {}: {}", stored.id, e);
+            Ok(None)
+        }
+    }
+}
+
+/// List the user's sessions, flagging the one the token belongs to.
+/// Signed tokens are not tracked, so only the current session is known.
+pub async fn list_sessions(
+    token_mode: &TokenMode,
+    token_keys: &TokenKeys,
+    token_store: &web::Data<SharedTokenStore>,
+    token: &AuthToken,
+    username: String,
+    client: ClientInfo,
+) -> Result<Vec<(Session, bool)>, Error> {
+    match token_mode {
+        TokenMode::Opaque => {
+            let token_store = token_store.clone();
+            let current_id = token.session_id();
+
+            let sessions = web::block(move || token_store.list_user_sessions(&username)).await??;
+
+            Ok(sessions
+                .into_iter()
+                .map(|session| {
+                    let is_current = session.id == current_id;
+                    (session, is_current)
+                })
+                .collect())
+        }
+        TokenMode::Signed { .. } => {
+            let claims = match signed_token::verify(token, token_keys, unix_timestamp_ms()) {
+                Some(claims) => claims,
+                None => return Ok(vec![]),
+            };
+
+            let session = Session {
+                id: claims.jti,
+                created_at: claims.auth_time / 1000,
+                last_seen_at: unix_timestamp(),
+                client,
+            };
+
+            Ok(vec![(session, true)])
+        }
+    }
+}
+
+/// Invalidate the token of one of the user's sessions.
+/// Return false if the user has no such session.
+pub async fn revoke_session(
+    token_mode: &TokenMode,
+    token_keys: &TokenKeys,
+    token_store: &web::Data<SharedTokenStore>,
+    pool: &web::Data<DbPool>,
+    token: AuthToken,
+    username: String,
+    session_id: String,
+) -> Result<bool, Error> {
+    match token_mode {
+        TokenMode::Opaque => {
+            let token_store = token_store.clone();
+
+            let removed =
+                web::block(move || token_store.remove_user_session(&username, &session_id))
+                    .await??;
+
+            Ok(removed)
+        }
+        TokenMode::Signed { .. } => {
+            // Only the current session is known
+            match signed_token::verify(&token, token_keys, unix_timestamp_ms()) {
+                Some(claims) if claims.jti == session_id => {
+                    revoke_token(token_mode, token_keys, token_store, pool, token).await?;
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        }
+    }
+}
+
+/// Invalidate the token
+pub async fn revoke_token(
+    token_mode: &TokenMode,
+    token_keys: &TokenKeys,
+    token_store: &web::Data<SharedTokenStore>,
+    pool: &web::Data<DbPool>,
+    token: AuthToken,
+) -> Result<(), Error> {
+    match token_mode {
+        TokenMode::Opaque => {
+            let token_store = token_store.clone();
+
+            web::block(move || token_store.remove(&token)).await??;
+        }
+        TokenMode::Signed { .. } => {
+            // An invalid token has nothing to revoke
+            let claims = match signed_token::verify(&token, token_keys, unix_timestamp_ms()) {
+                Some(claims) => claims,
+                None => return Ok(()),
+            };
+
+            let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+            web::block(move || {
+                db::actions::revoke_signed_token(
+                    &mut conn,
+                    &NewRevokedToken {
+                        jti: &claims.jti,
+                        expires_at: claims.exp,
+                    },
+                )
+            })
+            .await??;
+        }
+    }
+
+    Ok(())
+}
+
+/// Invalidate every token issued to the user
+pub async fn revoke_user_tokens(
+    token_mode: &TokenMode,
+    token_store: &web::Data<SharedTokenStore>,
+    pool: &web::Data<DbPool>,
+    username: String,
+) -> Result<(), Error> {
+    revoke_user_tokens_at(token_mode, token_store, pool, username, unix_timestamp_ms()).await
+}
+
+/// Invalidate every token issued to the user before `now`
+/// (in milliseconds since the unix epoch)
+async fn revoke_user_tokens_at(
+    token_mode: &TokenMode,
+    token_store: &web::Data<SharedTokenStore>,
+    pool: &web::Data<DbPool>,
+    username: String,
+    now: i64,
+) -> Result<(), Error> {
+    match token_mode {
+        TokenMode::Opaque => {
+            let token_store = token_store.clone();
+
+            web::block(move || token_store.remove_user_tokens(&username)).await??;
+        }
+        TokenMode::Signed { .. } => {
+            let mut conn = pool.get().expect("couldn't get db connection from pool");
+
+            web::block(move || {
+                db::actions::revoke_user_signed_tokens(
+                    &mut conn,
+                    &NewUserTokenRevocation {
+                        username: &username,
+                        revoked_at: now,
+                    },
+                )
+            })
+            .await??;
+        }
+    }
+
+    Ok(())
+}
+
+/// Invalidate every token issued to the user, then issue a new one for
+/// the current client, so that only its session stays open
+pub async fn revoke_other_sessions(
+    token_mode: &TokenMode,
+    token_keys: &TokenKeys,
+    token_store: &web::Data<SharedTokenStore>,
+    pool: &web::Data<DbPool>,
+    username: String,
+    client: ClientInfo,
+) -> Result<AuthToken, Error> {
+    // The revocation covers the tokens issued strictly before it,
+    // so the new token issued at the same time is left out
+    let now = unix_timestamp_ms();
+
+    revoke_user_tokens_at(token_mode, token_store, pool, username.clone(), now).await?;
+    issue_token_at(token_mode, token_keys, token_store, username, client, now).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::tokens_cache::TokensCache;
+    use crate::db::DB_MIGRATIONS;
+    use diesel::r2d2::{ConnectionManager, Pool};
+    use diesel_migrations::MigrationHarness;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[actix_rt::test]
+    async fn refresh_a_signed_token_only_once() {
+        // Every connection to ":memory:" opens a different database
+        let pool: DbPool = Pool::builder()
+            .max_size(1)
+            .build(ConnectionManager::new(":memory:"))
+            .unwrap();
+        pool.get()
+            .unwrap()
+            .run_pending_migrations(DB_MIGRATIONS)
+            .unwrap();
+
+        let token_mode = TokenMode::Signed {
+            lifetime: Duration::from_secs(60),
+            max_lifetime: None,
+        };
+        let token_keys = TokenKeys::generate().unwrap();
+        let token_store: SharedTokenStore = Arc::new(TokensCache::new(10, Duration::from_secs(60)));
+        let token_store = web::Data::new(token_store);
+        let pool = web::Data::new(pool);
+
+        let token = issue_token(
+            &token_mode,
+            &token_keys,
+            &token_store,
+            "foo".to_owned(),
+            ClientInfo::default(),
+        )
+        .await
+        .unwrap();
+
+        // e.g. two concurrent requests, both validated before either one refreshed the token
+        let first = refresh_token(&token_mode, &token_keys, &token_store, &pool, token.clone());
+        let second = refresh_token(&token_mode, &token_keys, &token_store, &pool, token);
+
+        assert!(first.await.unwrap().is_some());
+        assert!(second.await.unwrap().is_none());
+    }
+}
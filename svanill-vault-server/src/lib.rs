@@ -9,7 +9,10 @@ pub mod errors;
 pub mod file_server;
 pub mod http;
 mod post_policy;
+pub mod registration;
 pub mod server;
+pub mod utils;
+pub mod validation;
 
 #[cfg(test)]
 extern crate ctor;
@@ -0,0 +1,63 @@
+use crate::errors::VaultError;
+use subtle::ConstantTimeEq;
+
+/// Who is allowed to create a new account through `POST /users/`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum RegistrationMode {
+    /// Anyone can register
+    Open,
+    /// Nobody can register, users must be added by an operator
+    #[default]
+    Closed,
+    /// Only who knows the invite code can register
+    InviteCode(String),
+}
+
+impl RegistrationMode {
+    /// Check if a registration attempt, carrying an optional invite code, is allowed.
+    pub fn check(&self, invite_code: Option<&str>) -> Result<(), VaultError> {
+        match self {
+            RegistrationMode::Open => Ok(()),
+            RegistrationMode::Closed => Err(VaultError::RegistrationClosed),
+            RegistrationMode::InviteCode(expected) => match invite_code {
+                Some(code) if bool::from(code.as_bytes().ct_eq(expected.as_bytes())) => Ok(()),
+                _ => Err(VaultError::InvalidInviteCode),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RegistrationMode;
+    use crate::errors::VaultError;
+
+    #[test]
+    fn open_registration_accepts_anyone() {
+        assert!(RegistrationMode::Open.check(None).is_ok());
+        assert!(RegistrationMode::Open.check(Some("whatever")).is_ok());
+    }
+
+    #[test]
+    fn closed_registration_rejects_everyone() {
+        assert!(matches!(
+            RegistrationMode::Closed.check(Some("whatever")),
+            Err(VaultError::RegistrationClosed)
+        ));
+    }
+
+    #[test]
+    fn invite_registration_requires_the_right_code() {
+        let mode = RegistrationMode::InviteCode("s3cr3t".to_owned());
+
+        assert!(mode.check(Some("s3cr3t")).is_ok());
+        assert!(matches!(
+            mode.check(Some("wrong")),
+            Err(VaultError::InvalidInviteCode)
+        ));
+        assert!(matches!(
+            mode.check(None),
+            Err(VaultError::InvalidInviteCode)
+        ));
+    }
+}
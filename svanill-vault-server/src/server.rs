@@ -1,33 +1,148 @@
 use actix_web::dev::Server;
-use actix_web::middleware::{ErrorHandlers, Logger};
+use actix_web::middleware::{from_fn, ErrorHandlers, Logger};
 use actix_web::{http, web, App, HttpServer};
 
-use crate::auth::tokens_cache::TokensCache;
+use crate::auth::decoy_challenge::DecoyChallenges;
+use crate::auth::login_throttle::{LoginThrottle, SharedLoginThrottle};
+use crate::auth::token_keys::TokenKeys;
+use crate::auth::token_store::{SharedTokenStore, TokenStore};
+use crate::auth::TokenMode;
+use crate::db;
+use crate::errors::VaultError;
 use crate::file_server::FileServer;
 use crate::http::handlers::{config_handlers, render_40x, render_500};
+use crate::http::request_id::{request_id_middleware, REQUEST_ID_HEADER};
+use crate::http::tokens::TrustedProxy;
+use crate::registration::RegistrationMode;
+use crate::utils::{unix_timestamp, unix_timestamp_ms};
 use diesel::{
     r2d2::{self, ConnectionManager},
     SqliteConnection,
 };
 use r2d2::Pool;
-use ring::hmac;
 use std::net::TcpListener;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 pub struct AppData {
-    pub tokens_cache: TokensCache,
-    pub crypto_key: hmac::Key,
+    pub token_store: Box<dyn TokenStore>,
+    pub token_keys: TokenKeys,
+    pub token_mode: TokenMode,
     pub pool: Pool<ConnectionManager<SqliteConnection>>,
-    pub s3_fs: FileServer,
+    pub file_server: FileServer,
     pub cors_origin: String,
+    pub registration: RegistrationMode,
+    pub login_throttle: LoginThrottle,
+    pub decoy_challenges: Option<DecoyChallenges>,
+    /// How long the audit entries are kept, forever if None
+    pub audit_retention: Option<Duration>,
+    /// Take the client ip from the headers set by a reverse proxy
+    pub trusted_proxy: bool,
+}
+
+/// How often the stale tokens are removed from the token store
+const TOKENS_PURGE_INTERVAL: Duration = Duration::from_secs(60);
+
+fn spawn_tokens_purge(token_store: SharedTokenStore) {
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(TOKENS_PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let token_store = token_store.clone();
+            match web::block(move || token_store.purge_expired()).await {
+                Ok(Ok(0)) => (),
+                Ok(Ok(purged)) => log::debug!("Purged {purged} stale tokens"),
+                Ok(Err(e)) => log::error!("Cannot purge stale tokens: {e}"),
+                Err(e) => log::error!("Cannot purge stale tokens: {e}"),
+            }
+        }
+    });
+}
+
+/// Periodically remove the revocations of signed tokens that expired anyway
+fn spawn_revocations_purge(pool: Pool<ConnectionManager<SqliteConnection>>, lifetime: Duration) {
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(TOKENS_PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let pool = pool.clone();
+            let purge = web::block(move || {
+                let mut conn = pool
+                    .get()
+                    .map_err(|e| VaultError::UnexpectedError(e.to_string()))?;
+                db::actions::delete_stale_revocations(
+                    &mut conn,
+                    unix_timestamp_ms(),
+                    lifetime.as_millis() as i64,
+                )
+            });
+
+            match purge.await {
+                Ok(Ok(0)) => (),
+                Ok(Ok(purged)) => log::debug!("Purged {purged} stale token revocations"),
+                Ok(Err(e)) => log::error!("Cannot purge stale token revocations: {e}"),
+                Err(e) => log::error!("Cannot purge stale token revocations: {e}"),
+            }
+        }
+    });
+}
+
+/// How often the audit entries past the retention period are removed
+const AUDIT_LOG_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Periodically remove the audit entries older than the retention period
+fn spawn_audit_log_purge(pool: Pool<ConnectionManager<SqliteConnection>>, retention: Duration) {
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(AUDIT_LOG_PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let pool = pool.clone();
+            let purge = web::block(move || {
+                let mut conn = pool
+                    .get()
+                    .map_err(|e| VaultError::UnexpectedError(e.to_string()))?;
+                db::actions::delete_audit_entries_before(
+                    &mut conn,
+                    unix_timestamp() - retention.as_secs() as i64,
+                )
+            });
+
+            match purge.await {
+                Ok(Ok(0)) => (),
+                Ok(Ok(purged)) => log::debug!("Purged {purged} old audit entries"),
+                Ok(Err(e)) => log::error!("Cannot purge old audit entries: {e}"),
+                Err(e) => log::error!("Cannot purge old audit entries: {e}"),
+            }
+        }
+    });
 }
 
 pub fn run(listener: TcpListener, data: AppData) -> Result<Server, std::io::Error> {
-    let tokens_cache = Arc::new(RwLock::new(data.tokens_cache));
-    let crypto_key = Arc::new(data.crypto_key);
+    let token_store: SharedTokenStore = Arc::from(data.token_store);
+    let token_keys = Arc::new(data.token_keys);
+    let token_mode = data.token_mode;
     let pool = data.pool;
-    let s3_fs = Arc::new(data.s3_fs);
+    let file_server = Arc::new(data.file_server);
     let cors_origin = data.cors_origin;
+    let registration = data.registration;
+    let decoy_challenges = Arc::new(data.decoy_challenges);
+    let login_throttle: SharedLoginThrottle = Arc::new(Mutex::new(data.login_throttle));
+    let trusted_proxy = TrustedProxy(data.trusted_proxy);
+
+    match token_mode {
+        TokenMode::Opaque => spawn_tokens_purge(token_store.clone()),
+        TokenMode::Signed { lifetime, .. } => spawn_revocations_purge(pool.clone(), lifetime),
+    }
+
+    if let Some(retention) = data.audit_retention {
+        spawn_audit_log_purge(pool.clone(), retention);
+    }
 
     let server = HttpServer::new(move || {
         let cors_origin = &cors_origin;
@@ -38,7 +153,11 @@ pub fn run(listener: TcpListener, data: AppData) -> Result<Server, std::io::Erro
                 http::header::AUTHORIZATION,
                 http::header::ACCEPT,
                 http::header::CONTENT_TYPE,
+                http::header::HeaderName::from_static(REQUEST_ID_HEADER),
             ])
+            .expose_headers(vec![http::header::HeaderName::from_static(
+                REQUEST_ID_HEADER,
+            )])
             .max_age(86400);
 
         cors_handler = if cors_origin == "*" {
@@ -49,12 +168,18 @@ pub fn run(listener: TcpListener, data: AppData) -> Result<Server, std::io::Erro
 
         App::new()
             .app_data(web::Data::new(pool.clone()))
-            .app_data(web::Data::new(crypto_key.clone()))
-            .app_data(web::Data::new(tokens_cache.clone()))
-            .app_data(web::Data::new(s3_fs.clone()))
+            .app_data(web::Data::new(token_keys.clone()))
+            .app_data(web::Data::new(token_mode))
+            .app_data(web::Data::new(token_store.clone()))
+            .app_data(web::Data::new(file_server.clone()))
+            .app_data(web::Data::new(registration.clone()))
+            .app_data(web::Data::new(login_throttle.clone()))
+            .app_data(web::Data::new(decoy_challenges.clone()))
+            .app_data(web::Data::new(trusted_proxy))
             .wrap(ErrorHandlers::new().handler(http::StatusCode::INTERNAL_SERVER_ERROR, render_500))
             .wrap(ErrorHandlers::new().handler(http::StatusCode::BAD_REQUEST, render_40x))
             .wrap(Logger::default())
+            .wrap(from_fn(request_id_middleware))
             .wrap(cors_handler)
             .wrap(sentry_actix::Sentry::with_transaction())
             .configure(config_handlers)
@@ -0,0 +1,211 @@
+use crate::errors::VaultError;
+use unicode_normalization::UnicodeNormalization;
+
+const USERNAME_MAX_LENGTH: usize = 50;
+const KEY_ID_MAX_LENGTH: usize = 50;
+/// Longest filename, in bytes once normalized
+const FILENAME_MAX_LENGTH: usize = 255;
+
+/// Names of devices on Windows, that can't be used as file names
+/// (even with an extension) once the files are pulled there
+const RESERVED_FILENAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Check that a username is made of one or more ascii letters or numbers,
+/// underscores and hyphens, starting with a letter or a number.
+pub fn validate_username(username: &str) -> Result<(), VaultError> {
+    validate_identifier("username", username, USERNAME_MAX_LENGTH)
+}
+
+/// Check that the id of a user's key follows the same rules of the usernames.
+pub fn validate_key_id(key_id: &str) -> Result<(), VaultError> {
+    validate_identifier("key_id", key_id, KEY_ID_MAX_LENGTH)
+}
+
+/// Check that a filename can be stored, and return it in Unicode
+/// normalization form C, so that the same name always refers to the same file.
+///
+/// A `/` separates the directories: every part of the name must be
+/// non-empty, and can't be `.`, `..` or a reserved name. Control
+/// characters and backslashes are not allowed.
+pub fn validate_filename(filename: &str) -> Result<String, VaultError> {
+    let invalid = |reason: String| VaultError::InvalidField {
+        field: "filename".into(),
+        reason,
+    };
+
+    if filename.is_empty() {
+        return Err(VaultError::FieldRequired {
+            field: "filename".into(),
+        });
+    }
+
+    let filename: String = filename.nfc().collect();
+
+    if filename.len() > FILENAME_MAX_LENGTH {
+        return Err(invalid(format!(
+            "must be at most {FILENAME_MAX_LENGTH} bytes long"
+        )));
+    }
+
+    if filename.chars().any(|c| c.is_control() || c == '\\') {
+        return Err(invalid(
+            "control characters and backslashes are not allowed".into(),
+        ));
+    }
+
+    for part in filename.split('/') {
+        if part.is_empty() {
+            return Err(invalid(
+                "must not start or end with a /, nor contain //".into(),
+            ));
+        }
+
+        if part == "." || part == ".." {
+            return Err(invalid(format!("{part} is not allowed as a name")));
+        }
+
+        let stem = part.split('.').next().unwrap_or_default();
+        if RESERVED_FILENAMES
+            .iter()
+            .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+        {
+            return Err(invalid(format!("{part} is a reserved name")));
+        }
+    }
+
+    Ok(filename)
+}
+
+fn validate_identifier(field: &str, value: &str, max_length: usize) -> Result<(), VaultError> {
+    let invalid = |reason: &str| VaultError::InvalidField {
+        field: field.into(),
+        reason: reason.into(),
+    };
+
+    let first = match value.chars().next() {
+        None => {
+            return Err(VaultError::FieldRequired {
+                field: field.into(),
+            })
+        }
+        Some(c) => c,
+    };
+
+    if value.len() > max_length {
+        return Err(invalid("too long"));
+    }
+
+    if !first.is_ascii_alphanumeric() {
+        return Err(invalid("must start with a letter or a number"));
+    }
+
+    if !value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(invalid(
+            "only ascii letters, numbers, underscores and hyphens are allowed",
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_filename, validate_key_id, validate_username};
+    use crate::errors::VaultError;
+
+    #[test]
+    fn accept_valid_usernames() {
+        assert!(validate_username("michael-walsh").is_ok());
+        assert!(validate_username("0_user-1").is_ok());
+        assert!(validate_username("x").is_ok());
+    }
+
+    #[test]
+    fn empty_username_is_required() {
+        assert!(matches!(
+            validate_username(""),
+            Err(VaultError::FieldRequired { .. })
+        ));
+    }
+
+    #[test]
+    fn reject_invalid_usernames() {
+        for username in ["-foo", "_foo", "foo bar", "foo/bar", "fòo", &"a".repeat(51)] {
+            assert!(
+                matches!(
+                    validate_username(username),
+                    Err(VaultError::InvalidField { .. })
+                ),
+                "{username} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn key_id_follows_the_username_rules() {
+        assert!(validate_key_id("laptop-2").is_ok());
+        assert!(matches!(
+            validate_key_id(""),
+            Err(VaultError::FieldRequired { field }) if field == "key_id"
+        ));
+        assert!(matches!(
+            validate_key_id("my phone"),
+            Err(VaultError::InvalidField { field, .. }) if field == "key_id"
+        ));
+    }
+
+    #[test]
+    fn accept_valid_filenames() {
+        for filename in [
+            "secrets",
+            "dir/sub dir/file.txt",
+            "a&b#c?d%e",
+            "console",
+            "laptop-com1",
+        ] {
+            assert_eq!(filename, validate_filename(filename).unwrap());
+        }
+    }
+
+    #[test]
+    fn normalize_filenames() {
+        // "é" as "e" followed by the combining acute accent
+        assert_eq!("caf\u{e9}", validate_filename("cafe\u{301}").unwrap());
+    }
+
+    #[test]
+    fn reject_invalid_filenames() {
+        assert!(matches!(
+            validate_filename(""),
+            Err(VaultError::FieldRequired { field }) if field == "filename"
+        ));
+
+        for filename in [
+            "../secrets",
+            "dir/./secrets",
+            "/secrets",
+            "dir/",
+            "dir//secrets",
+            "new\nline",
+            "nul\0byte",
+            "back\\slash",
+            "CON",
+            "dir/lpt1.txt",
+            &"a".repeat(256),
+        ] {
+            assert!(
+                matches!(
+                    validate_filename(filename),
+                    Err(VaultError::InvalidField { field, .. }) if field == "filename"
+                ),
+                "{filename:?} should be invalid"
+            );
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// A db file in the temp dir, removed when dropped
+struct TempDb(PathBuf);
+
+impl TempDb {
+    fn new(name: &str) -> TempDb {
+        let path = std::env::temp_dir().join(format!(
+            "svanill-vault-server-{}-{}.db",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_file(&path);
+        TempDb(path)
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn run(db: &TempDb, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_svanill-vault-server"))
+        .arg("-q")
+        .arg("-d")
+        .arg(&db.0)
+        .args(args)
+        .env("RUST_BACKTRACE", "0")
+        .env_remove("SVANILL_VAULT_S3_BUCKET")
+        .env_remove("SVANILL_VAULT_STORAGE")
+        .env_remove("SVANILL_VAULT_STORAGE_DIR")
+        .env_remove("SVANILL_VAULT_REGISTRATION")
+        .env_remove("SVANILL_VAULT_INVITE_CODE")
+        .output()
+        .expect("failed to run svanill-vault-server")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    assert!(!output.status.success());
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn migrate_and_show_the_status() {
+    let db = TempDb::new("migrate");
+
+    let status = stdout(&run(&db, &["migrate", "--status"]));
+    assert!(status.lines().count() > 0);
+    assert!(status.lines().all(|line| line.starts_with("pending\t")));
+
+    let applied = stdout(&run(&db, &["migrate"]));
+    assert_eq!(status.lines().count(), applied.lines().count());
+
+    let status = stdout(&run(&db, &["migrate", "--status"]));
+    assert!(status.lines().all(|line| line.starts_with("applied\t")));
+
+    assert_eq!("No pending migrations\n", stdout(&run(&db, &["migrate"])));
+}
+
+#[test]
+fn manage_users() {
+    let db = TempDb::new("users");
+
+    assert_eq!(
+        "Error: The database has pending migrations, run the migrate command first\n",
+        stderr(&run(&db, &["user", "list"]))
+    );
+
+    stdout(&run(&db, &["migrate"]));
+
+    let add = |username: &str, extra: &[&str]| {
+        let mut args = vec!["user", "add", username, "--challenge", "c", "--answer", "a"];
+        args.extend_from_slice(extra);
+        run(&db, &args)
+    };
+
+    assert_eq!("Added user bob\n", stdout(&add("bob", &["--admin"])));
+    assert_eq!("Added user eve\n", stdout(&add("eve", &[])));
+    assert_eq!("Error: The user already exists\n", stderr(&add("eve", &[])));
+
+    assert_eq!(
+        "Disabled user eve\n",
+        stdout(&run(&db, &["user", "disable", "eve"]))
+    );
+    assert_eq!(
+        "bob\tadmin\tenabled\neve\tuser\tdisabled\n",
+        stdout(&run(&db, &["user", "list"]))
+    );
+
+    stdout(&run(
+        &db,
+        &["user", "disable", "eve", "--reason", "on leave"],
+    ));
+    assert_eq!(
+        "bob\tadmin\tenabled\neve\tuser\tdisabled: on leave\n",
+        stdout(&run(&db, &["user", "list"]))
+    );
+
+    assert_eq!(
+        "Enabled user eve\n",
+        stdout(&run(&db, &["user", "enable", "eve"]))
+    );
+    assert_eq!(
+        "Error: User nobody does not exist\n",
+        stderr(&run(&db, &["user", "disable", "nobody"]))
+    );
+
+    // the files can be removed only when the bucket is known
+    assert_eq!(
+        "Error: --s3-bucket is required to remove the user's files, or pass --keep-files\n",
+        stderr(&run(&db, &["user", "remove", "eve"]))
+    );
+    assert_eq!(
+        "Error: The files kept in memory can't be removed, pass --keep-files\n",
+        stderr(&run(&db, &["--storage", "memory", "user", "remove", "eve"]))
+    );
+    assert_eq!(
+        "Removed user eve\n",
+        stdout(&run(&db, &["user", "remove", "eve", "--keep-files"]))
+    );
+    assert_eq!(
+        "bob\tadmin\tenabled\n",
+        stdout(&run(&db, &["user", "list"]))
+    );
+}
+
+#[test]
+fn remove_the_files_of_a_user_from_the_fs_storage() {
+    let db = TempDb::new("fs-storage");
+    let storage_dir = db.0.with_extension("files");
+    let _ = std::fs::remove_dir_all(&storage_dir);
+
+    stdout(&run(&db, &["migrate"]));
+    stdout(&run(
+        &db,
+        &["user", "add", "eve", "--challenge", "c", "--answer", "a"],
+    ));
+
+    let user_dir = storage_dir.join("users").join("eve");
+    std::fs::create_dir_all(user_dir.join("dir")).unwrap();
+    std::fs::write(user_dir.join("foo"), "some content").unwrap();
+    std::fs::write(user_dir.join("dir").join("bar"), "some content").unwrap();
+
+    assert_eq!(
+        "Error: --storage-dir is required to remove the user's files, or pass --keep-files\n",
+        stderr(&run(&db, &["--storage", "fs", "user", "remove", "eve"]))
+    );
+
+    let storage_dir_arg = storage_dir.to_str().unwrap();
+    assert_eq!(
+        "Removed 2 files\nRemoved user eve\n",
+        stdout(&run(
+            &db,
+            &[
+                "--storage",
+                "fs",
+                "--storage-dir",
+                storage_dir_arg,
+                "user",
+                "remove",
+                "eve"
+            ]
+        ))
+    );
+    assert!(!user_dir.exists());
+
+    let _ = std::fs::remove_dir_all(&storage_dir);
+}
+
+#[test]
+fn refuse_to_serve_with_an_invalid_registration() {
+    let db = TempDb::new("registration");
+
+    let error = stderr(&run(
+        &db,
+        &["--storage", "memory", "--registration", "invite", "serve"],
+    ));
+    assert!(error.contains("--invite-code"), "{}", error);
+
+    let error = stderr(&run(
+        &db,
+        &["--storage", "memory", "--registration", "anyone", "serve"],
+    ));
+    assert!(error.contains("anyone"), "{}", error);
+
+    assert!(!db.0.exists());
+}
+
+#[test]
+fn generate_a_token_key() {
+    let db = TempDb::new("token-key");
+
+    let key = stdout(&run(&db, &["token-key", "generate"]));
+    let key = key.trim_end();
+
+    assert_eq!(64, key.len());
+    assert!(hex::decode(key).is_ok());
+}